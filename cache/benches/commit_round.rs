@@ -0,0 +1,172 @@
+//! What one extension round costs to persist in SQLite: 100 new rows, each
+//! attributed to two engines, plus progress for three. Measured both through
+//! [`SqliteStore::commit_round`]'s batched statements and, as the baseline
+//! they replaced, one statement per row, link and attribution, with engine
+//! ids looked up inside the transaction.
+//!
+//! Run with `cargo bench -p search-cache`.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use search_cache::{CacheStore, NewRow, Progress, RoundWrite, SqliteStore};
+use sqlx::{Sqlite, SqlitePool, Transaction};
+
+const ROWS_PER_ROUND: usize = 100;
+const ENGINES: [&str; 3] = ["Brave", "DuckDuckGo", "Mojeek"];
+
+fn round(query: usize) -> RoundWrite {
+    RoundWrite {
+        fetched_at: chrono::Utc::now().naive_utc(),
+        progress: ENGINES
+            .iter()
+            .map(|engine| {
+                let progress = Progress {
+                    next_start: ROWS_PER_ROUND as i64,
+                    exhausted: false,
+                };
+                (engine.to_string(), progress)
+            })
+            .collect(),
+        attributions: Vec::new(),
+        merged_len: 0,
+        appended: (0..ROWS_PER_ROUND)
+            .map(|i| {
+                let url = format!("https://example.com/{query}/{i}");
+                NewRow {
+                    payload: format!(r#"{{"url":"{url}","title":"Result {i}","snippet":"…"}}"#),
+                    url,
+                    engines: vec![ENGINES[i % 3].to_string(), ENGINES[(i + 1) % 3].to_string()],
+                }
+            })
+            .collect(),
+    }
+}
+
+async fn engine_id(tx: &mut Transaction<'_, Sqlite>, name: &str) -> sqlx::Result<i64> {
+    let existing = sqlx::query_scalar("SELECT id FROM engines WHERE name = ?")
+        .bind(name)
+        .fetch_optional(&mut **tx)
+        .await?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+    Ok(sqlx::query("INSERT INTO engines (name) VALUES (?)")
+        .bind(name)
+        .execute(&mut **tx)
+        .await?
+        .last_insert_rowid())
+}
+
+/// The same writes as [`SqliteStore::commit_round`], one at a time.
+async fn commit_round_row_by_row(
+    pool: &SqlitePool,
+    query_id: i64,
+    round: &RoundWrite,
+) -> sqlx::Result<()> {
+    let mut tx = pool.begin().await?;
+    let namespace_id: i64 =
+        sqlx::query_scalar("UPDATE queries SET fetched_at = ? WHERE id = ? RETURNING namespace_id")
+            .bind(round.fetched_at)
+            .bind(query_id)
+            .fetch_one(&mut *tx)
+            .await?;
+    for (engine, progress) in &round.progress {
+        let engine_id = engine_id(&mut tx, engine).await?;
+        sqlx::query(
+            "INSERT INTO query_engine_progress (query_id, engine_id, next_start, exhausted) \
+             VALUES (?, ?, ?, ?) ON CONFLICT (query_id, engine_id) \
+             DO UPDATE SET next_start = excluded.next_start, exhausted = excluded.exhausted",
+        )
+        .bind(query_id)
+        .bind(engine_id)
+        .bind(progress.next_start)
+        .bind(progress.exhausted)
+        .execute(&mut *tx)
+        .await?;
+    }
+    for (i, row) in round.appended.iter().enumerate() {
+        let inserted =
+            sqlx::query("INSERT OR IGNORE INTO rows (namespace_id, url, payload) VALUES (?, ?, ?)")
+                .bind(namespace_id)
+                .bind(&row.url)
+                .bind(&row.payload)
+                .execute(&mut *tx)
+                .await?;
+        let row_id = match inserted.rows_affected() {
+            0 => {
+                sqlx::query_scalar("SELECT id FROM rows WHERE namespace_id = ? AND url = ?")
+                    .bind(namespace_id)
+                    .bind(&row.url)
+                    .fetch_one(&mut *tx)
+                    .await?
+            }
+            _ => inserted.last_insert_rowid(),
+        };
+        sqlx::query(
+            "INSERT OR IGNORE INTO query_rows (query_id, row_id, merged_index) VALUES (?, ?, ?)",
+        )
+        .bind(query_id)
+        .bind(row_id)
+        .bind(round.merged_len + i as i64)
+        .execute(&mut *tx)
+        .await?;
+        for engine in &row.engines {
+            let engine_id = engine_id(&mut tx, engine).await?;
+            sqlx::query(
+                "INSERT OR IGNORE INTO query_row_engines (query_id, row_id, engine_id) \
+                 VALUES (?, ?, ?)",
+            )
+            .bind(query_id)
+            .bind(row_id)
+            .bind(engine_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+    tx.commit().await
+}
+
+fn commit_round(c: &mut Criterion) {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let dir = std::env::temp_dir().join(format!("search-cache-bench-{}", std::process::id()));
+    let path = dir.join("cache.db");
+    let store = rt.block_on(async {
+        SqliteStore::new(search_cache::open(path.to_str().unwrap()).await.unwrap())
+    });
+    let queries = AtomicUsize::new(0);
+
+    c.bench_function("commit a 100-row round", |b| {
+        b.to_async(&rt).iter(|| async {
+            let n = queries.fetch_add(1, Ordering::Relaxed);
+            let now = chrono::Utc::now().naive_utc();
+            let query_id = store
+                .open_query("bench", &format!("query {n}"), "null", now)
+                .await
+                .unwrap()
+                .id;
+            store.commit_round(query_id, &round(n)).await.unwrap();
+        })
+    });
+
+    c.bench_function("commit a 100-row round, row by row", |b| {
+        b.to_async(&rt).iter(|| async {
+            let n = queries.fetch_add(1, Ordering::Relaxed);
+            let now = chrono::Utc::now().naive_utc();
+            let query_id = store
+                .open_query("bench", &format!("query {n}"), "null", now)
+                .await
+                .unwrap()
+                .id;
+            commit_round_row_by_row(store.pool(), query_id, &round(n))
+                .await
+                .unwrap();
+        })
+    });
+
+    rt.block_on(store.pool().close());
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+criterion_group!(benches, commit_round);
+criterion_main!(benches);
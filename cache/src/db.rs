@@ -1,9 +1,19 @@
-//! Low-level SQLite access. Nothing here knows about ranking or engines —
-//! just namespaces/queries/rows/progress bookkeeping.
-
-use serde::{Serialize, de::DeserializeOwned};
-use sqlx::{Sqlite, SqlitePool, Transaction, sqlite::SqliteConnectOptions};
-use std::{env, str::FromStr, time::Duration};
+//! Low-level SQLite access, and [`SqliteStore`] on top of it. Nothing here
+//! knows about ranking or engines — just namespaces/queries/rows/progress
+//! bookkeeping.
+
+use async_trait::async_trait;
+use sqlx::{QueryBuilder, Sqlite, SqlitePool, Transaction, sqlite::SqliteConnectOptions};
+use std::{
+    collections::{BTreeSet, HashMap},
+    env,
+    ops::Range,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{CacheError, CacheStore, Progress, RoundWrite, Snapshot, StoredRow};
 
 const DEFAULT_SQLITE_DB_NAME: &str = "data/cache.db";
 const SQLITE_DB_ENV: &str = "CACHE_DB_PATH";
@@ -12,17 +22,24 @@ const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
 /// Bumped whenever the schema shape changes. Since this is a pure, disposable,
 /// TTL'd cache (never a source of truth), a version mismatch just drops and
 /// recreates the cache tables instead of running a data migration.
-const SCHEMA_VERSION: i64 = 1;
+const SCHEMA_VERSION: i64 = 4;
 
 pub async fn init() -> Result<SqlitePool, sqlx::Error> {
     let db_path = env::var(SQLITE_DB_ENV).unwrap_or_else(|_| DEFAULT_SQLITE_DB_NAME.to_string());
+    open(&db_path).await
+}
 
-    if let Some(parent) = std::path::Path::new(&db_path).parent().filter(|p| !p.as_os_str().is_empty()) {
+/// Like [`init`], but at an explicit `db_path` rather than `CACHE_DB_PATH`.
+pub async fn open(db_path: &str) -> Result<SqlitePool, sqlx::Error> {
+    if let Some(parent) = std::path::Path::new(&db_path)
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+    {
         std::fs::create_dir_all(parent).expect("failed to create cache db directory");
     }
 
     let options = SqliteConnectOptions::from_str(&format!("sqlite://{db_path}"))
-        .expect("invalid CACHE_DB_PATH")
+        .expect("invalid cache db path")
         .create_if_missing(true)
         .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
         .foreign_keys(true)
@@ -84,14 +101,24 @@ pub(crate) async fn create_schema(pool: &SqlitePool) -> Result<(), sqlx::Error>
             name TEXT NOT NULL UNIQUE
         );
 
+        -- One row per snapshot of a query: `params` is the caller's
+        -- per-search parameters, serialized, so the same query text under
+        -- different parameters (e.g. locales) is a separate entry with its
+        -- own merged order. The query's current snapshot is its newest
+        -- `published` one; a refresh fills a new snapshot unpublished, so
+        -- nobody reads it half-built.
         CREATE TABLE IF NOT EXISTS queries (
             id INTEGER PRIMARY KEY,
             query TEXT NOT NULL,
+            params TEXT NOT NULL,
             namespace_id INTEGER NOT NULL REFERENCES namespaces(id),
+            created_at DATETIME NOT NULL,
             fetched_at DATETIME NOT NULL,
-            UNIQUE (query, namespace_id)
+            published INTEGER NOT NULL DEFAULT 1
         );
 
+        CREATE INDEX IF NOT EXISTS queries_by_key ON queries (query, params, namespace_id);
+
         -- Per-(query, engine) raw pagination progress. Decoupled from the
         -- client-visible merged index in `query_rows` on purpose: an engine's
         -- own offset into its result stream has nothing to do with how many
@@ -104,13 +131,17 @@ pub(crate) async fn create_schema(pool: &SqlitePool) -> Result<(), sqlx::Error>
             PRIMARY KEY (query_id, engine_id)
         );
 
-        -- One row per unique URL, globally reused across every query that
-        -- surfaces it. `payload` is the caller's row type, serialized —
-        -- this crate has no idea what shape it is.
+        -- One row per unique URL within a namespace, reused across every
+        -- query of that namespace that surfaces it. `payload` is the
+        -- caller's row type, serialized — this crate has no idea what shape
+        -- it is, only that each namespace has its own, so the same URL in
+        -- two namespaces is two rows.
         CREATE TABLE IF NOT EXISTS rows (
             id INTEGER PRIMARY KEY,
-            url TEXT NOT NULL UNIQUE,
-            payload TEXT NOT NULL
+            namespace_id INTEGER NOT NULL REFERENCES namespaces(id),
+            url TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            UNIQUE (namespace_id, url)
         );
 
         -- The stable, append-only merged order for a query. `merged_index`
@@ -158,70 +189,136 @@ pub(crate) async fn get_or_create_namespace(
         .last_insert_rowid())
 }
 
-pub(crate) async fn get_or_create_engine(
+/// The newest published snapshot of the query, creating a first one if
+/// there's none.
+pub(crate) async fn get_or_create_query(
     tx: &mut Transaction<'_, Sqlite>,
-    name: &str,
-) -> Result<i64, sqlx::Error> {
-    if let Some((id,)) = sqlx::query_as::<_, (i64,)>("SELECT id FROM engines WHERE name = ?")
-        .bind(name)
-        .fetch_optional(&mut **tx)
-        .await?
+    query: &str,
+    params: &str,
+    namespace_id: i64,
+    fetched_at: chrono::NaiveDateTime,
+) -> Result<Snapshot, sqlx::Error> {
+    if let Some((id, created_at)) = sqlx::query_as::<_, (i64, chrono::NaiveDateTime)>(
+        r#"
+        SELECT id, created_at FROM queries
+        WHERE query = ? AND params = ? AND namespace_id = ? AND published = 1
+        ORDER BY id DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(query)
+    .bind(params)
+    .bind(namespace_id)
+    .fetch_optional(&mut **tx)
+    .await?
     {
-        return Ok(id);
+        return Ok(Snapshot { id, created_at });
     }
-    Ok(sqlx::query("INSERT INTO engines (name) VALUES (?)")
-        .bind(name)
-        .execute(&mut **tx)
-        .await?
-        .last_insert_rowid())
+    create_query(tx, query, params, namespace_id, fetched_at, true).await
 }
 
-pub(crate) async fn get_or_create_query(
+pub(crate) async fn create_query(
     tx: &mut Transaction<'_, Sqlite>,
     query: &str,
+    params: &str,
     namespace_id: i64,
     fetched_at: chrono::NaiveDateTime,
-) -> Result<i64, sqlx::Error> {
-    if let Some((id,)) =
-        sqlx::query_as::<_, (i64,)>("SELECT id FROM queries WHERE query = ? AND namespace_id = ?")
-            .bind(query)
-            .bind(namespace_id)
-            .fetch_optional(&mut **tx)
-            .await?
-    {
-        return Ok(id);
-    }
-    Ok(
-        sqlx::query("INSERT INTO queries (query, namespace_id, fetched_at) VALUES (?, ?, ?)")
-            .bind(query)
-            .bind(namespace_id)
-            .bind(fetched_at)
-            .execute(&mut **tx)
-            .await?
-            .last_insert_rowid(),
+    published: bool,
+) -> Result<Snapshot, sqlx::Error> {
+    let id = sqlx::query(
+        r#"
+        INSERT INTO queries (query, params, namespace_id, created_at, fetched_at, published)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(query)
+    .bind(params)
+    .bind(namespace_id)
+    .bind(fetched_at)
+    .bind(fetched_at)
+    .bind(published)
+    .execute(&mut **tx)
+    .await?
+    .last_insert_rowid();
+    Ok(Snapshot {
+        id,
+        created_at: fetched_at,
+    })
+}
+
+/// Published snapshot `id`, provided it's a snapshot of this query.
+pub(crate) async fn find_query(
+    pool: &SqlitePool,
+    namespace: &str,
+    query: &str,
+    params: &str,
+    id: i64,
+) -> Result<Option<Snapshot>, sqlx::Error> {
+    let created_at: Option<chrono::NaiveDateTime> = sqlx::query_scalar(
+        r#"
+        SELECT q.created_at
+        FROM queries q
+        JOIN namespaces n ON n.id = q.namespace_id
+        WHERE q.id = ? AND n.name = ? AND q.query = ? AND q.params = ? AND q.published = 1
+        "#,
     )
+    .bind(id)
+    .bind(namespace)
+    .bind(query)
+    .bind(params)
+    .fetch_optional(pool)
+    .await?;
+    Ok(created_at.map(|created_at| Snapshot { id, created_at }))
+}
+
+pub(crate) async fn publish_query(pool: &SqlitePool, query_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE queries SET published = 1 WHERE id = ?")
+        .bind(query_id)
+        .execute(pool)
+        .await?;
+    Ok(())
 }
 
+/// Deletes an unpublished snapshot. Its `query_rows` and progress go with
+/// it by cascade; engine attribution has no foreign key, so it's deleted
+/// here.
+pub(crate) async fn discard_query(pool: &SqlitePool, query_id: i64) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let deleted = sqlx::query("DELETE FROM queries WHERE id = ? AND published = 0")
+        .bind(query_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    if deleted > 0 {
+        sqlx::query("DELETE FROM query_row_engines WHERE query_id = ?")
+            .bind(query_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Returns the query's namespace id.
 pub(crate) async fn touch_query(
     tx: &mut Transaction<'_, Sqlite>,
     query_id: i64,
     fetched_at: chrono::NaiveDateTime,
-) -> Result<(), sqlx::Error> {
-    sqlx::query("UPDATE queries SET fetched_at = ? WHERE id = ?")
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("UPDATE queries SET fetched_at = ? WHERE id = ? RETURNING namespace_id")
         .bind(fetched_at)
         .bind(query_id)
-        .execute(&mut **tx)
-        .await?;
-    Ok(())
+        .fetch_one(&mut **tx)
+        .await
 }
 
-/// `(next_start, exhausted)`, defaulting to `(0, false)` if this engine has
-/// never been queried for this query yet.
+/// Defaults to no progress if this engine has never been queried for this
+/// query yet.
 pub(crate) async fn get_progress(
     pool: &SqlitePool,
     query_id: i64,
     engine_name: &str,
-) -> Result<(i64, bool), sqlx::Error> {
+) -> Result<Progress, sqlx::Error> {
     let row: Option<(i64, i64)> = sqlx::query_as(
         r#"
         SELECT p.next_start, p.exhausted
@@ -235,47 +332,89 @@ pub(crate) async fn get_progress(
     .fetch_optional(pool)
     .await?;
 
-    Ok(row.map(|(n, e)| (n, e != 0)).unwrap_or((0, false)))
+    Ok(row
+        .map(|(next_start, exhausted)| Progress {
+            next_start,
+            exhausted: exhausted != 0,
+        })
+        .unwrap_or_default())
+}
+
+/// One statement's worth of rows for the multi-row writes below: at most
+/// four binds each, well inside SQLite's limit on bound parameters.
+const ROWS_PER_STATEMENT: usize = 500;
+
+/// Ids for every engine in `names`, inserting whichever don't exist yet.
+pub(crate) async fn get_or_create_engines(
+    pool: &SqlitePool,
+    names: &[&str],
+) -> Result<Vec<(String, i64)>, sqlx::Error> {
+    let mut ids = Vec::with_capacity(names.len());
+    for chunk in names.chunks(ROWS_PER_STATEMENT) {
+        let mut insert = QueryBuilder::new("INSERT OR IGNORE INTO engines (name) ");
+        insert.push_values(chunk, |mut row, name| {
+            row.push_bind(*name);
+        });
+        insert.build().execute(pool).await?;
+
+        let mut select = QueryBuilder::new("SELECT name, id FROM engines WHERE name IN (");
+        let mut list = select.separated(", ");
+        for name in chunk {
+            list.push_bind(*name);
+        }
+        list.push_unseparated(")");
+        ids.extend(
+            select
+                .build_query_as::<(String, i64)>()
+                .fetch_all(pool)
+                .await?,
+        );
+    }
+    Ok(ids)
 }
 
 pub(crate) async fn set_progress(
     tx: &mut Transaction<'_, Sqlite>,
     query_id: i64,
-    engine_name: &str,
-    next_start: i64,
-    exhausted: bool,
+    progress: &[(i64, Progress)],
 ) -> Result<(), sqlx::Error> {
-    let engine_id = get_or_create_engine(tx, engine_name).await?;
-    sqlx::query(
-        r#"
-        INSERT INTO query_engine_progress (query_id, engine_id, next_start, exhausted)
-        VALUES (?, ?, ?, ?)
-        ON CONFLICT (query_id, engine_id)
-        DO UPDATE SET next_start = excluded.next_start, exhausted = excluded.exhausted
-        "#,
-    )
-    .bind(query_id)
-    .bind(engine_id)
-    .bind(next_start)
-    .bind(exhausted)
-    .execute(&mut **tx)
-    .await?;
+    for chunk in progress.chunks(ROWS_PER_STATEMENT) {
+        let mut upsert = QueryBuilder::new(
+            "INSERT INTO query_engine_progress (query_id, engine_id, next_start, exhausted) ",
+        );
+        upsert.push_values(chunk, |mut row, (engine_id, progress)| {
+            row.push_bind(query_id)
+                .push_bind(*engine_id)
+                .push_bind(progress.next_start)
+                .push_bind(progress.exhausted);
+        });
+        upsert.push(
+            " ON CONFLICT (query_id, engine_id) \
+             DO UPDATE SET next_start = excluded.next_start, exhausted = excluded.exhausted",
+        );
+        upsert.build().execute(&mut **tx).await?;
+    }
     Ok(())
 }
 
-/// One row of the persisted merged list, with enough identity (`row_id`,
-/// `url`) to attribute newly-discovered engine hits against it later.
-pub(crate) struct MergedRow<R> {
-    pub row_id: i64,
-    pub url: String,
-    pub value: R,
-    pub engines: Vec<String>,
+/// Merged indexes are assigned contiguously from 0, so the highest one
+/// gives the length without counting every row.
+pub(crate) async fn get_merged_len(pool: &SqlitePool, query_id: i64) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COALESCE(MAX(merged_index) + 1, 0) FROM query_rows WHERE query_id = ?",
+    )
+    .bind(query_id)
+    .fetch_one(pool)
+    .await
 }
 
-pub(crate) async fn get_merged_rows<R: DeserializeOwned>(
+/// Rows with `merged_index` in `[start, end)`.
+pub(crate) async fn get_merged_window(
     pool: &SqlitePool,
     query_id: i64,
-) -> Result<Vec<MergedRow<R>>, sqlx::Error> {
+    start: i64,
+    end: i64,
+) -> Result<Vec<StoredRow>, sqlx::Error> {
     let raw: Vec<(i64, i64, String, String, Option<String>)> = sqlx::query_as(
         r#"
         SELECT qr.merged_index, r.id, r.url, r.payload, e.name
@@ -283,15 +422,17 @@ pub(crate) async fn get_merged_rows<R: DeserializeOwned>(
         JOIN rows r ON r.id = qr.row_id
         LEFT JOIN query_row_engines qre ON qre.query_id = qr.query_id AND qre.row_id = qr.row_id
         LEFT JOIN engines e ON e.id = qre.engine_id
-        WHERE qr.query_id = ?
+        WHERE qr.query_id = ? AND qr.merged_index >= ? AND qr.merged_index < ?
         ORDER BY qr.merged_index ASC, e.name ASC
         "#,
     )
     .bind(query_id)
+    .bind(start)
+    .bind(end)
     .fetch_all(pool)
     .await?;
 
-    let mut out: Vec<MergedRow<R>> = Vec::new();
+    let mut out: Vec<StoredRow> = Vec::new();
     for (_merged_index, row_id, url, payload, engine_name) in raw {
         match out.last_mut() {
             Some(last) if last.row_id == row_id => {
@@ -299,77 +440,106 @@ pub(crate) async fn get_merged_rows<R: DeserializeOwned>(
                     last.engines.push(name);
                 }
             }
-            _ => {
-                let value: R = serde_json::from_str(&payload)
-                    .expect("cached payload didn't deserialize as the expected row type");
-                out.push(MergedRow {
-                    row_id,
-                    url,
-                    value,
-                    engines: engine_name.into_iter().collect(),
-                });
-            }
+            _ => out.push(StoredRow {
+                row_id,
+                url,
+                payload,
+                engines: engine_name.into_iter().collect(),
+            }),
         }
     }
 
     Ok(out)
 }
 
-/// Inserts (or reuses) the global `rows` entry for `url`, returning its id.
-pub(crate) async fn get_or_create_row<R: Serialize>(
-    tx: &mut Transaction<'_, Sqlite>,
-    url: &str,
-    value: &R,
-) -> Result<i64, sqlx::Error> {
-    let payload = serde_json::to_string(value).expect("row type must be serializable");
-
-    let res = sqlx::query("INSERT OR IGNORE INTO rows (url, payload) VALUES (?, ?)")
-        .bind(url)
-        .bind(&payload)
-        .execute(&mut **tx)
-        .await?;
+pub(crate) async fn get_merged_urls(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Vec<(String, i64)>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT r.url, r.id
+        FROM query_rows qr
+        JOIN rows r ON r.id = qr.row_id
+        WHERE qr.query_id = ?
+        "#,
+    )
+    .bind(query_id)
+    .fetch_all(pool)
+    .await
+}
 
-    if res.rows_affected() == 0 {
-        let (id,): (i64,) = sqlx::query_as("SELECT id FROM rows WHERE url = ?")
-            .bind(url)
-            .fetch_one(&mut **tx)
+/// Inserts (or reuses) the namespace's `rows` entry for each
+/// `(url, payload)`, returning their ids by URL.
+pub(crate) async fn get_or_create_rows(
+    tx: &mut Transaction<'_, Sqlite>,
+    namespace_id: i64,
+    rows: &[(&str, &str)],
+) -> Result<HashMap<String, i64>, sqlx::Error> {
+    let mut ids = HashMap::with_capacity(rows.len());
+    for chunk in rows.chunks(ROWS_PER_STATEMENT) {
+        let mut insert =
+            QueryBuilder::new("INSERT OR IGNORE INTO rows (namespace_id, url, payload) ");
+        insert.push_values(chunk, |mut row, (url, payload)| {
+            row.push_bind(namespace_id)
+                .push_bind(*url)
+                .push_bind(*payload);
+        });
+        insert.build().execute(&mut **tx).await?;
+
+        let mut select = QueryBuilder::new("SELECT url, id FROM rows WHERE namespace_id = ");
+        select.push_bind(namespace_id).push(" AND url IN (");
+        let mut list = select.separated(", ");
+        for (url, _) in chunk {
+            list.push_bind(*url);
+        }
+        list.push_unseparated(")");
+        let found = select
+            .build_query_as::<(String, i64)>()
+            .fetch_all(&mut **tx)
             .await?;
-        Ok(id)
-    } else {
-        Ok(res.last_insert_rowid())
+        ids.extend(found);
     }
+    Ok(ids)
 }
 
-pub(crate) async fn link_query_row(
+/// Places each `(row_id, merged_index)` in the query's merged order.
+pub(crate) async fn link_query_rows(
     tx: &mut Transaction<'_, Sqlite>,
     query_id: i64,
-    row_id: i64,
-    merged_index: i64,
+    rows: &[(i64, i64)],
 ) -> Result<(), sqlx::Error> {
-    sqlx::query("INSERT OR IGNORE INTO query_rows (query_id, row_id, merged_index) VALUES (?, ?, ?)")
-        .bind(query_id)
-        .bind(row_id)
-        .bind(merged_index)
-        .execute(&mut **tx)
-        .await?;
+    for chunk in rows.chunks(ROWS_PER_STATEMENT) {
+        let mut insert =
+            QueryBuilder::new("INSERT OR IGNORE INTO query_rows (query_id, row_id, merged_index) ");
+        insert.push_values(chunk, |mut row, (row_id, merged_index)| {
+            row.push_bind(query_id)
+                .push_bind(*row_id)
+                .push_bind(*merged_index);
+        });
+        insert.build().execute(&mut **tx).await?;
+    }
     Ok(())
 }
 
-pub(crate) async fn attribute_engine(
+/// Records each `(row_id, engine_id)` as having surfaced that row for the
+/// query.
+pub(crate) async fn attribute_engines(
     tx: &mut Transaction<'_, Sqlite>,
     query_id: i64,
-    row_id: i64,
-    engine_name: &str,
+    attributions: &[(i64, i64)],
 ) -> Result<(), sqlx::Error> {
-    let engine_id = get_or_create_engine(tx, engine_name).await?;
-    sqlx::query(
-        "INSERT OR IGNORE INTO query_row_engines (query_id, row_id, engine_id) VALUES (?, ?, ?)",
-    )
-    .bind(query_id)
-    .bind(row_id)
-    .bind(engine_id)
-    .execute(&mut **tx)
-    .await?;
+    for chunk in attributions.chunks(ROWS_PER_STATEMENT) {
+        let mut insert = QueryBuilder::new(
+            "INSERT OR IGNORE INTO query_row_engines (query_id, row_id, engine_id) ",
+        );
+        insert.push_values(chunk, |mut row, (row_id, engine_id)| {
+            row.push_bind(query_id)
+                .push_bind(*row_id)
+                .push_bind(*engine_id);
+        });
+        insert.build().execute(&mut **tx).await?;
+    }
     Ok(())
 }
 
@@ -385,6 +555,12 @@ pub async fn purge_stale_queries(
         .await?
         .rows_affected();
 
+    // SQLite may hand a purged query's id to the next query created, which
+    // mustn't inherit its attribution.
+    sqlx::query("DELETE FROM query_row_engines WHERE query_id NOT IN (SELECT id FROM queries)")
+        .execute(&mut *tx)
+        .await?;
+
     sqlx::query("DELETE FROM rows WHERE id NOT IN (SELECT row_id FROM query_rows)")
         .execute(&mut *tx)
         .await?;
@@ -393,3 +569,221 @@ pub async fn purge_stale_queries(
 
     Ok(purged)
 }
+
+/// [`CacheStore`] over a SQLite pool (see [`open`]): durable, and shareable
+/// between processes pointed at the same file.
+///
+/// Each round is written in one short transaction of multi-row statements,
+/// so rounds from concurrent queries spend little time holding SQLite's
+/// write lock.
+#[derive(Clone)]
+pub struct SqliteStore {
+    pool: SqlitePool,
+    ids: Arc<Mutex<KnownIds>>,
+}
+
+/// Engine and namespace ids by name. Neither table is ever purged, so an id
+/// stays valid once looked up.
+#[derive(Default)]
+struct KnownIds {
+    engines: HashMap<String, i64>,
+    namespaces: HashMap<String, i64>,
+}
+
+impl SqliteStore {
+    /// `pool` must already have the schema — [`init`]/[`open`] take care of
+    /// that.
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool,
+            ids: Arc::default(),
+        }
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// The id of `namespace`, created within `tx` if it doesn't exist yet —
+    /// hence only remembered once `tx` commits, via
+    /// [`remember_namespace`](Self::remember_namespace).
+    async fn namespace_id(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        namespace: &str,
+    ) -> Result<i64, sqlx::Error> {
+        let known = self.ids.lock().unwrap().namespaces.get(namespace).copied();
+        match known {
+            Some(id) => Ok(id),
+            None => get_or_create_namespace(tx, namespace).await,
+        }
+    }
+
+    fn remember_namespace(&self, namespace: &str, id: i64) {
+        let mut known = self.ids.lock().unwrap();
+        known.namespaces.insert(namespace.to_string(), id);
+    }
+
+    /// Ids for `names`, creating the engines that don't exist yet. Runs
+    /// outside any round's transaction, so an id is only remembered once
+    /// it's committed.
+    async fn engine_ids(&self, names: BTreeSet<&str>) -> Result<HashMap<String, i64>, sqlx::Error> {
+        let mut ids = HashMap::with_capacity(names.len());
+        let mut missing = Vec::new();
+        {
+            let known = self.ids.lock().unwrap();
+            for name in names {
+                match known.engines.get(name) {
+                    Some(&id) => {
+                        ids.insert(name.to_string(), id);
+                    }
+                    None => missing.push(name),
+                }
+            }
+        }
+        if !missing.is_empty() {
+            let created = get_or_create_engines(&self.pool, &missing).await?;
+            let mut known = self.ids.lock().unwrap();
+            known.engines.extend(created.iter().cloned());
+            ids.extend(created);
+        }
+        Ok(ids)
+    }
+}
+
+#[async_trait]
+impl CacheStore for SqliteStore {
+    async fn open_query(
+        &self,
+        namespace: &str,
+        query: &str,
+        params: &str,
+        fetched_at: chrono::NaiveDateTime,
+    ) -> Result<Snapshot, CacheError> {
+        let mut tx = self.pool.begin().await?;
+        let namespace_id = self.namespace_id(&mut tx, namespace).await?;
+        let snapshot =
+            get_or_create_query(&mut tx, query, params, namespace_id, fetched_at).await?;
+        tx.commit().await?;
+        self.remember_namespace(namespace, namespace_id);
+        Ok(snapshot)
+    }
+
+    async fn find_snapshot(
+        &self,
+        namespace: &str,
+        query: &str,
+        params: &str,
+        id: i64,
+    ) -> Result<Option<Snapshot>, CacheError> {
+        Ok(find_query(&self.pool, namespace, query, params, id).await?)
+    }
+
+    async fn begin_snapshot(
+        &self,
+        namespace: &str,
+        query: &str,
+        params: &str,
+        fetched_at: chrono::NaiveDateTime,
+    ) -> Result<Snapshot, CacheError> {
+        let mut tx = self.pool.begin().await?;
+        let namespace_id = self.namespace_id(&mut tx, namespace).await?;
+        let snapshot =
+            create_query(&mut tx, query, params, namespace_id, fetched_at, false).await?;
+        tx.commit().await?;
+        self.remember_namespace(namespace, namespace_id);
+        Ok(snapshot)
+    }
+
+    async fn publish_snapshot(&self, id: i64) -> Result<(), CacheError> {
+        Ok(publish_query(&self.pool, id).await?)
+    }
+
+    async fn discard_snapshot(&self, id: i64) -> Result<(), CacheError> {
+        Ok(discard_query(&self.pool, id).await?)
+    }
+
+    async fn progress(&self, query_id: i64, engine: &str) -> Result<Progress, CacheError> {
+        Ok(get_progress(&self.pool, query_id, engine).await?)
+    }
+
+    async fn merged_len(&self, query_id: i64) -> Result<usize, CacheError> {
+        Ok(get_merged_len(&self.pool, query_id).await? as usize)
+    }
+
+    async fn merged_window(
+        &self,
+        query_id: i64,
+        range: Range<usize>,
+    ) -> Result<Vec<StoredRow>, CacheError> {
+        let bound = |i: usize| i64::try_from(i).unwrap_or(i64::MAX);
+        let (start, end) = (bound(range.start), bound(range.end));
+        Ok(get_merged_window(&self.pool, query_id, start, end).await?)
+    }
+
+    async fn merged_urls(&self, query_id: i64) -> Result<HashMap<String, i64>, CacheError> {
+        let urls = get_merged_urls(&self.pool, query_id).await?;
+        Ok(urls.into_iter().collect())
+    }
+
+    async fn commit_round(
+        &self,
+        query_id: i64,
+        round: &RoundWrite,
+    ) -> Result<Vec<i64>, CacheError> {
+        let progress_engines = round.progress.iter().map(|(engine, _)| engine);
+        let rediscovering = round.attributions.iter().map(|(_, engine)| engine);
+        let surfacing = round.appended.iter().flat_map(|row| &row.engines);
+        let names = progress_engines.chain(rediscovering).chain(surfacing);
+        let engine_ids = self.engine_ids(names.map(String::as_str).collect()).await?;
+        let engine_id = |name: &String| engine_ids[name];
+
+        let progress: Vec<(i64, Progress)> = round
+            .progress
+            .iter()
+            .map(|(engine, progress)| (engine_id(engine), *progress))
+            .collect();
+        let rows: Vec<(&str, &str)> = round
+            .appended
+            .iter()
+            .map(|row| (row.url.as_str(), row.payload.as_str()))
+            .collect();
+
+        let mut tx = self.pool.begin().await?;
+        let namespace_id = touch_query(&mut tx, query_id, round.fetched_at).await?;
+        set_progress(&mut tx, query_id, &progress).await?;
+
+        let ids_by_url = get_or_create_rows(&mut tx, namespace_id, &rows).await?;
+        let row_ids: Vec<i64> = round
+            .appended
+            .iter()
+            .map(|row| ids_by_url[&row.url])
+            .collect();
+        let links: Vec<(i64, i64)> = (round.merged_len..)
+            .zip(&row_ids)
+            .map(|(merged_index, row_id)| (*row_id, merged_index))
+            .collect();
+        link_query_rows(&mut tx, query_id, &links).await?;
+
+        let mut attributions: Vec<(i64, i64)> = round
+            .attributions
+            .iter()
+            .map(|(row_id, engine)| (*row_id, engine_id(engine)))
+            .collect();
+        for (row_id, row) in row_ids.iter().zip(&round.appended) {
+            attributions.extend(
+                row.engines
+                    .iter()
+                    .map(|engine| (*row_id, engine_id(engine))),
+            );
+        }
+        attribute_engines(&mut tx, query_id, &attributions).await?;
+        tx.commit().await?;
+
+        Ok(row_ids)
+    }
+
+    async fn purge_stale(&self, cutoff: chrono::NaiveDateTime) -> Result<u64, CacheError> {
+        Ok(purge_stale_queries(&self.pool, cutoff).await?)
+    }
+}
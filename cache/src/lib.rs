@@ -1,5 +1,5 @@
 //! A generic, ranked, append-only merge cache for paginated multi-source
-//! search results, backed by SQLite.
+//! search results, backed by SQLite (or any other [`CacheStore`]).
 //!
 //! This crate has no idea what a "search engine" or a "ranking algorithm"
 //! is — callers bring their own row type ([`CacheableRow`]), their own
@@ -9,25 +9,39 @@
 //! **stable, append-only** order, so a client's `start`/`count` is always a
 //! true index into that order — no drift between a cold fetch and a cache
 //! hit, and no dropped/duplicated pages during pagination.
+//!
+//! A cache can also be told how long a query stays fresh
+//! ([`MergedCache::fresh_for`]); past that, the query is rebuilt in the
+//! background as a new snapshot with an order of its own. Callers paging
+//! an older snapshot pin it by id ([`ExtendResult::snapshot`]), so the
+//! guarantee above holds per snapshot.
 
 mod db;
+mod memory;
+pub mod metrics;
+mod store;
 
 use async_trait::async_trait;
 use serde::{Serialize, de::DeserializeOwned};
 use sqlx::SqlitePool;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fmt,
+    ops::Range,
     sync::{Arc, Mutex as StdMutex},
-    time::Duration,
+    time::{Duration, Instant},
 };
 use tokio::{
-    sync::{Mutex as AsyncMutex, OnceCell},
+    sync::{Mutex as AsyncMutex, OnceCell, mpsc::UnboundedSender},
     task::JoinSet,
-    time::timeout,
+    time::{error::Elapsed, timeout},
 };
 
-pub use db::init;
+pub use db::{SqliteStore, init, open};
+pub use memory::MemoryStore;
+pub use store::{CacheStore, NewRow, Progress, RoundWrite, Snapshot, StoredRow};
+
+use metrics::{CacheObserver, Refresh};
 
 /// Caps rounds of "fetch more, still not enough" per call, so a deep `start`
 /// or a source with broken pagination can't loop forever.
@@ -51,22 +65,70 @@ pub trait Ranker<R: CacheableRow>: Send + Sync {
 /// (e.g. a search engine). Decouples this crate from knowing about any
 /// particular engine. `start` is that source's own raw offset — callers get
 /// it back via [`ExtendResult`]/progress tracking, never derived by the
-/// client.
+/// client. `P` is whatever per-search parameters the caller wants handed
+/// through (language, filters, ...); this crate only ever serializes it, as
+/// part of the cache key.
+// See `CacheStore` for why this allow is needed.
+#[allow(clippy::double_must_use)]
 #[async_trait]
-pub trait EngineSource<R: CacheableRow>: Send + Sync {
+pub trait EngineSource<R: CacheableRow, P = ()>: Send + Sync {
     fn name(&self) -> &'static str;
-    async fn fetch_page(&self, query: &str, start: usize) -> Result<Vec<R>, String>;
+    async fn fetch_page(
+        &self,
+        query: &str,
+        params: &P,
+        start: usize,
+    ) -> Result<Vec<R>, SourceError>;
+}
+
+/// Why one [`EngineSource::fetch_page`] call failed. Deliberately coarse —
+/// just enough for callers to tell a source that's *refusing* them (blocked,
+/// rate limited: back off) apart from one that's broken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceError {
+    /// Served a captcha/bot wall instead of results.
+    Blocked(String),
+    RateLimited {
+        retry_after: Option<Duration>,
+    },
+    /// Any other non-success upstream status.
+    HttpStatus(u16),
+    /// Anything else: network errors, unparseable responses, etc.
+    Other(String),
+}
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceError::Blocked(reason) => write!(f, "blocked: {reason}"),
+            SourceError::RateLimited {
+                retry_after: Some(after),
+            } => write!(f, "rate limited (retry after {}s)", after.as_secs()),
+            SourceError::RateLimited { retry_after: None } => write!(f, "rate limited"),
+            SourceError::HttpStatus(code) => write!(f, "HTTP status {code}"),
+            SourceError::Other(msg) => f.write_str(msg),
+        }
+    }
 }
 
+impl std::error::Error for SourceError {}
+
 #[derive(Debug)]
 pub enum CacheError {
     Sqlx(sqlx::Error),
+    /// Any other [`CacheStore`] implementation's failure.
+    Store(Box<dyn std::error::Error + Send + Sync>),
+    /// A cached row that doesn't decode as the cache's row type — say, one
+    /// written by a build whose row type had a different shape.
+    Payload(serde_json::Error),
 }
 
 impl fmt::Display for CacheError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             CacheError::Sqlx(e) => write!(f, "cache db error: {e}"),
+            CacheError::Store(e) => write!(f, "cache store error: {e}"),
+            CacheError::Payload(e) => write!(f, "cached row didn't decode: {e}"),
         }
     }
 }
@@ -95,12 +157,16 @@ pub struct MergedRowResult<R> {
 #[derive(Debug, Clone)]
 pub enum EngineOutcome {
     Ok,
-    Failed(String),
+    Failed(SourceError),
     TimedOut,
 }
 
 pub struct ExtendResult<R> {
     pub rows: Vec<MergedRowResult<R>>,
+    /// The snapshot `rows` came from. Pass it back when paging on, so later
+    /// pages come from the same merged order even if the query has been
+    /// refreshed in between.
+    pub snapshot: i64,
     /// True if there are more merged rows beyond this slice, or at least one
     /// requested source hasn't yet proven itself exhausted — a real
     /// exhaustion signal, not a "did this page look full" heuristic.
@@ -110,6 +176,61 @@ pub struct ExtendResult<R> {
     pub engine_outcomes: Vec<(String, EngineOutcome)>,
 }
 
+/// What [`MergedCache::stream_extend`] reports while it works, ahead of the
+/// [`ExtendResult`] it finally returns.
+#[derive(Debug, Clone)]
+pub enum ExtendEvent<R> {
+    /// Rows that just landed inside the requested window, in merged order:
+    /// first whatever was already cached, then each source's new rows as
+    /// soon as that source answers. Engine attribution is as of when the
+    /// rows landed — a slower source surfacing the same URL later doesn't
+    /// resend it.
+    Rows(Vec<MergedRowResult<R>>),
+    /// One source's outcome for one round, as soon as it's known.
+    Engine(String, EngineOutcome),
+}
+
+/// One source's answer to one round: its `fetch_page` result, or the
+/// `round_timeout` elapsing first.
+type RoundResult<R> = (&'static str, Result<Result<Vec<R>, SourceError>, Elapsed>);
+
+/// Where one `get_or_extend`/`stream_extend` call has got to, carried from
+/// round to round (and, when streaming, from source to source).
+struct ExtendState<R> {
+    query_id: i64,
+    /// How many rows the merged order holds.
+    len: usize,
+    /// The requested window of the merged order.
+    range: Range<usize>,
+    /// The merged rows inside `range` so far, in order: `window[i]` is
+    /// merged index `range.start + i`.
+    window: Vec<MergedRow<R>>,
+    /// Row id by URL for the whole merged order, to dedupe against — loaded
+    /// by the first round, since a call served from cache never needs it.
+    urls: Option<HashMap<String, i64>>,
+    next_start: HashMap<&'static str, i64>,
+    exhausted: HashMap<&'static str, bool>,
+    engine_outcomes: Vec<(String, EngineOutcome)>,
+}
+
+/// One row of the merged list, with enough identity (`row_id`) to attribute
+/// newly-discovered engine hits against it later.
+struct MergedRow<R> {
+    row_id: i64,
+    value: R,
+    engines: Vec<String>,
+}
+
+impl<R: CacheableRow> MergedRow<R> {
+    fn from_stored(row: StoredRow) -> Result<Self, CacheError> {
+        Ok(Self {
+            row_id: row.row_id,
+            value: serde_json::from_str(&row.payload).map_err(CacheError::Payload)?,
+            engines: row.engines,
+        })
+    }
+}
+
 /// Per-`(namespace, query)` locks so concurrent requests for the same query
 /// (duplicate/overlapping polls from a client) don't both miss the cache and
 /// fire off redundant source requests + concurrent SQLite writes.
@@ -134,6 +255,30 @@ fn release_query_lock(key: &str, lock: Arc<AsyncMutex<()>>) {
     }
 }
 
+/// Queries with a background refresh under way in this process, by the
+/// same key as [`QUERY_LOCKS`], so a burst of requests for a stale query
+/// starts only one.
+static REFRESHING: StdMutex<Option<HashSet<String>>> = StdMutex::new(None);
+
+/// Marks `key` as refreshing until dropped; `None` if it already is.
+struct RefreshGuard(String);
+
+impl RefreshGuard {
+    fn acquire(key: String) -> Option<Self> {
+        let mut guard = REFRESHING.lock().unwrap();
+        let refreshing = guard.get_or_insert_with(HashSet::new);
+        refreshing.insert(key.clone()).then_some(Self(key))
+    }
+}
+
+impl Drop for RefreshGuard {
+    fn drop(&mut self) {
+        if let Some(refreshing) = REFRESHING.lock().unwrap().as_mut() {
+            refreshing.remove(&self.0);
+        }
+    }
+}
+
 static SQLPOOL: OnceCell<SqlitePool> = OnceCell::const_new();
 
 /// Lazily-initialized process-global pool, matching this crate's `init()`.
@@ -145,204 +290,583 @@ pub async fn shared_pool() -> &'static SqlitePool {
         .await
 }
 
-pub async fn clean_cache(pool: &SqlitePool, max_age: Duration) -> Result<u64, CacheError> {
+/// Initializes the pool behind [`shared_pool`] at `db_path` instead of
+/// `CACHE_DB_PATH`. Only takes effect if called before anything else has
+/// used the shared pool.
+pub async fn init_shared_pool(db_path: &str) -> &'static SqlitePool {
+    SQLPOOL
+        .get_or_init(|| async { open(db_path).await.expect("failed to init cache db") })
+        .await
+}
+
+/// Purges snapshots (and their now-orphaned rows) from `store` that haven't
+/// been fetched within `max_age` — including ones a refresh has since
+/// replaced, which until then stay readable for whoever is paging them.
+/// Returns the number of snapshots purged.
+pub async fn clean_cache(store: &dyn CacheStore, max_age: Duration) -> Result<u64, CacheError> {
     let max_age = chrono::Duration::from_std(max_age).unwrap_or(chrono::Duration::MAX);
     let cutoff = chrono::Utc::now().naive_utc() - max_age;
-    Ok(db::purge_stale_queries(pool, cutoff).await?)
+    store.purge_stale(cutoff).await
 }
 
+#[derive(Clone)]
 pub struct MergedCache<R: CacheableRow> {
-    pool: SqlitePool,
+    store: Arc<dyn CacheStore>,
     namespace: &'static str,
     ranker: Arc<dyn Ranker<R>>,
+    fresh_for: Option<Duration>,
+    observer: Arc<dyn CacheObserver>,
 }
 
 impl<R: CacheableRow> MergedCache<R> {
-    pub fn new(pool: SqlitePool, namespace: &'static str, ranker: Arc<dyn Ranker<R>>) -> Self {
+    /// Several caches (one per row type, say) can share a `store` as long as
+    /// their `namespace`s differ.
+    pub fn new(
+        store: Arc<dyn CacheStore>,
+        namespace: &'static str,
+        ranker: Arc<dyn Ranker<R>>,
+    ) -> Self {
         Self {
-            pool,
+            store,
             namespace,
             ranker,
+            fresh_for: None,
+            observer: Arc::new(metrics::Unobserved),
         }
     }
 
+    /// How long a query's first fetch stays fresh. Once its current snapshot
+    /// is older than that, a call reading from it is still answered from
+    /// it straight away, but also starts building a new snapshot in the
+    /// background, which takes over once its first window is fetched.
+    /// Without this, a query serves its first snapshot until it's purged.
+    pub fn fresh_for(mut self, fresh_for: Duration) -> Self {
+        self.fresh_for = Some(fresh_for);
+        self
+    }
+
+    /// Who to tell about fetches, appended rows, cache hits and refreshes,
+    /// to count them. Without this, nobody is told.
+    pub fn observed_by(mut self, observer: Arc<dyn CacheObserver>) -> Self {
+        self.observer = observer;
+        self
+    }
+
     /// Returns rows `[start, start+count)` for `query`, extending the merged
     /// cache from `sources` (each resumed from its own persisted progress)
     /// until the window is satisfied or every source is exhausted.
-    pub async fn get_or_extend(
+    ///
+    /// The same query under different `params` is cached separately — its
+    /// merged order, progress and rows never mix with another `params`'.
+    ///
+    /// `snapshot` pins the call to the [`ExtendResult::snapshot`] of an
+    /// earlier one; `None` (or a snapshot since purged) reads the query's
+    /// current snapshot.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn get_or_extend<P>(
         &self,
         query: &str,
-        sources: &[Arc<dyn EngineSource<R>>],
+        params: &P,
+        sources: &[Arc<dyn EngineSource<R, P>>],
+        snapshot: Option<i64>,
         start: usize,
         count: usize,
         round_timeout: Duration,
-    ) -> Result<ExtendResult<R>, CacheError> {
-        let lock_key = format!("{}:{}", self.namespace, query);
+    ) -> Result<ExtendResult<R>, CacheError>
+    where
+        P: Serialize + Clone + Send + Sync + 'static,
+    {
+        let window = start..start + count;
+        self.extend(
+            query,
+            params,
+            sources,
+            snapshot,
+            window,
+            round_timeout,
+            None,
+        )
+        .await
+    }
+
+    /// Like [`get_or_extend`](Self::get_or_extend), but reports rows and
+    /// per-source outcomes on `events` as they happen instead of only once
+    /// every source in a round has answered. Each source's page is merged
+    /// (ranked and persisted) on its own as soon as it arrives, so rows land
+    /// in arrival order rather than being ranked across the whole round.
+    ///
+    /// Events stop being sent, but the call still runs to completion (and
+    /// fills the cache), if the receiver goes away.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn stream_extend<P>(
+        &self,
+        query: &str,
+        params: &P,
+        sources: &[Arc<dyn EngineSource<R, P>>],
+        snapshot: Option<i64>,
+        start: usize,
+        count: usize,
+        round_timeout: Duration,
+        events: UnboundedSender<ExtendEvent<R>>,
+    ) -> Result<ExtendResult<R>, CacheError>
+    where
+        P: Serialize + Clone + Send + Sync + 'static,
+    {
+        let window = start..start + count;
+        self.extend(
+            query,
+            params,
+            sources,
+            snapshot,
+            window,
+            round_timeout,
+            Some(&events),
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn extend<P>(
+        &self,
+        query: &str,
+        params: &P,
+        sources: &[Arc<dyn EngineSource<R, P>>],
+        snapshot: Option<i64>,
+        range: Range<usize>,
+        round_timeout: Duration,
+        events: Option<&UnboundedSender<ExtendEvent<R>>>,
+    ) -> Result<ExtendResult<R>, CacheError>
+    where
+        P: Serialize + Clone + Send + Sync + 'static,
+    {
+        let params_key = serde_json::to_string(params).expect("params type must be serializable");
+        let lock_key = format!("{}:{}:{}", self.namespace, params_key, query);
         let lock = lock_for_query(lock_key.clone()).await;
         let _guard = lock.lock().await;
 
-        let mut tx = self.pool.begin().await?;
-        let namespace_id = db::get_or_create_namespace(&mut tx, self.namespace).await?;
-        let query_id =
-            db::get_or_create_query(&mut tx, query, namespace_id, chrono::Utc::now().naive_utc())
-                .await?;
-        tx.commit().await?;
+        let now = chrono::Utc::now().naive_utc();
+        let pinned = match snapshot {
+            Some(id) => {
+                self.store
+                    .find_snapshot(self.namespace, query, &params_key, id)
+                    .await?
+            }
+            None => None,
+        };
+        let (snapshot, stale) = match pinned {
+            Some(snapshot) => (snapshot, false),
+            None => {
+                let current = self
+                    .store
+                    .open_query(self.namespace, query, &params_key, now)
+                    .await?;
+                (current, self.is_stale(&current, now))
+            }
+        };
+
+        let count = range.len();
+        let result = self
+            .fill(
+                snapshot.id,
+                query,
+                params,
+                sources,
+                range,
+                round_timeout,
+                events,
+            )
+            .await;
+
+        drop(_guard);
+        release_query_lock(&lock_key, lock);
+        let result = result?;
+
+        let hits = result.rows.iter().filter(|r| r.cached).count();
+        let misses = result.rows.len() - hits;
+        self.observer.returned(self.namespace, hits, misses);
+
+        // Only once stale rows have actually been served: a snapshot that
+        // was empty has just been fetched into, so it's as fresh as can be.
+        if stale && hits > 0 {
+            self.spawn_refresh(
+                query,
+                params,
+                params_key,
+                lock_key,
+                sources,
+                count,
+                round_timeout,
+            );
+        }
 
-        let mut merged = db::get_merged_rows::<R>(&self.pool, query_id).await?;
-        let initial_len = merged.len();
-        let needed_end = start + count;
+        Ok(result)
+    }
+
+    fn is_stale(&self, snapshot: &Snapshot, now: chrono::NaiveDateTime) -> bool {
+        self.fresh_for.is_some_and(|fresh_for| {
+            let fresh_for = chrono::Duration::from_std(fresh_for).unwrap_or(chrono::Duration::MAX);
+            now - snapshot.created_at >= fresh_for
+        })
+    }
 
+    /// Builds a new snapshot of the query in the background, unless one is
+    /// already being built: fetches its first `count` rows and publishes it,
+    /// or drops it if no source had anything (so the stale snapshot at least
+    /// keeps serving).
+    #[allow(clippy::too_many_arguments)]
+    fn spawn_refresh<P>(
+        &self,
+        query: &str,
+        params: &P,
+        params_key: String,
+        lock_key: String,
+        sources: &[Arc<dyn EngineSource<R, P>>],
+        count: usize,
+        round_timeout: Duration,
+    ) where
+        P: Serialize + Clone + Send + Sync + 'static,
+    {
+        let Some(refreshing) = RefreshGuard::acquire(lock_key) else {
+            return;
+        };
+        let cache = self.clone();
+        let query = query.to_string();
+        let params = params.clone();
+        let sources = sources.to_vec();
+        tokio::spawn(async move {
+            let _refreshing = refreshing;
+            let result = cache
+                .refresh(&query, &params, &params_key, &sources, count, round_timeout)
+                .await;
+            let refresh = match result {
+                Ok(true) => Refresh::Published,
+                Ok(false) => Refresh::Discarded,
+                Err(e) => {
+                    log::warn!("refreshing \"{query}\" in {} failed: {e}", cache.namespace);
+                    Refresh::Failed
+                }
+            };
+            cache.observer.refreshed(cache.namespace, refresh);
+        });
+    }
+
+    /// Returns whether the new snapshot was published.
+    async fn refresh<P>(
+        &self,
+        query: &str,
+        params: &P,
+        params_key: &str,
+        sources: &[Arc<dyn EngineSource<R, P>>],
+        count: usize,
+        round_timeout: Duration,
+    ) -> Result<bool, CacheError>
+    where
+        P: Serialize + Clone + Send + Sync + 'static,
+    {
+        let now = chrono::Utc::now().naive_utc();
+        let snapshot = self
+            .store
+            .begin_snapshot(self.namespace, query, params_key, now)
+            .await?;
+        let filled = self
+            .fill(
+                snapshot.id,
+                query,
+                params,
+                sources,
+                0..count,
+                round_timeout,
+                None,
+            )
+            .await;
+        match filled {
+            Ok(result) if !result.rows.is_empty() => {
+                self.store.publish_snapshot(snapshot.id).await?;
+                Ok(true)
+            }
+            Ok(_) => {
+                self.store.discard_snapshot(snapshot.id).await?;
+                Ok(false)
+            }
+            Err(e) => {
+                let _ = self.store.discard_snapshot(snapshot.id).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Returns rows `range` of snapshot `query_id`, extending it from
+    /// `sources` as needed.
+    #[allow(clippy::too_many_arguments)]
+    async fn fill<P>(
+        &self,
+        query_id: i64,
+        query: &str,
+        params: &P,
+        sources: &[Arc<dyn EngineSource<R, P>>],
+        range: Range<usize>,
+        round_timeout: Duration,
+        events: Option<&UnboundedSender<ExtendEvent<R>>>,
+    ) -> Result<ExtendResult<R>, CacheError>
+    where
+        P: Serialize + Clone + Send + Sync + 'static,
+    {
+        let needed_end = range.end;
+        let initial_len = self.store.merged_len(query_id).await?;
+        let window: Vec<MergedRow<R>> = if range.start < initial_len {
+            let stored = self.store.merged_window(query_id, range.clone()).await?;
+            let rows = stored.into_iter().map(MergedRow::from_stored);
+            rows.collect::<Result<_, _>>()?
+        } else {
+            Vec::new()
+        };
         let mut next_start: HashMap<&'static str, i64> = HashMap::new();
         let mut exhausted: HashMap<&'static str, bool> = HashMap::new();
         for src in sources {
-            let (ns, ex) = db::get_progress(&self.pool, query_id, src.name()).await?;
-            next_start.insert(src.name(), ns);
-            exhausted.insert(src.name(), ex);
+            let progress = self.store.progress(query_id, src.name()).await?;
+            next_start.insert(src.name(), progress.next_start);
+            exhausted.insert(src.name(), progress.exhausted);
         }
 
-        let mut engine_outcomes: Vec<(String, EngineOutcome)> = Vec::new();
+        let mut state = ExtendState {
+            query_id,
+            len: initial_len,
+            range,
+            window,
+            urls: None,
+            next_start,
+            exhausted,
+            engine_outcomes: Vec::new(),
+        };
+        if let Some(events) = events {
+            emit_progress(events, &state, (0, 0), initial_len);
+        }
 
         for _round in 0..MAX_ROUNDS {
-            if merged.len() >= needed_end {
+            if state.len >= needed_end {
                 break;
             }
 
-            let needy: Vec<Arc<dyn EngineSource<R>>> = sources
+            let needy: Vec<Arc<dyn EngineSource<R, P>>> = sources
                 .iter()
-                .filter(|s| !exhausted[s.name()])
+                .filter(|s| !state.exhausted[s.name()])
                 .cloned()
                 .collect();
             if needy.is_empty() {
                 break;
             }
 
+            let rows_before = state.len;
             let mut set = JoinSet::new();
             for src in needy {
                 let q = query.to_string();
-                let start_for_src = next_start[src.name()] as usize;
+                let params = params.clone();
+                let start_for_src = state.next_start[src.name()] as usize;
+                let namespace = self.namespace;
+                let observer = self.observer.clone();
                 set.spawn(async move {
-                    let outcome = timeout(round_timeout, src.fetch_page(&q, start_for_src)).await;
+                    let started = Instant::now();
+                    let outcome =
+                        timeout(round_timeout, src.fetch_page(&q, &params, start_for_src)).await;
+                    let reported = match &outcome {
+                        Ok(Ok(_)) => EngineOutcome::Ok,
+                        Ok(Err(e)) => EngineOutcome::Failed(e.clone()),
+                        Err(_) => EngineOutcome::TimedOut,
+                    };
+                    observer.fetched(namespace, src.name(), &reported, started.elapsed());
                     (src.name(), outcome)
                 });
             }
-            let round_results = set.join_all().await;
-
-            let existing_urls: HashMap<String, i64> =
-                merged.iter().map(|r| (r.url.clone(), r.row_id)).collect();
-            let mut batch_index: HashMap<String, usize> = HashMap::new();
-            let mut fresh_batch: Vec<(R, Vec<String>)> = Vec::new();
-            let mut attribute_existing: Vec<(i64, String)> = Vec::new();
-            let mut any_new = false;
-
-            for (name, outcome) in round_results {
-                match outcome {
-                    Ok(Ok(rows)) => {
-                        let raw_count = rows.len();
-                        for row in rows {
-                            let url = row.url().to_string();
-                            if let Some(&row_id) = existing_urls.get(&url) {
-                                attribute_existing.push((row_id, name.to_string()));
-                            } else if let Some(&idx) = batch_index.get(&url) {
-                                fresh_batch[idx].1.push(name.to_string());
-                            } else {
-                                batch_index.insert(url, fresh_batch.len());
-                                fresh_batch.push((row, vec![name.to_string()]));
-                                any_new = true;
-                            }
-                        }
-                        exhausted.insert(name, raw_count == 0);
-                        next_start.insert(name, next_start[name] + raw_count as i64);
-                        engine_outcomes.push((name.to_string(), EngineOutcome::Ok));
-                    }
-                    Ok(Err(e)) => {
-                        log::warn!("source \"{name}\" failed: {e}");
-                        engine_outcomes.push((name.to_string(), EngineOutcome::Failed(e)));
-                    }
-                    Err(_) => {
-                        log::warn!("source \"{name}\" timed out");
-                        engine_outcomes.push((name.to_string(), EngineOutcome::TimedOut));
+
+            let any_new = match events {
+                Some(events) => {
+                    let mut any_new = false;
+                    while let Some(joined) = set.join_next().await {
+                        let result =
+                            joined.unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()));
+                        let before = (state.window.len(), state.engine_outcomes.len());
+                        any_new |= self
+                            .merge_batch(&mut state, query, sources, vec![result])
+                            .await?;
+                        emit_progress(events, &state, before, initial_len);
                     }
+                    any_new
                 }
-            }
+                None => {
+                    let round_results = set.join_all().await;
+                    self.merge_batch(&mut state, query, sources, round_results)
+                        .await?
+                }
+            };
+            let appended = state.len - rows_before;
+            self.observer.appended(self.namespace, appended);
 
-            let mut tx = self.pool.begin().await?;
-            db::touch_query(&mut tx, query_id, chrono::Utc::now().naive_utc()).await?;
-            for src in sources {
-                db::set_progress(
-                    &mut tx,
-                    query_id,
-                    src.name(),
-                    next_start[src.name()],
-                    exhausted[src.name()],
-                )
-                .await?;
+            if !any_new {
+                break;
             }
-            for (row_id, engine_name) in &attribute_existing {
-                db::attribute_engine(&mut tx, query_id, *row_id, engine_name).await?;
+        }
+
+        let has_more = needed_end < state.len || sources.iter().any(|s| !state.exhausted[s.name()]);
+
+        Ok(ExtendResult {
+            rows: window_rows(&state, 0, initial_len),
+            snapshot: query_id,
+            has_more,
+            engine_outcomes: state.engine_outcomes,
+        })
+    }
+
+    /// Dedupes `results` against the merged order and persists them: progress
+    /// for every source, attribution for rediscovered URLs, and the new rows
+    /// (ranked as one batch) appended at the tail. Returns whether any new
+    /// rows were added.
+    async fn merge_batch<P>(
+        &self,
+        state: &mut ExtendState<R>,
+        query: &str,
+        sources: &[Arc<dyn EngineSource<R, P>>],
+        results: Vec<RoundResult<R>>,
+    ) -> Result<bool, CacheError> {
+        let mut existing_urls = match state.urls.take() {
+            Some(urls) => urls,
+            None => self.store.merged_urls(state.query_id).await?,
+        };
+        let mut batch_index: HashMap<String, usize> = HashMap::new();
+        let mut fresh_batch: Vec<(R, Vec<String>)> = Vec::new();
+        let mut attribute_existing: Vec<(i64, String)> = Vec::new();
+        let mut any_new = false;
+
+        for (name, outcome) in results {
+            match outcome {
+                Ok(Ok(rows)) => {
+                    let raw_count = rows.len();
+                    for row in rows {
+                        let url = row.url().to_string();
+                        if let Some(&row_id) = existing_urls.get(&url) {
+                            attribute_existing.push((row_id, name.to_string()));
+                        } else if let Some(&idx) = batch_index.get(&url) {
+                            fresh_batch[idx].1.push(name.to_string());
+                        } else {
+                            batch_index.insert(url, fresh_batch.len());
+                            fresh_batch.push((row, vec![name.to_string()]));
+                            any_new = true;
+                        }
+                    }
+                    state.exhausted.insert(name, raw_count == 0);
+                    state
+                        .next_start
+                        .insert(name, state.next_start[name] + raw_count as i64);
+                    state
+                        .engine_outcomes
+                        .push((name.to_string(), EngineOutcome::Ok));
+                }
+                Ok(Err(e)) => {
+                    log::warn!("source \"{name}\" failed: {e}");
+                    state
+                        .engine_outcomes
+                        .push((name.to_string(), EngineOutcome::Failed(e)));
+                }
+                Err(_) => {
+                    log::warn!("source \"{name}\" timed out");
+                    state
+                        .engine_outcomes
+                        .push((name.to_string(), EngineOutcome::TimedOut));
+                }
             }
+        }
 
-            let mut engines_by_url: HashMap<String, Vec<String>> = fresh_batch
+        let mut engines_by_url: HashMap<String, Vec<String>> = fresh_batch
+            .iter()
+            .map(|(r, e)| (r.url().to_string(), e.clone()))
+            .collect();
+        let ranked_rows = self
+            .ranker
+            .rank(query, fresh_batch.into_iter().map(|(r, _)| r).collect());
+        let appended: Vec<NewRow> = ranked_rows
+            .iter()
+            .map(|row| NewRow {
+                url: row.url().to_string(),
+                payload: serde_json::to_string(row).expect("row type must be serializable"),
+                engines: engines_by_url.remove(row.url()).unwrap_or_default(),
+            })
+            .collect();
+
+        let round = RoundWrite {
+            fetched_at: chrono::Utc::now().naive_utc(),
+            progress: sources
                 .iter()
-                .map(|(r, e)| (r.url().to_string(), e.clone()))
-                .collect();
-            let ranked_rows = self
-                .ranker
-                .rank(query, fresh_batch.into_iter().map(|(r, _)| r).collect());
-
-            let mut next_index = merged.len() as i64;
-            for row in ranked_rows {
-                let url = row.url().to_string();
-                let engines = engines_by_url.remove(&url).unwrap_or_default();
-                let row_id = db::get_or_create_row(&mut tx, &url, &row).await?;
-                db::link_query_row(&mut tx, query_id, row_id, next_index).await?;
-                for engine_name in &engines {
-                    db::attribute_engine(&mut tx, query_id, row_id, engine_name).await?;
-                }
-                merged.push(db::MergedRow {
+                .map(|src| {
+                    let progress = Progress {
+                        next_start: state.next_start[src.name()],
+                        exhausted: state.exhausted[src.name()],
+                    };
+                    (src.name().to_string(), progress)
+                })
+                .collect(),
+            attributions: attribute_existing,
+            merged_len: state.len as i64,
+            appended,
+        };
+        let row_ids = self.store.commit_round(state.query_id, &round).await?;
+
+        for ((row, new_row), row_id) in ranked_rows.into_iter().zip(round.appended).zip(row_ids) {
+            existing_urls.insert(new_row.url, row_id);
+            if state.range.contains(&state.len) {
+                state.window.push(MergedRow {
                     row_id,
-                    url,
                     value: row,
-                    engines,
+                    engines: new_row.engines,
                 });
-                next_index += 1;
-            }
-            tx.commit().await?;
-
-            for (row_id, engine_name) in attribute_existing {
-                if let Some(r) = merged.iter_mut().find(|r| r.row_id == row_id)
-                    && !r.engines.contains(&engine_name)
-                {
-                    r.engines.push(engine_name);
-                }
             }
+            state.len += 1;
+        }
+        state.urls = Some(existing_urls);
 
-            if !any_new {
-                break;
+        for (row_id, engine_name) in round.attributions {
+            if let Some(r) = state.window.iter_mut().find(|r| r.row_id == row_id)
+                && !r.engines.contains(&engine_name)
+            {
+                r.engines.push(engine_name);
             }
         }
 
-        drop(_guard);
-        release_query_lock(&lock_key, lock);
-
-        let end = merged.len().min(needed_end);
-        let start = start.min(end);
-        let has_more = end < merged.len() || sources.iter().any(|s| !exhausted[s.name()]);
-
-        let rows = merged[start..end]
-            .iter()
-            .enumerate()
-            .map(|(i, r)| MergedRowResult {
-                value: r.value.clone(),
-                engines: r.engines.clone(),
-                cached: start + i < initial_len,
-            })
-            .collect();
+        Ok(any_new)
+    }
+}
 
-        Ok(ExtendResult {
-            rows,
-            has_more,
-            engine_outcomes,
+/// `state.window[from..]` as results; rows below merged index `initial_len`
+/// were already cached before this call started.
+fn window_rows<R: CacheableRow>(
+    state: &ExtendState<R>,
+    from: usize,
+    initial_len: usize,
+) -> Vec<MergedRowResult<R>> {
+    let start = state.range.start + from;
+    state.window[from..]
+        .iter()
+        .enumerate()
+        .map(|(i, r)| MergedRowResult {
+            value: r.value.clone(),
+            engines: r.engines.clone(),
+            cached: start + i < initial_len,
         })
+        .collect()
+}
+
+/// Sends whatever `state` gained since `before` (rows in the window,
+/// engine outcomes). Send errors just mean nobody's listening anymore.
+fn emit_progress<R: CacheableRow>(
+    events: &UnboundedSender<ExtendEvent<R>>,
+    state: &ExtendState<R>,
+    (rows_before, outcomes_before): (usize, usize),
+    initial_len: usize,
+) {
+    for (name, outcome) in &state.engine_outcomes[outcomes_before..] {
+        let _ = events.send(ExtendEvent::Engine(name.clone(), outcome.clone()));
+    }
+    if rows_before < state.window.len() {
+        let rows = window_rows(state, rows_before, initial_len);
+        let _ = events.send(ExtendEvent::Rows(rows));
     }
 }
 
@@ -406,19 +930,65 @@ mod test {
             self.name
         }
 
-        async fn fetch_page(&self, _query: &str, _start: usize) -> Result<Vec<TestRow>, String> {
+        async fn fetch_page(
+            &self,
+            _query: &str,
+            _params: &(),
+            _start: usize,
+        ) -> Result<Vec<TestRow>, SourceError> {
             self.calls.fetch_add(1, Ordering::SeqCst);
             Ok(self.pages.lock().unwrap().pop_front().unwrap_or_default())
         }
     }
 
+    /// A source that always fails with the given error.
+    struct FailingSource(SourceError);
+
+    #[async_trait]
+    impl EngineSource<TestRow> for FailingSource {
+        fn name(&self) -> &'static str {
+            "Failing"
+        }
+
+        async fn fetch_page(
+            &self,
+            _query: &str,
+            _params: &(),
+            _start: usize,
+        ) -> Result<Vec<TestRow>, SourceError> {
+            Err(self.0.clone())
+        }
+    }
+
+    /// A source keyed on its params: returns a single row named after them.
+    struct ParamEchoSource;
+
+    #[async_trait]
+    impl EngineSource<TestRow, String> for ParamEchoSource {
+        fn name(&self) -> &'static str {
+            "Echo"
+        }
+
+        async fn fetch_page(
+            &self,
+            _query: &str,
+            params: &String,
+            start: usize,
+        ) -> Result<Vec<TestRow>, SourceError> {
+            if start > 0 {
+                return Ok(Vec::new());
+            }
+            Ok(vec![row(params)])
+        }
+    }
+
     /// An isolated in-memory pool per test — deliberately avoids `db::init`'s
     /// env-var-based file path, since tests run concurrently in one process
     /// and a shared global env var races across threads (each test would
     /// risk connecting to whichever path a *different*, concurrently-running
     /// test just set). `max_connections(1)` keeps every checkout on the same
     /// `:memory:` database instead of each connection getting its own.
-    async fn test_cache() -> MergedCache<TestRow> {
+    async fn test_store() -> SqliteStore {
         let options = sqlx::sqlite::SqliteConnectOptions::from_str("sqlite::memory:")
             .unwrap()
             .foreign_keys(true);
@@ -428,7 +998,11 @@ mod test {
             .await
             .unwrap();
         db::create_schema(&pool).await.unwrap();
-        MergedCache::new(pool, "test", Arc::new(NoopRanker))
+        SqliteStore::new(pool)
+    }
+
+    async fn test_cache() -> MergedCache<TestRow> {
+        MergedCache::new(Arc::new(test_store().await), "test", Arc::new(NoopRanker))
     }
 
     fn sources(v: Vec<Arc<ScriptedSource>>) -> Vec<Arc<dyn EngineSource<TestRow>>> {
@@ -441,13 +1015,129 @@ mod test {
         sources(vec![s])
     }
 
+    #[tokio::test]
+    async fn source_errors_are_reported_without_flattening_them() {
+        let cache = test_cache().await;
+        let error = SourceError::RateLimited {
+            retry_after: Some(Duration::from_secs(30)),
+        };
+        let source: Arc<dyn EngineSource<TestRow>> = Arc::new(FailingSource(error.clone()));
+
+        let result = cache
+            .get_or_extend("q", &(), &[source], None, 0, 3, Duration::from_secs(1))
+            .await
+            .unwrap();
+
+        assert!(result.rows.is_empty());
+        assert!(result.has_more, "a failing source hasn't proven exhaustion");
+        assert!(
+            result
+                .engine_outcomes
+                .iter()
+                .all(|(name, o)| name == "Failing"
+                    && matches!(o, EngineOutcome::Failed(e) if *e == error))
+        );
+    }
+
+    /// Writes down what it's told, one line per call.
+    #[derive(Default)]
+    struct RecordingObserver(StdMutex<Vec<String>>);
+
+    impl CacheObserver for RecordingObserver {
+        fn fetched(&self, namespace: &str, source: &str, outcome: &EngineOutcome, _: Duration) {
+            let outcome = match outcome {
+                EngineOutcome::Ok => "ok",
+                EngineOutcome::Failed(_) => "failed",
+                EngineOutcome::TimedOut => "timed out",
+            };
+            let line = format!("{namespace}: {source} {outcome}");
+            self.0.lock().unwrap().push(line);
+        }
+
+        fn appended(&self, namespace: &str, rows: usize) {
+            let line = format!("{namespace}: appended {rows}");
+            self.0.lock().unwrap().push(line);
+        }
+
+        fn returned(&self, namespace: &str, hits: usize, misses: usize) {
+            let line = format!("{namespace}: {hits} hits, {misses} misses");
+            self.0.lock().unwrap().push(line);
+        }
+    }
+
+    #[tokio::test]
+    async fn fetches_and_cache_hits_are_reported_to_the_observer() {
+        let observer = Arc::new(RecordingObserver::default());
+        let store = Arc::new(MemoryStore::new());
+        let cache =
+            MergedCache::new(store, "observed", Arc::new(NoopRanker)).observed_by(observer.clone());
+        let blocked = SourceError::Blocked("captcha".into());
+        let sources: Vec<Arc<dyn EngineSource<TestRow>>> = vec![
+            ScriptedSource::new("Scripted", vec![vec![row("a"), row("b")]]),
+            Arc::new(FailingSource(blocked)),
+        ];
+
+        for _ in 0..2 {
+            cache
+                .get_or_extend("q", &(), &sources, None, 0, 2, Duration::from_secs(1))
+                .await
+                .unwrap();
+        }
+
+        let mut calls = observer.0.lock().unwrap().clone();
+        // The two sources answer in whichever order they finish.
+        calls[..2].sort();
+        assert_eq!(
+            calls,
+            [
+                "observed: Failing failed",
+                "observed: Scripted ok",
+                "observed: appended 2",
+                "observed: 0 hits, 2 misses",
+                "observed: 2 hits, 0 misses",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn same_query_with_different_params_is_cached_separately() {
+        let cache = test_cache().await;
+        let source: Arc<dyn EngineSource<TestRow, String>> = Arc::new(ParamEchoSource);
+
+        for params in ["en", "de", "en"] {
+            let result = cache
+                .get_or_extend(
+                    "q",
+                    &params.to_string(),
+                    std::slice::from_ref(&source),
+                    None,
+                    0,
+                    5,
+                    Duration::from_secs(1),
+                )
+                .await
+                .unwrap();
+
+            let urls: Vec<_> = result.rows.iter().map(|r| r.value.url.clone()).collect();
+            assert_eq!(urls, vec![row(params).url], "params {params:?}");
+        }
+    }
+
     #[tokio::test]
     async fn fresh_query_reports_has_more_until_a_source_proves_exhaustion() {
         let cache = test_cache().await;
         let source = ScriptedSource::new("A", vec![vec![row("a"), row("b"), row("c")]]);
 
         let result = cache
-            .get_or_extend("q", &one_source(source), 0, 3, Duration::from_secs(1))
+            .get_or_extend(
+                "q",
+                &(),
+                &one_source(source),
+                None,
+                0,
+                3,
+                Duration::from_secs(1),
+            )
             .await
             .unwrap();
 
@@ -473,7 +1163,15 @@ mod test {
         let calls = source.calls.clone();
 
         let page1 = cache
-            .get_or_extend("q", &one_source(source.clone()), 0, 5, Duration::from_secs(1))
+            .get_or_extend(
+                "q",
+                &(),
+                &one_source(source.clone()),
+                None,
+                0,
+                5,
+                Duration::from_secs(1),
+            )
             .await
             .unwrap();
         assert_eq!(page1.rows.len(), 5);
@@ -483,7 +1181,15 @@ mod test {
         // client/process) for page 2 — everything it needs is already in the
         // merged cache from page 1's over-fetch.
         let page2 = cache
-            .get_or_extend("q", &one_source(source.clone()), 5, 5, Duration::from_secs(1))
+            .get_or_extend(
+                "q",
+                &(),
+                &one_source(source.clone()),
+                None,
+                5,
+                5,
+                Duration::from_secs(1),
+            )
             .await
             .unwrap();
 
@@ -501,6 +1207,125 @@ mod test {
         assert_eq!(urls2[0], "u5");
     }
 
+    /// A [`MemoryStore`] that records what the cache reads from it.
+    #[derive(Default)]
+    struct ReadCountingStore {
+        inner: MemoryStore,
+        rows_read: AtomicUsize,
+        url_loads: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl CacheStore for ReadCountingStore {
+        async fn open_query(
+            &self,
+            namespace: &str,
+            query: &str,
+            params: &str,
+            fetched_at: chrono::NaiveDateTime,
+        ) -> Result<Snapshot, CacheError> {
+            let inner = &self.inner;
+            inner.open_query(namespace, query, params, fetched_at).await
+        }
+
+        async fn find_snapshot(
+            &self,
+            namespace: &str,
+            query: &str,
+            params: &str,
+            id: i64,
+        ) -> Result<Option<Snapshot>, CacheError> {
+            self.inner.find_snapshot(namespace, query, params, id).await
+        }
+
+        async fn begin_snapshot(
+            &self,
+            namespace: &str,
+            query: &str,
+            params: &str,
+            fetched_at: chrono::NaiveDateTime,
+        ) -> Result<Snapshot, CacheError> {
+            let inner = &self.inner;
+            inner
+                .begin_snapshot(namespace, query, params, fetched_at)
+                .await
+        }
+
+        async fn publish_snapshot(&self, id: i64) -> Result<(), CacheError> {
+            self.inner.publish_snapshot(id).await
+        }
+
+        async fn discard_snapshot(&self, id: i64) -> Result<(), CacheError> {
+            self.inner.discard_snapshot(id).await
+        }
+
+        async fn progress(&self, query_id: i64, engine: &str) -> Result<Progress, CacheError> {
+            self.inner.progress(query_id, engine).await
+        }
+
+        async fn merged_len(&self, query_id: i64) -> Result<usize, CacheError> {
+            self.inner.merged_len(query_id).await
+        }
+
+        async fn merged_window(
+            &self,
+            query_id: i64,
+            range: Range<usize>,
+        ) -> Result<Vec<StoredRow>, CacheError> {
+            let rows = self.inner.merged_window(query_id, range).await?;
+            self.rows_read.fetch_add(rows.len(), Ordering::SeqCst);
+            Ok(rows)
+        }
+
+        async fn merged_urls(&self, query_id: i64) -> Result<HashMap<String, i64>, CacheError> {
+            self.url_loads.fetch_add(1, Ordering::SeqCst);
+            self.inner.merged_urls(query_id).await
+        }
+
+        async fn commit_round(
+            &self,
+            query_id: i64,
+            round: &RoundWrite,
+        ) -> Result<Vec<i64>, CacheError> {
+            self.inner.commit_round(query_id, round).await
+        }
+
+        async fn purge_stale(&self, cutoff: chrono::NaiveDateTime) -> Result<u64, CacheError> {
+            self.inner.purge_stale(cutoff).await
+        }
+    }
+
+    #[tokio::test]
+    async fn cached_pages_only_read_their_own_window() {
+        let store = Arc::new(ReadCountingStore::default());
+        let cache = MergedCache::new(store.clone(), "test", Arc::new(NoopRanker));
+        let source =
+            ScriptedSource::new("A", vec![(0..30).map(|i| row(&format!("u{i}"))).collect()]);
+        let get = |start| {
+            let (cache, sources) = (&cache, one_source(source.clone()));
+            async move {
+                cache
+                    .get_or_extend("q", &(), &sources, None, start, 5, Duration::from_secs(1))
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let url_loads = || store.url_loads.load(Ordering::SeqCst);
+        let rows_read = || store.rows_read.load(Ordering::SeqCst);
+
+        get(0).await;
+        assert_eq!(url_loads(), 1, "the round dedupes");
+        assert_eq!(rows_read(), 0, "nothing cached yet");
+
+        let page = get(20).await;
+        let urls: Vec<_> = page.rows.iter().map(|r| r.value.url.as_str()).collect();
+        assert_eq!(urls, ["u20", "u21", "u22", "u23", "u24"]);
+        assert!(page.rows.iter().all(|r| r.cached));
+        assert_eq!(rows_read(), 5);
+        assert_eq!(url_loads(), 1, "no round, no URL set");
+    }
+
     #[tokio::test]
     async fn has_more_reflects_the_source_that_is_still_alive() {
         let cache = test_cache().await;
@@ -513,7 +1338,9 @@ mod test {
         let result = cache
             .get_or_extend(
                 "q",
+                &(),
                 &sources(vec![short, long]),
+                None,
                 0,
                 5,
                 Duration::from_secs(1),
@@ -532,7 +1359,15 @@ mod test {
         let b = ScriptedSource::new("B", vec![vec![row("shared")], vec![]]);
 
         let result = cache
-            .get_or_extend("q", &sources(vec![a, b]), 0, 1, Duration::from_secs(1))
+            .get_or_extend(
+                "q",
+                &(),
+                &sources(vec![a, b]),
+                None,
+                0,
+                1,
+                Duration::from_secs(1),
+            )
             .await
             .unwrap();
 
@@ -545,7 +1380,7 @@ mod test {
         // attributes to the existing row instead of duplicating it.
         let c = ScriptedSource::new("C", vec![vec![row("shared")]]);
         let result2 = cache
-            .get_or_extend("q", &one_source(c), 1, 1, Duration::from_secs(1))
+            .get_or_extend("q", &(), &one_source(c), None, 1, 1, Duration::from_secs(1))
             .await
             .unwrap();
         assert_eq!(result2.rows.len(), 0, "no new merged row — it's the same URL");
@@ -553,7 +1388,9 @@ mod test {
         let result3 = cache
             .get_or_extend(
                 "q",
+                &(),
                 &one_source(ScriptedSource::new("D", vec![])),
+                None,
                 0,
                 1,
                 Duration::from_secs(1),
@@ -567,36 +1404,213 @@ mod test {
 
     #[tokio::test]
     async fn purge_stale_removes_old_queries_and_orphaned_rows() {
-        let cache = test_cache().await;
+        let store = Arc::new(test_store().await);
+        let cache = MergedCache::new(store.clone(), "test", Arc::new(NoopRanker));
         let source = ScriptedSource::new("A", vec![vec![row("old")]]);
         cache
-            .get_or_extend("stale query", &one_source(source), 0, 1, Duration::from_secs(1))
+            .get_or_extend(
+                "stale query",
+                &(),
+                &one_source(source),
+                None,
+                0,
+                1,
+                Duration::from_secs(1),
+            )
             .await
             .unwrap();
 
         // Backdate it directly, then purge with a cutoff that only catches it.
         let (query_id,): (i64,) = sqlx::query_as("SELECT id FROM queries WHERE query = 'stale query'")
-            .fetch_one(&cache.pool)
+            .fetch_one(store.pool())
             .await
             .unwrap();
         sqlx::query("UPDATE queries SET fetched_at = ? WHERE id = ?")
             .bind(chrono::Utc::now().naive_utc() - chrono::Duration::days(30))
             .bind(query_id)
-            .execute(&cache.pool)
+            .execute(store.pool())
             .await
             .unwrap();
 
         let cutoff = chrono::Utc::now().naive_utc() - chrono::Duration::days(7);
-        let purged = db::purge_stale_queries(&cache.pool, cutoff).await.unwrap();
+        let purged = store.purge_stale(cutoff).await.unwrap();
         assert_eq!(purged, 1);
 
         let (row_count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM rows")
-            .fetch_one(&cache.pool)
+            .fetch_one(store.pool())
             .await
             .unwrap();
         assert_eq!(row_count, 0, "orphaned rows should be swept");
     }
 
+    fn new_row(url: &str, engine: &str) -> NewRow {
+        NewRow {
+            url: url.to_string(),
+            payload: serde_json::to_string(&row(url)).unwrap(),
+            engines: vec![engine.to_string()],
+        }
+    }
+
+    /// Both stores, driven directly: rows are shared between queries by URL,
+    /// attribution accumulates, and purging only sweeps rows nothing else
+    /// references.
+    #[tokio::test]
+    async fn stores_share_rows_between_queries_and_purge_alike() {
+        let sqlite: Arc<dyn CacheStore> = Arc::new(test_store().await);
+        let memory: Arc<dyn CacheStore> = Arc::new(MemoryStore::new());
+        let now = chrono::Utc::now().naive_utc();
+        let old = now - chrono::Duration::days(30);
+        let progress = Progress {
+            next_start: 3,
+            exhausted: false,
+        };
+        let round = |fetched_at, appended| RoundWrite {
+            fetched_at,
+            progress: vec![("B".to_string(), progress)],
+            attributions: Vec::new(),
+            merged_len: 0,
+            appended,
+        };
+
+        for store in [sqlite, memory] {
+            let stale = store
+                .open_query("ns", "stale", "null", old)
+                .await
+                .unwrap()
+                .id;
+            let live = store
+                .open_query("ns", "live", "null", now)
+                .await
+                .unwrap()
+                .id;
+            let reopened = store
+                .open_query("ns", "live", "null", old)
+                .await
+                .unwrap()
+                .id;
+            assert_eq!(reopened, live);
+
+            let stale_rows = vec![new_row("a", "A"), new_row("shared", "A")];
+            let stale_ids = store
+                .commit_round(stale, &round(old, stale_rows))
+                .await
+                .unwrap();
+            let live_rows = vec![new_row("shared", "B")];
+            let live_ids = store
+                .commit_round(live, &round(now, live_rows))
+                .await
+                .unwrap();
+            assert_eq!(live_ids, [stale_ids[1]], "the row for a URL is reused");
+
+            let rediscovered = RoundWrite {
+                attributions: vec![(live_ids[0], "A".to_string())],
+                merged_len: 1,
+                ..round(now, Vec::new())
+            };
+            store.commit_round(live, &rediscovered).await.unwrap();
+
+            let cutoff = now - chrono::Duration::days(7);
+            assert_eq!(store.purge_stale(cutoff).await.unwrap(), 1);
+            assert_eq!(store.merged_len(live).await.unwrap(), 1);
+            let rows = store.merged_window(live, 0..10).await.unwrap();
+            assert_eq!(rows.len(), 1);
+            assert_eq!(rows[0].url, "shared");
+            assert_eq!(rows[0].engines, ["A", "B"]);
+            assert_eq!(store.progress(live, "B").await.unwrap(), progress);
+            let untouched = store.progress(live, "C").await.unwrap();
+            assert_eq!(untouched, Progress::default());
+
+            let reopened = store
+                .open_query("ns", "stale", "null", now)
+                .await
+                .unwrap()
+                .id;
+            assert_eq!(store.merged_len(reopened).await.unwrap(), 0);
+        }
+    }
+
+    /// Both stores: a snapshot being built stays out of sight until it's
+    /// published, the one it replaces stays readable by id, and ids never
+    /// leak across queries.
+    #[tokio::test]
+    async fn stores_publish_snapshots_without_dropping_the_old_one() {
+        let sqlite: Arc<dyn CacheStore> = Arc::new(test_store().await);
+        let memory: Arc<dyn CacheStore> = Arc::new(MemoryStore::new());
+        let now = chrono::Utc::now().naive_utc();
+        let later = now + chrono::Duration::hours(1);
+
+        for store in [sqlite, memory] {
+            let first = store.open_query("ns", "q", "null", now).await.unwrap();
+            let other = store.open_query("ns", "other", "null", now).await.unwrap();
+            let find = |id| store.find_snapshot("ns", "q", "null", id);
+
+            let building = store
+                .begin_snapshot("ns", "q", "null", later)
+                .await
+                .unwrap();
+            assert_eq!(building.created_at, later);
+            let current = store.open_query("ns", "q", "null", later).await.unwrap();
+            assert_eq!(current, first, "unpublished snapshots aren't current");
+            assert_eq!(find(building.id).await.unwrap(), None);
+
+            store.publish_snapshot(building.id).await.unwrap();
+            let current = store.open_query("ns", "q", "null", later).await.unwrap();
+            assert_eq!(current, building);
+            assert_eq!(find(first.id).await.unwrap(), Some(first));
+            assert_eq!(find(other.id).await.unwrap(), None, "another query's id");
+
+            let abandoned = store
+                .begin_snapshot("ns", "q", "null", later)
+                .await
+                .unwrap();
+            let round = RoundWrite {
+                fetched_at: later,
+                progress: Vec::new(),
+                attributions: Vec::new(),
+                merged_len: 0,
+                appended: vec![new_row("a", "A")],
+            };
+            store.commit_round(abandoned.id, &round).await.unwrap();
+            store.discard_snapshot(abandoned.id).await.unwrap();
+            store.discard_snapshot(building.id).await.unwrap();
+            let current = store.open_query("ns", "q", "null", later).await.unwrap();
+            assert_eq!(current, building, "published snapshots can't be discarded");
+        }
+    }
+
+    /// Rounds are written in multi-row statements of bounded size; one
+    /// bigger than a statement must still land whole, in order.
+    #[tokio::test]
+    async fn sqlite_store_writes_rounds_larger_than_one_statement() {
+        let store = test_store().await;
+        let now = chrono::Utc::now().naive_utc();
+        let query_id = store.open_query("ns", "big", "null", now).await.unwrap().id;
+        let urls: Vec<String> = (0..1200).map(|i| format!("u{i}")).collect();
+        let round = RoundWrite {
+            fetched_at: now,
+            progress: vec![("A".to_string(), Progress::default())],
+            attributions: Vec::new(),
+            merged_len: 0,
+            appended: urls.iter().map(|url| new_row(url, "A")).collect(),
+        };
+        let row_ids = store.commit_round(query_id, &round).await.unwrap();
+        assert_eq!(row_ids.len(), urls.len());
+
+        let rediscovered = RoundWrite {
+            attributions: row_ids.iter().map(|&id| (id, "B".to_string())).collect(),
+            merged_len: urls.len() as i64,
+            appended: Vec::new(),
+            ..round
+        };
+        store.commit_round(query_id, &rediscovered).await.unwrap();
+
+        assert_eq!(store.merged_len(query_id).await.unwrap(), urls.len());
+        let tail = store.merged_window(query_id, 995..1005).await.unwrap();
+        let tail_urls: Vec<_> = tail.iter().map(|r| r.url.as_str()).collect();
+        assert_eq!(tail_urls, &urls[995..1005]);
+        assert!(tail.iter().all(|r| r.engines == ["A", "B"]));
+    }
+
     /// A source that counts invocations and sleeps briefly, so overlapping
     /// callers genuinely race rather than trivially serializing.
     struct SlowCountingSource {
@@ -609,7 +1623,12 @@ mod test {
             "Slow"
         }
 
-        async fn fetch_page(&self, _query: &str, start: usize) -> Result<Vec<TestRow>, String> {
+        async fn fetch_page(
+            &self,
+            _query: &str,
+            _params: &(),
+            start: usize,
+        ) -> Result<Vec<TestRow>, SourceError> {
             self.calls.fetch_add(1, Ordering::SeqCst);
             tokio::time::sleep(Duration::from_millis(50)).await;
             if start > 0 {
@@ -633,8 +1652,24 @@ mod test {
         let src_vec = vec![source];
 
         let (a, b) = tokio::join!(
-            cache.get_or_extend("dedup race", &src_vec, 0, 5, Duration::from_secs(1)),
-            cache.get_or_extend("dedup race", &src_vec, 0, 5, Duration::from_secs(1)),
+            cache.get_or_extend(
+                "dedup race",
+                &(),
+                &src_vec,
+                None,
+                0,
+                5,
+                Duration::from_secs(1)
+            ),
+            cache.get_or_extend(
+                "dedup race",
+                &(),
+                &src_vec,
+                None,
+                0,
+                5,
+                Duration::from_secs(1)
+            ),
         );
 
         assert_eq!(a.unwrap().rows.len(), 5);
@@ -646,6 +1681,207 @@ mod test {
         );
     }
 
+    /// Drains everything `stream_extend` sent, once it has returned.
+    fn drain<R>(
+        mut rx: tokio::sync::mpsc::UnboundedReceiver<ExtendEvent<R>>,
+    ) -> Vec<ExtendEvent<R>> {
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push(event);
+        }
+        events
+    }
+
+    #[tokio::test]
+    async fn stream_extend_sends_each_sources_rows_as_it_answers() {
+        let cache = test_cache().await;
+        let fast: Arc<dyn EngineSource<TestRow>> =
+            ScriptedSource::new("Fast", vec![vec![row("f1"), row("f2")], vec![]]);
+        let slow: Arc<dyn EngineSource<TestRow>> = Arc::new(SlowCountingSource {
+            calls: Arc::new(AtomicUsize::new(0)),
+        });
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+
+        let result = cache
+            .stream_extend(
+                "q",
+                &(),
+                &[fast, slow],
+                None,
+                0,
+                4,
+                Duration::from_secs(1),
+                tx,
+            )
+            .await
+            .unwrap();
+
+        let events = drain(rx);
+        // Fast's rows go out on their own, before Slow has even answered.
+        let urls = |e: &ExtendEvent<TestRow>| match e {
+            ExtendEvent::Rows(rows) => rows.iter().map(|r| r.value.url.clone()).collect(),
+            ExtendEvent::Engine(..) => Vec::new(),
+        };
+        let batches: Vec<Vec<String>> = events.iter().map(urls).filter(|u| !u.is_empty()).collect();
+        assert_eq!(batches, vec![vec!["f1", "f2"], vec!["s0", "s1"]]);
+        assert!(
+            matches!(&events[0], ExtendEvent::Engine(name, EngineOutcome::Ok) if name == "Fast")
+        );
+
+        // The returned window agrees with what was streamed.
+        let final_urls: Vec<_> = result.rows.iter().map(|r| r.value.url.as_str()).collect();
+        assert_eq!(final_urls, ["f1", "f2", "s0", "s1"]);
+    }
+
+    #[tokio::test]
+    async fn stream_extend_sends_the_cached_window_first() {
+        let cache = test_cache().await;
+        let source =
+            ScriptedSource::new("A", vec![(0..6).map(|i| row(&format!("u{i}"))).collect()]);
+        cache
+            .get_or_extend(
+                "q",
+                &(),
+                &one_source(source.clone()),
+                None,
+                0,
+                3,
+                Duration::from_secs(1),
+            )
+            .await
+            .unwrap();
+
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        cache
+            .stream_extend(
+                "q",
+                &(),
+                &one_source(source.clone()),
+                None,
+                2,
+                3,
+                Duration::from_secs(1),
+                tx,
+            )
+            .await
+            .unwrap();
+
+        let events = drain(rx);
+        assert_eq!(events.len(), 1, "fully cached — no source contacted");
+        let ExtendEvent::Rows(rows) = &events[0] else {
+            panic!("expected rows, got {:?}", events[0]);
+        };
+        let urls: Vec<_> = rows.iter().map(|r| r.value.url.as_str()).collect();
+        assert_eq!(urls, ["u2", "u3", "u4"]);
+        assert!(rows.iter().all(|r| r.cached));
+        assert_eq!(source.calls.load(Ordering::SeqCst), 1);
+    }
+
+    /// A row type of another shape, as a second namespace would store.
+    #[derive(Debug, Clone, Serialize, serde::Deserialize, PartialEq)]
+    struct VideoRow {
+        url: String,
+        duration: u32,
+    }
+
+    impl CacheableRow for VideoRow {
+        fn url(&self) -> &str {
+            &self.url
+        }
+    }
+
+    struct VideoSource(VideoRow);
+
+    #[async_trait]
+    impl EngineSource<VideoRow> for VideoSource {
+        fn name(&self) -> &'static str {
+            "Video"
+        }
+
+        async fn fetch_page(
+            &self,
+            _query: &str,
+            _params: &(),
+            start: usize,
+        ) -> Result<Vec<VideoRow>, SourceError> {
+            Ok(if start == 0 {
+                vec![self.0.clone()]
+            } else {
+                Vec::new()
+            })
+        }
+    }
+
+    struct VideoRanker;
+    impl Ranker<VideoRow> for VideoRanker {
+        fn rank(&self, _query: &str, batch: Vec<VideoRow>) -> Vec<VideoRow> {
+            batch
+        }
+    }
+
+    /// The same URL surfacing in two namespaces, each with its own row type:
+    /// neither namespace's row may stand in for the other's.
+    #[tokio::test]
+    async fn namespaces_sharing_a_store_keep_their_own_row_for_a_url() {
+        let sqlite: Arc<dyn CacheStore> = Arc::new(test_store().await);
+        let memory: Arc<dyn CacheStore> = Arc::new(MemoryStore::new());
+        let url = "https://video.example/watch?v=1";
+        let timeout = Duration::from_secs(1);
+
+        for store in [sqlite, memory] {
+            let text = MergedCache::new(store.clone(), "text", Arc::new(NoopRanker));
+            let video = MergedCache::new(store, "video", Arc::new(VideoRanker));
+            let text_sources = one_source(ScriptedSource::new("A", vec![vec![row(url)]]));
+            let clip = VideoRow {
+                url: url.to_string(),
+                duration: 245,
+            };
+            let video_sources: Vec<Arc<dyn EngineSource<VideoRow>>> =
+                vec![Arc::new(VideoSource(clip.clone()))];
+
+            let fetched = text.get_or_extend("q", &(), &text_sources, None, 0, 1, timeout);
+            assert_eq!(fetched.await.unwrap().rows[0].value, row(url));
+            let fetched = video.get_or_extend("q", &(), &video_sources, None, 0, 1, timeout);
+            assert_eq!(fetched.await.unwrap().rows[0].value, clip);
+
+            let cached = text.get_or_extend("q", &(), &text_sources, None, 0, 1, timeout);
+            let cached = cached.await.unwrap();
+            assert!(cached.rows[0].cached);
+            assert_eq!(cached.rows[0].value, row(url));
+            let cached = video.get_or_extend("q", &(), &video_sources, None, 0, 1, timeout);
+            let cached = cached.await.unwrap();
+            assert!(cached.rows[0].cached);
+            assert_eq!(cached.rows[0].value, clip);
+        }
+    }
+
+    #[tokio::test]
+    async fn a_payload_of_the_wrong_shape_is_an_error_not_a_panic() {
+        let store = Arc::new(MemoryStore::new());
+        let now = chrono::Utc::now().naive_utc();
+        let snapshot = store.open_query("test", "q", "null", now).await.unwrap();
+        let round = RoundWrite {
+            fetched_at: now,
+            progress: vec![("A".to_string(), Progress::default())],
+            attributions: Vec::new(),
+            merged_len: 0,
+            appended: vec![NewRow {
+                url: "a".to_string(),
+                payload: r#"{"url":"a","snippet":"no title"}"#.to_string(),
+                engines: vec!["A".to_string()],
+            }],
+        };
+        store.commit_round(snapshot.id, &round).await.unwrap();
+
+        let cache = MergedCache::new(store, "test", Arc::new(NoopRanker));
+        let sources = one_source(ScriptedSource::new("A", Vec::new()));
+        let result = cache
+            .get_or_extend("q", &(), &sources, None, 0, 1, Duration::from_secs(1))
+            .await;
+
+        assert!(matches!(result, Err(CacheError::Payload(_))));
+    }
+
     #[tokio::test]
     async fn arbitrary_row_payload_round_trips_through_json_storage() {
         let cache = test_cache().await;
@@ -653,10 +1889,101 @@ mod test {
         let source = ScriptedSource::new("A", vec![vec![weird.clone()]]);
 
         let result = cache
-            .get_or_extend("unicode", &one_source(source), 0, 1, Duration::from_secs(1))
+            .get_or_extend(
+                "unicode",
+                &(),
+                &one_source(source),
+                None,
+                0,
+                1,
+                Duration::from_secs(1),
+            )
             .await
             .unwrap();
 
         assert_eq!(result.rows[0].value, weird);
     }
+
+    /// Waits out the background refresh of `query` in the `"test"`
+    /// namespace, started by a call that has already returned.
+    async fn refresh_done(query: &str) {
+        let key = format!("test:null:{query}");
+        let refreshing = || REFRESHING.lock().unwrap().as_ref().unwrap().contains(&key);
+        while refreshing() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn stale_queries_are_served_then_refreshed_into_a_new_snapshot() {
+        let store = Arc::new(test_store().await);
+        let cache =
+            MergedCache::new(store.clone(), "test", Arc::new(NoopRanker)).fresh_for(Duration::ZERO);
+        let source = ScriptedSource::new(
+            "A",
+            vec![vec![row("a"), row("b")], vec![row("c"), row("a")]],
+        );
+        let src = one_source(source.clone());
+        let urls = |result: &ExtendResult<TestRow>| -> Vec<String> {
+            result.rows.iter().map(|r| r.value.url.clone()).collect()
+        };
+        let get = |snapshot| {
+            cache.get_or_extend("news", &(), &src, snapshot, 0, 2, Duration::from_secs(1))
+        };
+
+        let first = get(None).await.unwrap();
+        assert_eq!(urls(&first), ["a", "b"]);
+        assert_eq!(
+            source.calls.load(Ordering::SeqCst),
+            1,
+            "a new snapshot isn't stale"
+        );
+
+        let stale = get(None).await.unwrap();
+        assert_eq!(stale.snapshot, first.snapshot);
+        assert_eq!(urls(&stale), ["a", "b"]);
+        assert!(
+            stale.rows.iter().all(|r| r.cached),
+            "served before refreshing"
+        );
+        refresh_done("news").await;
+        assert_eq!(source.calls.load(Ordering::SeqCst), 2);
+
+        let pinned = get(Some(first.snapshot)).await.unwrap();
+        assert_eq!(pinned.snapshot, first.snapshot);
+        assert_eq!(urls(&pinned), ["a", "b"], "the old snapshot doesn't drift");
+
+        let refreshed = get(None).await.unwrap();
+        assert_ne!(refreshed.snapshot, first.snapshot);
+        assert_eq!(urls(&refreshed), ["c", "a"]);
+        assert!(refreshed.rows.iter().all(|r| r.cached));
+        refresh_done("news").await;
+    }
+
+    #[tokio::test]
+    async fn a_refresh_that_finds_nothing_keeps_the_stale_snapshot() {
+        let store = Arc::new(test_store().await);
+        let cache =
+            MergedCache::new(store.clone(), "test", Arc::new(NoopRanker)).fresh_for(Duration::ZERO);
+        let source = ScriptedSource::new("A", vec![vec![row("a")]]);
+        let src = one_source(source.clone());
+        let get = || cache.get_or_extend("quiet", &(), &src, None, 0, 1, Duration::from_secs(1));
+
+        let first = get().await.unwrap();
+        get().await.unwrap();
+        refresh_done("quiet").await;
+        assert_eq!(source.calls.load(Ordering::SeqCst), 2, "the refresh ran");
+
+        let now = chrono::Utc::now().naive_utc();
+        let current = store
+            .open_query("test", "quiet", "null", now)
+            .await
+            .unwrap();
+        assert_eq!(current.id, first.snapshot);
+        let snapshots: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM queries")
+            .fetch_one(store.pool())
+            .await
+            .unwrap();
+        assert_eq!(snapshots, 1, "the empty snapshot was discarded");
+    }
 }
@@ -0,0 +1,296 @@
+//! [`MemoryStore`]: the whole cache in process memory.
+
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use std::{
+    collections::{BTreeSet, HashMap},
+    ops::Range,
+    sync::Mutex,
+};
+
+use crate::{CacheError, CacheStore, Progress, RoundWrite, Snapshot, StoredRow};
+
+/// [`CacheStore`] that keeps everything in a process-local map, and so
+/// loses it all on restart. Same semantics as
+/// [`SqliteStore`](crate::SqliteStore) — rows shared between a namespace's
+/// queries by URL, append-only merged order, accumulated attribution — for
+/// tests, and for deployments that can't write to disk (read-only
+/// containers, serverless).
+///
+/// Nothing is evicted until [`purge_stale`](CacheStore::purge_stale), so
+/// schedule [`clean_cache`](crate::clean_cache) as you would for SQLite.
+#[derive(Default)]
+pub struct MemoryStore {
+    tables: Mutex<Tables>,
+}
+
+#[derive(Default)]
+struct Tables {
+    /// Shared by snapshots and rows, like SQLite's per-table rowids but
+    /// without reuse — so a later snapshot always has a higher id.
+    last_id: i64,
+    /// The current snapshot by `(namespace, query, params)`.
+    query_ids: HashMap<(String, String, String), i64>,
+    /// Every snapshot, current or not, by id.
+    queries: HashMap<i64, Query>,
+    /// By `(namespace, url)`: each namespace has its own row type.
+    row_ids: HashMap<(String, String), i64>,
+    rows: HashMap<i64, Row>,
+}
+
+/// One snapshot of a query.
+struct Query {
+    key: (String, String, String),
+    created_at: NaiveDateTime,
+    fetched_at: NaiveDateTime,
+    published: bool,
+    progress: HashMap<String, Progress>,
+    /// Row ids, in merged order.
+    merged: Vec<i64>,
+    engines: HashMap<i64, BTreeSet<String>>,
+}
+
+struct Row {
+    url: String,
+    payload: String,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Tables {
+    fn next_id(&mut self) -> i64 {
+        self.last_id += 1;
+        self.last_id
+    }
+
+    fn insert_query(
+        &mut self,
+        key: (String, String, String),
+        fetched_at: NaiveDateTime,
+        published: bool,
+    ) -> Snapshot {
+        let id = self.next_id();
+        if published {
+            self.query_ids.insert(key.clone(), id);
+        }
+        let query = Query {
+            key,
+            created_at: fetched_at,
+            fetched_at,
+            published,
+            progress: HashMap::new(),
+            merged: Vec::new(),
+            engines: HashMap::new(),
+        };
+        self.queries.insert(id, query);
+        Snapshot {
+            id,
+            created_at: fetched_at,
+        }
+    }
+
+    fn query(&self, query_id: i64) -> Result<&Query, CacheError> {
+        self.queries
+            .get(&query_id)
+            .ok_or_else(|| unknown_query(query_id))
+    }
+
+    fn query_mut(&mut self, query_id: i64) -> Result<&mut Query, CacheError> {
+        self.queries
+            .get_mut(&query_id)
+            .ok_or_else(|| unknown_query(query_id))
+    }
+}
+
+fn unknown_query(query_id: i64) -> CacheError {
+    CacheError::Store(format!("no cached query with id {query_id}").into())
+}
+
+#[async_trait]
+impl CacheStore for MemoryStore {
+    async fn open_query(
+        &self,
+        namespace: &str,
+        query: &str,
+        params: &str,
+        fetched_at: NaiveDateTime,
+    ) -> Result<Snapshot, CacheError> {
+        let mut tables = self.tables.lock().unwrap();
+        let key = (namespace.to_string(), query.to_string(), params.to_string());
+        if let Some(&id) = tables.query_ids.get(&key) {
+            let created_at = tables.query(id)?.created_at;
+            return Ok(Snapshot { id, created_at });
+        }
+        Ok(tables.insert_query(key, fetched_at, true))
+    }
+
+    async fn find_snapshot(
+        &self,
+        namespace: &str,
+        query: &str,
+        params: &str,
+        id: i64,
+    ) -> Result<Option<Snapshot>, CacheError> {
+        let tables = self.tables.lock().unwrap();
+        let snapshot = tables.queries.get(&id).filter(|q| {
+            q.published && q.key.0 == namespace && q.key.1 == query && q.key.2 == params
+        });
+        Ok(snapshot.map(|q| Snapshot {
+            id,
+            created_at: q.created_at,
+        }))
+    }
+
+    async fn begin_snapshot(
+        &self,
+        namespace: &str,
+        query: &str,
+        params: &str,
+        fetched_at: NaiveDateTime,
+    ) -> Result<Snapshot, CacheError> {
+        let mut tables = self.tables.lock().unwrap();
+        let key = (namespace.to_string(), query.to_string(), params.to_string());
+        Ok(tables.insert_query(key, fetched_at, false))
+    }
+
+    async fn publish_snapshot(&self, id: i64) -> Result<(), CacheError> {
+        let mut tables = self.tables.lock().unwrap();
+        let query = tables.query_mut(id)?;
+        query.published = true;
+        let key = query.key.clone();
+        tables.query_ids.insert(key, id);
+        Ok(())
+    }
+
+    async fn discard_snapshot(&self, id: i64) -> Result<(), CacheError> {
+        let mut tables = self.tables.lock().unwrap();
+        if tables.queries.get(&id).is_some_and(|q| !q.published) {
+            tables.queries.remove(&id);
+        }
+        Ok(())
+    }
+
+    async fn progress(&self, query_id: i64, engine: &str) -> Result<Progress, CacheError> {
+        let tables = self.tables.lock().unwrap();
+        let query = tables.query(query_id)?;
+        Ok(query.progress.get(engine).copied().unwrap_or_default())
+    }
+
+    async fn merged_len(&self, query_id: i64) -> Result<usize, CacheError> {
+        let tables = self.tables.lock().unwrap();
+        Ok(tables.query(query_id)?.merged.len())
+    }
+
+    async fn merged_window(
+        &self,
+        query_id: i64,
+        range: Range<usize>,
+    ) -> Result<Vec<StoredRow>, CacheError> {
+        let tables = self.tables.lock().unwrap();
+        let query = tables.query(query_id)?;
+        let end = range.end.min(query.merged.len());
+        let start = range.start.min(end);
+        Ok(query.merged[start..end]
+            .iter()
+            .map(|row_id| {
+                let row = &tables.rows[row_id];
+                let engines = query.engines.get(row_id).into_iter().flatten();
+                StoredRow {
+                    row_id: *row_id,
+                    url: row.url.clone(),
+                    payload: row.payload.clone(),
+                    engines: engines.cloned().collect(),
+                }
+            })
+            .collect())
+    }
+
+    async fn merged_urls(&self, query_id: i64) -> Result<HashMap<String, i64>, CacheError> {
+        let tables = self.tables.lock().unwrap();
+        let query = tables.query(query_id)?;
+        let urls = query
+            .merged
+            .iter()
+            .map(|id| (tables.rows[id].url.clone(), *id));
+        Ok(urls.collect())
+    }
+
+    async fn commit_round(
+        &self,
+        query_id: i64,
+        round: &RoundWrite,
+    ) -> Result<Vec<i64>, CacheError> {
+        let mut tables = self.tables.lock().unwrap();
+        // Checked up front so a missing query leaves nothing half-written.
+        let namespace = tables.query(query_id)?.key.0.clone();
+
+        let mut row_ids = Vec::with_capacity(round.appended.len());
+        for row in &round.appended {
+            let key = (namespace.clone(), row.url.clone());
+            let row_id = match tables.row_ids.get(&key) {
+                Some(&id) => id,
+                None => {
+                    let id = tables.next_id();
+                    tables.row_ids.insert(key, id);
+                    let stored = Row {
+                        url: row.url.clone(),
+                        payload: row.payload.clone(),
+                    };
+                    tables.rows.insert(id, stored);
+                    id
+                }
+            };
+            row_ids.push(row_id);
+        }
+
+        let query = tables.query_mut(query_id)?;
+        query.fetched_at = round.fetched_at;
+        for (engine, progress) in &round.progress {
+            query.progress.insert(engine.clone(), *progress);
+        }
+        for (row_id, engine) in &round.attributions {
+            let engines = query.engines.entry(*row_id).or_default();
+            engines.insert(engine.clone());
+        }
+        for (row_id, row) in row_ids.iter().zip(&round.appended) {
+            let engines = query.engines.entry(*row_id).or_default();
+            engines.extend(row.engines.iter().cloned());
+        }
+        // No duplicate check: callers only append URLs not already in the
+        // query's merged order.
+        query.merged.extend(&row_ids);
+
+        Ok(row_ids)
+    }
+
+    async fn purge_stale(&self, cutoff: NaiveDateTime) -> Result<u64, CacheError> {
+        let mut tables = self.tables.lock().unwrap();
+        let stale: Vec<i64> = tables
+            .queries
+            .iter()
+            .filter(|(_, q)| q.fetched_at < cutoff)
+            .map(|(&id, _)| id)
+            .collect();
+        for id in &stale {
+            if let Some(query) = tables.queries.remove(id)
+                && tables.query_ids.get(&query.key) == Some(id)
+            {
+                tables.query_ids.remove(&query.key);
+            }
+        }
+
+        let live: BTreeSet<i64> = tables
+            .queries
+            .values()
+            .flat_map(|q| q.merged.iter().copied())
+            .collect();
+        tables.rows.retain(|id, _| live.contains(id));
+        tables.row_ids.retain(|_, id| live.contains(id));
+
+        Ok(stale.len() as u64)
+    }
+}
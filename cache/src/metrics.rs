@@ -0,0 +1,251 @@
+//! Counters and histograms in the Prometheus text exposition format.
+//! Deliberately tiny — labelled counters and histograms are all we need —
+//! and usable by callers for their own metrics, so one scrape renders
+//! everything.
+//!
+//! This crate doesn't name or register any series itself: a
+//! [`MergedCache`](crate::MergedCache) reports what it sees to a
+//! [`CacheObserver`], and the caller decides what to count.
+
+use std::{collections::BTreeMap, fmt::Write, sync::Mutex, time::Duration};
+
+use crate::EngineOutcome;
+
+/// What a [`MergedCache`](crate::MergedCache) reports as it works (see
+/// [`MergedCache::observed_by`](crate::MergedCache::observed_by)). Every
+/// method does nothing unless overridden.
+#[allow(unused_variables)]
+pub trait CacheObserver: Send + Sync {
+    /// A source answered a page fetch (or timed out) after `elapsed`.
+    fn fetched(&self, namespace: &str, source: &str, outcome: &EngineOutcome, elapsed: Duration) {}
+
+    /// A fetch round appended `rows` new rows to a query's merged results.
+    fn appended(&self, namespace: &str, rows: usize) {}
+
+    /// A call returned `hits` rows that were already cached and `misses`
+    /// it had to fetch.
+    fn returned(&self, namespace: &str, hits: usize, misses: usize) {}
+
+    /// A background refresh of a stale query finished.
+    fn refreshed(&self, namespace: &str, refresh: Refresh) {}
+}
+
+/// How a background refresh of a stale query ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Refresh {
+    /// The new snapshot took over.
+    Published,
+    /// No source had anything, so the stale snapshot keeps serving.
+    Discarded,
+    /// The store failed.
+    Failed,
+}
+
+/// The observer a cache has until it's given one.
+pub(crate) struct Unobserved;
+
+impl CacheObserver for Unobserved {}
+
+/// A counter per combination of label values.
+pub struct CounterVec {
+    name: &'static str,
+    help: &'static str,
+    labels: &'static [&'static str],
+    values: Mutex<BTreeMap<Vec<String>, u64>>,
+}
+
+impl CounterVec {
+    pub const fn new(
+        name: &'static str,
+        help: &'static str,
+        labels: &'static [&'static str],
+    ) -> Self {
+        Self {
+            name,
+            help,
+            labels,
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// `label_values` line up with the labels given to [`new`](Self::new).
+    pub fn inc(&self, label_values: &[&str]) {
+        self.add(label_values, 1);
+    }
+
+    pub fn add(&self, label_values: &[&str], n: u64) {
+        debug_assert_eq!(label_values.len(), self.labels.len());
+        let key = label_values.iter().map(|v| v.to_string()).collect();
+        *self.values.lock().unwrap().entry(key).or_default() += n;
+    }
+
+    pub fn get(&self, label_values: &[&str]) -> u64 {
+        let key: Vec<String> = label_values.iter().map(|v| v.to_string()).collect();
+        self.values.lock().unwrap().get(&key).copied().unwrap_or(0)
+    }
+
+    pub fn render(&self, out: &mut String) {
+        header(out, self.name, self.help, "counter");
+        for (values, n) in self.values.lock().unwrap().iter() {
+            sample(out, self.name, self.labels, values, None, *n as f64);
+        }
+    }
+}
+
+/// A histogram per combination of label values, over fixed bucket bounds.
+pub struct HistogramVec {
+    name: &'static str,
+    help: &'static str,
+    labels: &'static [&'static str],
+    /// Upper bounds, ascending; `+Inf` is implied.
+    bounds: &'static [f64],
+    values: Mutex<BTreeMap<Vec<String>, Observations>>,
+}
+
+#[derive(Default)]
+struct Observations {
+    /// Per bucket, not cumulative — summed up when rendering.
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl HistogramVec {
+    pub const fn new(
+        name: &'static str,
+        help: &'static str,
+        labels: &'static [&'static str],
+        bounds: &'static [f64],
+    ) -> Self {
+        Self {
+            name,
+            help,
+            labels,
+            bounds,
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn observe(&self, label_values: &[&str], value: f64) {
+        debug_assert_eq!(label_values.len(), self.labels.len());
+        let key = label_values.iter().map(|v| v.to_string()).collect();
+        let mut values = self.values.lock().unwrap();
+        let observations = values.entry(key).or_default();
+        observations.buckets.resize(self.bounds.len(), 0);
+        if let Some(bucket) = self.bounds.iter().position(|&bound| value <= bound) {
+            observations.buckets[bucket] += 1;
+        }
+        observations.sum += value;
+        observations.count += 1;
+    }
+
+    pub fn render(&self, out: &mut String) {
+        header(out, self.name, self.help, "histogram");
+        let bucket_name = format!("{}_bucket", self.name);
+        for (values, observations) in self.values.lock().unwrap().iter() {
+            let mut cumulative = 0;
+            for (bound, n) in self.bounds.iter().zip(&observations.buckets) {
+                cumulative += n;
+                let le = bound.to_string();
+                sample(
+                    out,
+                    &bucket_name,
+                    self.labels,
+                    values,
+                    Some(&le),
+                    cumulative as f64,
+                );
+            }
+            let count = observations.count as f64;
+            sample(out, &bucket_name, self.labels, values, Some("+Inf"), count);
+            let sum_name = format!("{}_sum", self.name);
+            sample(out, &sum_name, self.labels, values, None, observations.sum);
+            let count_name = format!("{}_count", self.name);
+            sample(out, &count_name, self.labels, values, None, count);
+        }
+    }
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+/// One `name{labels} value` line; `le` is a histogram bucket's bound.
+fn sample(
+    out: &mut String,
+    name: &str,
+    labels: &[&str],
+    values: &[String],
+    le: Option<&str>,
+    value: f64,
+) {
+    let mut pairs: Vec<String> = labels
+        .iter()
+        .zip(values)
+        .map(|(label, value)| format!("{label}=\"{}\"", escape(value)))
+        .collect();
+    if let Some(le) = le {
+        pairs.push(format!("le=\"{le}\""));
+    }
+    if pairs.is_empty() {
+        let _ = writeln!(out, "{name} {value}");
+    } else {
+        let _ = writeln!(out, "{name}{{{}}} {value}", pairs.join(","));
+    }
+}
+
+/// Label values are quoted, so backslashes, quotes and newlines need
+/// escaping.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn counters_render_one_line_per_label_set() {
+        let counter = CounterVec::new("test_total", "A test counter.", &["engine"]);
+        counter.inc(&["Brave"]);
+        counter.add(&["Say \"hi\""], 2);
+        counter.inc(&["Brave"]);
+
+        let mut out = String::new();
+        counter.render(&mut out);
+        assert_eq!(
+            out,
+            "# HELP test_total A test counter.\n\
+             # TYPE test_total counter\n\
+             test_total{engine=\"Brave\"} 2\n\
+             test_total{engine=\"Say \\\"hi\\\"\"} 2\n"
+        );
+        assert_eq!(counter.get(&["Brave"]), 2);
+        assert_eq!(counter.get(&["DuckDuckGo"]), 0);
+    }
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let histogram = HistogramVec::new("test_seconds", "A test histogram.", &[], &[0.5, 1.0]);
+        histogram.observe(&[], 0.25);
+        histogram.observe(&[], 0.75);
+        histogram.observe(&[], 4.0);
+
+        let mut out = String::new();
+        histogram.render(&mut out);
+        assert_eq!(
+            out,
+            "# HELP test_seconds A test histogram.\n\
+             # TYPE test_seconds histogram\n\
+             test_seconds_bucket{le=\"0.5\"} 1\n\
+             test_seconds_bucket{le=\"1\"} 2\n\
+             test_seconds_bucket{le=\"+Inf\"} 3\n\
+             test_seconds_sum 5\n\
+             test_seconds_count 3\n"
+        );
+    }
+}
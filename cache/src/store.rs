@@ -0,0 +1,157 @@
+//! Where [`MergedCache`](crate::MergedCache) keeps its state. Everything
+//! crossing this boundary is already serialized (row payloads and params as
+//! JSON), so a store never needs to know the caller's row type.
+
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use std::{collections::HashMap, ops::Range};
+
+use crate::CacheError;
+
+/// Storage for the merge cache: namespaces and queries, each query's
+/// snapshots, and per snapshot: per-source pagination progress, the
+/// append-only merged order of rows (one per URL per namespace), and which
+/// sources surfaced which of its rows.
+///
+/// Every method taking a `query_id` takes a [`Snapshot::id`]: progress and
+/// merged order belong to one snapshot, never to the query as a whole.
+///
+/// [`SqliteStore`](crate::SqliteStore) is the durable implementation;
+/// [`MemoryStore`](crate::MemoryStore) keeps everything in process, for
+/// tests and for deployments that can't (or needn't) write to disk.
+///
+/// Every write for one round goes through a single
+/// [`commit_round`](Self::commit_round) call, which implementations must
+/// apply atomically — a reader never sees a round half-persisted.
+// `async_trait` marks each method `#[must_use]` on top of the boxed future's
+// own, which clippy flags once per `Result`-returning method.
+#[allow(clippy::double_must_use)]
+#[async_trait]
+pub trait CacheStore: Send + Sync {
+    /// The current snapshot of `query` under `params` (serialized) in
+    /// `namespace`, creating the namespace, query and a first snapshot if
+    /// none exists yet — `fetched_at` is only recorded for a new snapshot.
+    async fn open_query(
+        &self,
+        namespace: &str,
+        query: &str,
+        params: &str,
+        fetched_at: NaiveDateTime,
+    ) -> Result<Snapshot, CacheError>;
+
+    /// Snapshot `id` of `query` under `params` in `namespace`, if it's been
+    /// published and not yet purged. `None` for an id that belongs to
+    /// anything else, so a caller can't be steered into another query.
+    async fn find_snapshot(
+        &self,
+        namespace: &str,
+        query: &str,
+        params: &str,
+        id: i64,
+    ) -> Result<Option<Snapshot>, CacheError>;
+
+    /// A new, empty snapshot of the query, invisible to
+    /// [`open_query`](Self::open_query) and
+    /// [`find_snapshot`](Self::find_snapshot) until it's published.
+    async fn begin_snapshot(
+        &self,
+        namespace: &str,
+        query: &str,
+        params: &str,
+        fetched_at: NaiveDateTime,
+    ) -> Result<Snapshot, CacheError>;
+
+    /// Makes snapshot `id` its query's current one. Older snapshots stay
+    /// readable by id until they're purged.
+    async fn publish_snapshot(&self, id: i64) -> Result<(), CacheError>;
+
+    /// Drops an unpublished snapshot, and everything written into it.
+    async fn discard_snapshot(&self, id: i64) -> Result<(), CacheError>;
+
+    /// How far `engine` has got for `query_id`; the default (nothing
+    /// fetched, not exhausted) if it has never been asked.
+    async fn progress(&self, query_id: i64, engine: &str) -> Result<Progress, CacheError>;
+
+    /// How many rows the query's merged order holds.
+    async fn merged_len(&self, query_id: i64) -> Result<usize, CacheError>;
+
+    /// The rows at merged indexes `range` (fewer if the order is shorter),
+    /// in order, with each row's engines sorted by name.
+    async fn merged_window(
+        &self,
+        query_id: i64,
+        range: Range<usize>,
+    ) -> Result<Vec<StoredRow>, CacheError>;
+
+    /// Row id by URL for every row in the query's merged order — what a
+    /// round dedupes against, without decoding any payloads.
+    async fn merged_urls(&self, query_id: i64) -> Result<HashMap<String, i64>, CacheError>;
+
+    /// Persists one round's results for `query_id`. Returns the row ids of
+    /// `round.appended`, in order.
+    async fn commit_round(&self, query_id: i64, round: &RoundWrite)
+    -> Result<Vec<i64>, CacheError>;
+
+    /// Drops snapshots last fetched before `cutoff`, and any rows no
+    /// remaining snapshot references. Returns the number of snapshots
+    /// dropped.
+    async fn purge_stale(&self, cutoff: NaiveDateTime) -> Result<u64, CacheError>;
+}
+
+/// One version of a query's merged order. A query starts out with one;
+/// refreshing it builds another alongside, so whoever is paging the old one
+/// keeps a stable order until it's purged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Snapshot {
+    pub id: i64,
+    /// When the snapshot was begun — what its freshness is judged by, as
+    /// opposed to `fetched_at`, which every round moves on.
+    pub created_at: NaiveDateTime,
+}
+
+/// One source's pagination progress for one snapshot.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// The source's own raw offset to fetch from next.
+    pub next_start: i64,
+    /// Whether the source has returned an empty page.
+    pub exhausted: bool,
+}
+
+/// A row of a query's merged order, as stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredRow {
+    pub row_id: i64,
+    pub url: String,
+    /// The caller's row, as JSON.
+    pub payload: String,
+    pub engines: Vec<String>,
+}
+
+/// Everything one round changes for a snapshot.
+#[derive(Debug, Clone)]
+pub struct RoundWrite {
+    /// Becomes the snapshot's `fetched_at`, for [`CacheStore::purge_stale`].
+    pub fetched_at: NaiveDateTime,
+    /// The new progress of every source taking part.
+    pub progress: Vec<(String, Progress)>,
+    /// Sources that rediscovered rows already in the merged order, as
+    /// `(row_id, engine)`.
+    pub attributions: Vec<(i64, String)>,
+    /// How many rows the merged order held before this round; `appended`
+    /// takes the indexes from here on.
+    pub merged_len: i64,
+    /// New rows for the tail of the merged order, in order.
+    pub appended: Vec<NewRow>,
+}
+
+/// A row a round appends. If another query in the same namespace already
+/// stored a row for `url`, that one is reused (payload and all). Namespaces
+/// never share rows: each may store a different row type.
+#[derive(Debug, Clone)]
+pub struct NewRow {
+    pub url: String,
+    /// The caller's row, as JSON.
+    pub payload: String,
+    pub engines: Vec<String>,
+}
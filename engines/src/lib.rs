@@ -8,7 +8,8 @@
 //! backend) can depend on `search-engines`/`search-cache` directly instead
 //! of this crate.
 //!
-//! [`SearchBuilder`] and [`ImageSearchBuilder`] are the main entry points:
+//! [`SearchBuilder`], [`ImageSearchBuilder`], [`NewsSearchBuilder`] and
+//! [`VideoSearchBuilder`] are the main entry points:
 //!
 //! ```no_run
 //! # async fn run() -> Result<(), private_search_engines::FetchError> {
@@ -25,22 +26,61 @@
 //! # }
 //! ```
 
-use async_trait::async_trait;
-use search_cache::{CacheableRow, EngineOutcome, EngineSource, MergedCache, Ranker};
-use search_engines::{Brave, DuckDuckGo, EngineInfo, ImageEngine, SearchEngine};
-use serde::{Deserialize, Serialize};
-use std::{cmp::Ordering, fmt, sync::Arc, time::Duration};
-use tokio::sync::OnceCell;
+pub mod metrics;
 
-const ENGINE_TIMEOUT: u64 = 3; // seconds
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use search_cache::{
+    CacheStore, CacheableRow, EngineOutcome, EngineSource, ExtendEvent, MergedCache,
+    MergedRowResult, Ranker, SourceError, SqliteStore,
+};
+use search_engines::{
+    Brave, DuckDuckGo, EngineError, EngineInfo, ImageEngine, Marginalia, MediaWiki, Mojeek,
+    NewsEngine, Qwant, RawImage, RawNews, RawResult, RawVideo, SearchEngine, Startpage,
+    SuggestEngine, VideoEngine,
+};
+use serde::{Deserialize, Deserializer, Serialize, de};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    path::Path,
+    pin::Pin,
+    str::FromStr,
+    sync::{Arc, LazyLock, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+pub use search_engines::{
+    ClientConfig, DeclarativeEngine, DefinitionError, ProxyError, SafeSearch, SearchParams,
+    TimeRange, configure_clients,
+};
+use tokio::sync::{
+    OnceCell,
+    mpsc::{self, UnboundedReceiver},
+};
+
+/// How long a search waits on each engine per round unless told otherwise
+/// (see [`SearchBuilder::timeout`]).
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3);
 const DEFAULT_SEARCH_COUNT: usize = 10;
 const DEFAULT_IMAGE_COUNT: usize = 50;
+const DEFAULT_NEWS_COUNT: usize = 10;
+const DEFAULT_VIDEO_COUNT: usize = 10;
+/// Suggestions are fetched on every keystroke, so a slow engine is dropped
+/// rather than waited on.
+const SUGGEST_TIMEOUT: Duration = Duration::from_millis(800);
+const SUGGEST_TTL: Duration = Duration::from_secs(5 * 60);
+const SUGGEST_CACHE_CAPACITY: usize = 4096;
+const MAX_SUGGESTIONS: usize = 10;
 /// Hint passed to an engine adapter's own page size — most of ours ignore it
 /// and return whatever a real page contains (see `search-engines`).
 const ENGINE_PAGE_HINT: usize = 20;
 
-pub async fn init_db() {
-    search_cache::shared_pool().await;
+/// Opens (creating if needed) the SQLite result cache at `db_path`. Call
+/// once at startup, before any search.
+pub async fn init_db(db_path: &str) {
+    search_cache::init_shared_pool(db_path).await;
 }
 
 /// Purges cached queries (and their now-orphaned rows) older than `max_age`.
@@ -50,8 +90,39 @@ pub async fn init_db() {
 /// binary) are expected to schedule it periodically, since only they know
 /// what cadence/retention makes sense for their deployment.
 pub async fn clean_cache(max_age: Duration) -> Result<u64, FetchError> {
-    let pool = search_cache::shared_pool().await;
-    Ok(search_cache::clean_cache(pool, max_age).await?)
+    Ok(search_cache::clean_cache(&*shared_store().await, max_age).await?)
+}
+
+/// How long each kind of search's cached results stay fresh: a search for a
+/// query cached longer ago than this still gets the cached results, but
+/// also refreshes them in the background for the searches after it (see
+/// [`MergedCache::fresh_for`]). `None`, the default, keeps serving a query's
+/// first results until [`clean_cache`] purges them.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CacheFreshness {
+    pub general: Option<Duration>,
+    pub images: Option<Duration>,
+    pub news: Option<Duration>,
+    pub videos: Option<Duration>,
+}
+
+static CACHE_FRESHNESS: RwLock<CacheFreshness> = RwLock::new(CacheFreshness {
+    general: None,
+    images: None,
+    news: None,
+    videos: None,
+});
+
+/// Sets how long cached results stay fresh. Call once at startup, before
+/// any search — each kind's cache reads this when it's first used.
+pub fn configure_cache_freshness(freshness: CacheFreshness) {
+    *CACHE_FRESHNESS.write().unwrap() = freshness;
+}
+
+/// The SQLite store every cache shares, on the pool [`init_db`] opened.
+async fn shared_store() -> Arc<dyn CacheStore> {
+    let pool = search_cache::shared_pool().await.clone();
+    Arc::new(SqliteStore::new(pool))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -79,6 +150,38 @@ impl CacheableRow for CachedImage {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedNews {
+    url: String,
+    title: String,
+    snippet: String,
+    source: String,
+    published: Option<DateTime<Utc>>,
+    thumbnail: Option<String>,
+}
+
+impl CacheableRow for CachedNews {
+    fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedVideo {
+    url: String,
+    title: String,
+    duration_secs: Option<u64>,
+    uploader: Option<String>,
+    published: Option<DateTime<Utc>>,
+    thumbnail: Option<String>,
+}
+
+impl CacheableRow for CachedVideo {
+    fn url(&self) -> &str {
+        &self.url
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct SearchResult {
     pub url: String,
@@ -100,6 +203,18 @@ impl PartialOrd for SearchResult {
     }
 }
 
+impl From<MergedRowResult<CachedResult>> for SearchResult {
+    fn from(r: MergedRowResult<CachedResult>) -> Self {
+        SearchResult {
+            url: r.value.url,
+            title: r.value.title,
+            description: r.value.description,
+            engines: r.engines,
+            cached: r.cached,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct ImageResult {
     pub url: String,
@@ -120,6 +235,96 @@ impl PartialOrd for ImageResult {
     }
 }
 
+impl From<MergedRowResult<CachedImage>> for ImageResult {
+    fn from(r: MergedRowResult<CachedImage>) -> Self {
+        ImageResult {
+            url: r.value.url,
+            title: r.value.title,
+            engines: r.engines,
+            cached: r.cached,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NewsResult {
+    pub url: String,
+    pub title: String,
+    pub snippet: String,
+    pub source: String,
+    pub published: Option<DateTime<Utc>>,
+    pub thumbnail: Option<String>,
+    pub engines: Vec<String>,
+    pub cached: bool,
+}
+
+impl PartialEq for NewsResult {
+    fn eq(&self, other: &Self) -> bool {
+        self.url == other.url
+    }
+}
+
+impl PartialOrd for NewsResult {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.url.cmp(&other.url))
+    }
+}
+
+impl From<MergedRowResult<CachedNews>> for NewsResult {
+    fn from(r: MergedRowResult<CachedNews>) -> Self {
+        NewsResult {
+            url: r.value.url,
+            title: r.value.title,
+            snippet: r.value.snippet,
+            source: r.value.source,
+            published: r.value.published,
+            thumbnail: r.value.thumbnail,
+            engines: r.engines,
+            cached: r.cached,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VideoResult {
+    pub url: String,
+    pub title: String,
+    /// Length in whole seconds.
+    pub duration: Option<u64>,
+    pub uploader: Option<String>,
+    pub published: Option<DateTime<Utc>>,
+    pub thumbnail: Option<String>,
+    pub engines: Vec<String>,
+    pub cached: bool,
+}
+
+impl PartialEq for VideoResult {
+    fn eq(&self, other: &Self) -> bool {
+        self.url == other.url
+    }
+}
+
+impl PartialOrd for VideoResult {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.url.cmp(&other.url))
+    }
+}
+
+impl From<MergedRowResult<CachedVideo>> for VideoResult {
+    fn from(r: MergedRowResult<CachedVideo>) -> Self {
+        VideoResult {
+            url: r.value.url,
+            title: r.value.title,
+            duration: r.value.duration_secs,
+            uploader: r.value.uploader,
+            published: r.value.published,
+            thumbnail: r.value.thumbnail,
+            engines: r.engines,
+            cached: r.cached,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum FetchError {
     Cache(search_cache::CacheError),
@@ -154,6 +359,14 @@ pub enum EngineStatus {
     Ok,
     TimedOut,
     Failed(String),
+    /// The engine served a captcha/bot wall — it's refusing us, not broken.
+    Blocked(String),
+    /// Seconds the engine asked us to wait before retrying, if it said.
+    RateLimited(Option<u64>),
+    HttpStatus(u16),
+    /// Left out of the search: it can't apply the search's params (see
+    /// [`EngineInfo::honors`]).
+    Skipped,
 }
 
 impl From<&EngineOutcome> for EngineStatus {
@@ -161,7 +374,27 @@ impl From<&EngineOutcome> for EngineStatus {
         match o {
             EngineOutcome::Ok => EngineStatus::Ok,
             EngineOutcome::TimedOut => EngineStatus::TimedOut,
-            EngineOutcome::Failed(msg) => EngineStatus::Failed(msg.clone()),
+            EngineOutcome::Failed(SourceError::Blocked(reason)) => {
+                EngineStatus::Blocked(reason.clone())
+            }
+            EngineOutcome::Failed(SourceError::RateLimited { retry_after }) => {
+                EngineStatus::RateLimited(retry_after.map(|d| d.as_secs()))
+            }
+            EngineOutcome::Failed(SourceError::HttpStatus(code)) => EngineStatus::HttpStatus(*code),
+            EngineOutcome::Failed(SourceError::Other(msg)) => EngineStatus::Failed(msg.clone()),
+        }
+    }
+}
+
+/// Keeps the adapter's classification of a failure so it survives the trip
+/// through the cache layer into [`EngineStatus`].
+fn source_error(e: EngineError) -> SourceError {
+    match e {
+        EngineError::Blocked { reason } => SourceError::Blocked(reason),
+        EngineError::RateLimited { retry_after } => SourceError::RateLimited { retry_after },
+        EngineError::HttpStatus(code) => SourceError::HttpStatus(code),
+        e @ (EngineError::ReqwestError(_) | EngineError::ParseError(_) | EngineError::Timeout) => {
+            SourceError::Other(e.to_string())
         }
     }
 }
@@ -172,9 +405,30 @@ pub struct EngineReport {
     pub status: EngineStatus,
 }
 
-/// Results from a [`SearchBuilder`] or [`ImageSearchBuilder`] call, paired
-/// with a per-engine status report so callers can surface timeouts/failures
-/// alongside the (possibly partial) results.
+/// One step of a streamed search — see [`SearchBuilder::stream`] (and the
+/// image, news and video builders' equivalents).
+#[derive(Debug)]
+pub enum SearchEvent<T> {
+    /// Results that just landed in the requested window, in their final
+    /// merged order: whatever was already cached first, then each engine's
+    /// new results as soon as that engine answers.
+    Results(Vec<T>),
+    /// An engine answered (or failed, or timed out) — once per round it
+    /// was asked in.
+    Engine(EngineReport),
+    /// Always the last event unless the search [`Failed`](Self::Failed):
+    /// the final per-engine reports, as [`SearchResponse::engines`].
+    Done {
+        engines: Vec<EngineReport>,
+        has_more: bool,
+        snapshot: i64,
+    },
+    Failed(FetchError),
+}
+
+/// Results from a [`SearchBuilder`] (or the image, news and video builders)
+/// call, paired with a per-engine status report so callers can surface
+/// timeouts/failures alongside the (possibly partial) results.
 #[derive(Debug, Clone, Serialize)]
 pub struct SearchResponse<T> {
     pub results: Vec<T>,
@@ -183,6 +437,9 @@ pub struct SearchResponse<T> {
     /// signal from the cache, not a "was this page full" guess.
     #[serde(rename = "hasMore")]
     pub has_more: bool,
+    /// Which snapshot of the query's results this page is from; pass it to
+    /// the builder's `snapshot` when fetching the next page.
+    pub snapshot: i64,
 }
 
 /// A query word matching a whole `.`/`-`-delimited domain segment (e.g.
@@ -268,271 +525,1068 @@ impl Ranker<CachedImage> for UrlSortRanker {
     }
 }
 
-struct BraveTextSource;
+/// News is only worth reading while it's current: newest first, undated
+/// stories last, ties keeping the engines' own order.
+struct RecencyRanker;
 
-#[async_trait]
-impl EngineSource<CachedResult> for BraveTextSource {
-    fn name(&self) -> &'static str {
-        Brave.name()
+impl Ranker<CachedNews> for RecencyRanker {
+    fn rank(&self, _query: &str, mut batch: Vec<CachedNews>) -> Vec<CachedNews> {
+        batch.sort_by_key(|n| std::cmp::Reverse(n.published));
+        batch
     }
+}
 
-    async fn fetch_page(&self, query: &str, start: usize) -> Result<Vec<CachedResult>, String> {
-        Brave
-            .search_results(query, start, ENGINE_PAGE_HINT)
-            .await
-            .map(|rows| {
-                rows.into_iter()
-                    .map(|r| CachedResult {
-                        url: r.url,
-                        title: r.title,
-                        description: r.description,
-                    })
-                    .collect()
-            })
-            .map_err(|e| e.to_string())
+/// Video URLs are nearly all on a handful of hosting sites, so
+/// [`sort_results`]' domain matching has nothing to go on — keep the
+/// engine's own relevance order instead.
+struct EngineOrderRanker;
+
+impl Ranker<CachedVideo> for EngineOrderRanker {
+    fn rank(&self, _query: &str, batch: Vec<CachedVideo>) -> Vec<CachedVideo> {
+        batch
     }
 }
 
-struct DdgTextSource;
+/// One engine's page of rows, as fetched for one tab.
+type PageFuture<'a, Raw> = Pin<Box<dyn Future<Output = Result<Vec<Raw>, SourceError>> + Send + 'a>>;
+
+/// Boxes an adapter's search call as a [`PageFuture`].
+fn page<'a, Raw>(
+    fetch: impl Future<Output = Result<Vec<Raw>, EngineError>> + Send + 'a,
+) -> PageFuture<'a, Raw> {
+    Box::pin(async move { fetch.await.map_err(source_error) })
+}
+
+/// An adapter's search method for one tab, called with the query, how many
+/// rows the cache already has, and the search's params.
+type FetchPage<Raw> =
+    dyn for<'a> Fn(&'a str, usize, &'a SearchParams) -> PageFuture<'a, Raw> + Send + Sync;
+
+/// Feeds one engine into a tab's cache: `fetch` calls the adapter's search
+/// method for that tab (e.g. Brave's `search_news`), and its rows are
+/// converted to the tab's cached row type.
+struct Source<Raw> {
+    name: &'static str,
+    fetch: Box<FetchPage<Raw>>,
+}
+
+/// The [`Source`] for the engine called `name`, fetching with `fetch`.
+fn engine_source<Raw, R>(
+    name: &'static str,
+    fetch: impl for<'a> Fn(&'a str, usize, &'a SearchParams) -> PageFuture<'a, Raw>
+    + Send
+    + Sync
+    + 'static,
+) -> Arc<dyn EngineSource<R, SearchParams>>
+where
+    Raw: 'static,
+    R: CacheableRow + From<Raw>,
+{
+    Arc::new(Source {
+        name,
+        fetch: Box::new(fetch),
+    })
+}
 
 #[async_trait]
-impl EngineSource<CachedResult> for DdgTextSource {
+impl<R, Raw> EngineSource<R, SearchParams> for Source<Raw>
+where
+    R: CacheableRow + From<Raw>,
+{
     fn name(&self) -> &'static str {
-        DuckDuckGo.name()
+        self.name
     }
 
-    async fn fetch_page(&self, query: &str, start: usize) -> Result<Vec<CachedResult>, String> {
-        DuckDuckGo
-            .search_results(query, start, ENGINE_PAGE_HINT)
-            .await
-            .map(|rows| {
-                rows.into_iter()
-                    .map(|r| CachedResult {
-                        url: r.url,
-                        title: r.title,
-                        description: r.description,
-                    })
-                    .collect()
-            })
-            .map_err(|e| e.to_string())
+    async fn fetch_page(
+        &self,
+        query: &str,
+        params: &SearchParams,
+        start: usize,
+    ) -> Result<Vec<R>, SourceError> {
+        let rows = (self.fetch)(query, start, params).await?;
+        Ok(rows.into_iter().map(R::from).collect())
     }
 }
 
-struct BraveImageSource;
+impl From<RawResult> for CachedResult {
+    fn from(r: RawResult) -> Self {
+        CachedResult {
+            url: r.url,
+            title: r.title,
+            description: r.description,
+        }
+    }
+}
 
-#[async_trait]
-impl EngineSource<CachedImage> for BraveImageSource {
-    fn name(&self) -> &'static str {
-        Brave.name()
+impl From<RawImage> for CachedImage {
+    fn from(r: RawImage) -> Self {
+        CachedImage {
+            url: r.url,
+            title: r.title,
+        }
     }
+}
 
-    async fn fetch_page(&self, query: &str, start: usize) -> Result<Vec<CachedImage>, String> {
-        Brave
-            .search_images(query, start, ENGINE_PAGE_HINT)
-            .await
-            .map(|rows| {
-                rows.into_iter()
-                    .map(|r| CachedImage {
-                        url: r.url,
-                        title: r.title,
-                    })
-                    .collect()
-            })
-            .map_err(|e| e.to_string())
+static WIKIPEDIA: LazyLock<RwLock<MediaWiki>> =
+    LazyLock::new(|| RwLock::new(MediaWiki::wikipedia("en")));
+
+/// Points [`SearchEngines::Wikipedia`] at `base_url` (the site root, with
+/// `{lang}` where the language edition goes, e.g.
+/// `https://{lang}.wikipedia.org`), searched in `language` unless a search
+/// asks for another. Call once at startup, before any search.
+pub fn configure_wikipedia(base_url: &str, language: &str) {
+    *WIKIPEDIA.write().unwrap() = MediaWiki::wikipedia_at(base_url, language);
+}
+
+fn wikipedia() -> MediaWiki {
+    WIKIPEDIA.read().unwrap().clone()
+}
+
+impl From<RawNews> for CachedNews {
+    fn from(r: RawNews) -> Self {
+        CachedNews {
+            url: r.url,
+            title: r.title,
+            snippet: r.snippet,
+            source: r.source,
+            published: r.published,
+            thumbnail: r.thumbnail,
+        }
+    }
+}
+
+impl From<RawVideo> for CachedVideo {
+    fn from(r: RawVideo) -> Self {
+        CachedVideo {
+            url: r.url,
+            title: r.title,
+            duration_secs: r.duration.map(|d| d.as_secs()),
+            uploader: r.uploader,
+            published: r.published,
+            thumbnail: r.thumbnail,
+        }
+    }
+}
+
+/// Every [`DeclarativeEngine`] registered so far, addressed by name through
+/// [`SearchEngines::Declarative`]/[`ImageEngines::Declarative`]. Those
+/// variants are `Copy`, so each name is leaked once, on registration; as a
+/// name can't be registered twice, that's bounded by the engines loaded.
+static DECLARATIVE_ENGINES: RwLock<Vec<(&'static str, DeclarativeEngine)>> =
+    RwLock::new(Vec::new());
+
+/// Names of the hand-written engines, every tab's included.
+const BUILT_IN_ENGINES: [SearchEngines; 7] = [
+    SearchEngines::Brave,
+    SearchEngines::DuckDuckGo,
+    SearchEngines::Mojeek,
+    SearchEngines::Startpage,
+    SearchEngines::Qwant,
+    SearchEngines::Wikipedia,
+    SearchEngines::Marginalia,
+];
+
+/// Why `name` can't be registered: engines are picked by name,
+/// case-insensitively, so it mustn't match a built-in or registered one.
+fn name_taken<'a>(name: &str, registered: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    let name = name.trim();
+    let builtin = BUILT_IN_ENGINES
+        .into_iter()
+        .map(|e| -> &'a str { e.name() });
+    builtin
+        .chain(registered)
+        .find(|taken| taken.trim().eq_ignore_ascii_case(name))
+        .map(|taken| format!("{name}: the name is taken by the {taken} engine"))
+}
+
+/// Adds engines whose names were already checked with [`name_taken`].
+fn register_unchecked(
+    registered: &mut Vec<(&'static str, DeclarativeEngine)>,
+    engine: DeclarativeEngine,
+) -> &'static str {
+    let name: &'static str = Box::leak(engine.name().trim().into());
+    registered.push((name, engine));
+    name
+}
+
+/// Registers `engine` so it's queried as part of [`SearchEngines::all`]
+/// and/or [`ImageEngines::all`] (whichever sections its definition has).
+/// Fails if its name is already taken; a changed definition needs a
+/// restart to replace the one loaded.
+pub fn register_declarative_engine(engine: DeclarativeEngine) -> Result<(), DefinitionError> {
+    let mut registered = DECLARATIVE_ENGINES.write().unwrap();
+    if let Some(taken) = name_taken(engine.name(), registered.iter().map(|(n, _)| *n)) {
+        return Err(DefinitionError::Invalid(taken));
     }
+    register_unchecked(&mut registered, engine);
+    Ok(())
+}
+
+/// Loads and registers every `.toml`/`.json` engine definition in `dir`,
+/// returning the registered names. Fails on the first invalid definition
+/// without registering any of them, so a typo can't leave half a directory
+/// loaded.
+pub fn load_declarative_engines(
+    dir: impl AsRef<Path>,
+) -> Result<Vec<&'static str>, DefinitionError> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .map_err(DefinitionError::Io)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            matches!(
+                p.extension().and_then(|e| e.to_str()),
+                Some("toml" | "json")
+            )
+        })
+        .collect();
+    paths.sort();
+
+    let engines = paths
+        .iter()
+        .map(DeclarativeEngine::from_file)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Every name is checked, against each other too, before any is taken.
+    let mut registered = DECLARATIVE_ENGINES.write().unwrap();
+    for (i, engine) in engines.iter().enumerate() {
+        let earlier = engines[..i].iter().map(|e| e.name());
+        let taken = registered.iter().map(|(n, _)| *n).chain(earlier);
+        if let Some(taken) = name_taken(engine.name(), taken) {
+            return Err(DefinitionError::Invalid(taken));
+        }
+    }
+    Ok(engines
+        .into_iter()
+        .map(|engine| register_unchecked(&mut registered, engine))
+        .collect())
+}
+
+fn declarative_engine(name: &str) -> Option<DeclarativeEngine> {
+    DECLARATIVE_ENGINES
+        .read()
+        .unwrap()
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, e)| e.clone())
+}
+
+/// Looked up by name on every fetch: the [`SearchEngines::Declarative`]
+/// or [`ImageEngines::Declarative`] a source is made from only carries the
+/// name.
+fn registered_declarative_engine(name: &str) -> Result<DeclarativeEngine, SourceError> {
+    declarative_engine(name).ok_or_else(|| {
+        SourceError::Other(format!(
+            "no declarative engine named {name:?} is registered"
+        ))
+    })
+}
+
+fn declarative_engines_where(pred: impl Fn(&DeclarativeEngine) -> bool) -> Vec<&'static str> {
+    DECLARATIVE_ENGINES
+        .read()
+        .unwrap()
+        .iter()
+        .filter(|(_, e)| pred(e))
+        .map(|(n, _)| *n)
+        .collect()
+}
+
+/// An engine name (e.g. from a config file) that isn't one of the engines
+/// for that kind of search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownEngine {
+    name: String,
+    kind: &'static str,
+    known: Vec<&'static str>,
+}
+
+impl fmt::Display for UnknownEngine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown {} engine {:?} (expected one of: {})",
+            self.kind,
+            self.name,
+            self.known.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for UnknownEngine {}
+
+/// Finds the engine in `candidates` named `name`, ignoring case.
+fn parse_engine<E: Copy>(
+    name: &str,
+    kind: &'static str,
+    candidates: Vec<E>,
+    name_of: fn(E) -> &'static str,
+) -> Result<E, UnknownEngine> {
+    candidates
+        .iter()
+        .copied()
+        .find(|&e| name_of(e).eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| UnknownEngine {
+            name: name.to_string(),
+            kind,
+            known: candidates.into_iter().map(name_of).collect(),
+        })
+}
+
+/// Engines deserialize from their names, as parsed by their `FromStr`.
+fn deserialize_engine<'de, D, E>(deserializer: D) -> Result<E, D::Error>
+where
+    D: Deserializer<'de>,
+    E: FromStr<Err = UnknownEngine>,
+{
+    let name = String::deserialize(deserializer)?;
+    name.parse().map_err(de::Error::custom)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SearchEngines {
     Brave,
     DuckDuckGo,
+    Mojeek,
+    Startpage,
+    Qwant,
+    /// Wikipedia in the search's language (or the configured one, see
+    /// [`configure_wikipedia`]), via the MediaWiki search API.
+    Wikipedia,
+    /// Opt-in only: never part of [`SearchEngines::all`], so it's only
+    /// queried when asked for by name via [`SearchBuilder::engine`].
+    Marginalia,
+    /// A [`DeclarativeEngine`] registered under this name (see
+    /// [`register_declarative_engine`]).
+    Declarative(&'static str),
 }
 
 impl SearchEngines {
-    /// Every known text-search engine; the default set for [`SearchBuilder`].
+    /// Every default text-search engine; the default set for
+    /// [`SearchBuilder`]. Opt-in engines (e.g. [`SearchEngines::Marginalia`])
+    /// are deliberately left out.
     pub fn all() -> Vec<Self> {
-        vec![Self::Brave, Self::DuckDuckGo]
+        let mut all = vec![
+            Self::Brave,
+            Self::DuckDuckGo,
+            Self::Mojeek,
+            Self::Startpage,
+            Self::Qwant,
+            Self::Wikipedia,
+        ];
+        all.extend(
+            declarative_engines_where(DeclarativeEngine::supports_search)
+                .into_iter()
+                .map(Self::Declarative),
+        );
+        all
     }
 
     fn name(self) -> &'static str {
         match self {
             Self::Brave => Brave.name(),
             Self::DuckDuckGo => DuckDuckGo.name(),
+            Self::Mojeek => Mojeek.name(),
+            Self::Startpage => Startpage.name(),
+            Self::Qwant => Qwant.name(),
+            Self::Wikipedia => MediaWiki::WIKIPEDIA,
+            Self::Marginalia => Marginalia.name(),
+            Self::Declarative(name) => name,
+        }
+    }
+
+    /// See [`EngineInfo::honors`]. An unregistered declarative engine is
+    /// left in, to fail visibly on fetch.
+    fn honors(self, params: &SearchParams) -> bool {
+        match self {
+            Self::Brave => Brave.honors(params),
+            Self::DuckDuckGo => DuckDuckGo.honors(params),
+            Self::Mojeek => Mojeek.honors(params),
+            Self::Startpage => Startpage.honors(params),
+            Self::Qwant => Qwant.honors(params),
+            Self::Wikipedia => wikipedia().honors(params),
+            Self::Marginalia => Marginalia.honors(params),
+            Self::Declarative(name) => declarative_engine(name).is_none_or(|e| e.honors(params)),
         }
     }
 
-    fn source(self) -> Arc<dyn EngineSource<CachedResult>> {
+    fn source(self) -> Arc<dyn EngineSource<CachedResult, SearchParams>> {
+        let name = self.name();
         match self {
-            Self::Brave => Arc::new(BraveTextSource),
-            Self::DuckDuckGo => Arc::new(DdgTextSource),
+            Self::Brave => engine_source(name, |query, start, params| {
+                page(Brave.search_results(query, start, ENGINE_PAGE_HINT, params))
+            }),
+            Self::DuckDuckGo => engine_source(name, |query, start, params| {
+                page(DuckDuckGo.search_results(query, start, ENGINE_PAGE_HINT, params))
+            }),
+            Self::Mojeek => engine_source(name, |query, start, params| {
+                page(Mojeek.search_results(query, start, ENGINE_PAGE_HINT, params))
+            }),
+            Self::Startpage => engine_source(name, |query, start, params| {
+                page(Startpage.search_results(query, start, ENGINE_PAGE_HINT, params))
+            }),
+            Self::Qwant => engine_source(name, |query, start, params| {
+                page(Qwant.search_results(query, start, ENGINE_PAGE_HINT, params))
+            }),
+            Self::Wikipedia => engine_source(name, |query, start, params| {
+                page(async move {
+                    wikipedia()
+                        .search_results(query, start, ENGINE_PAGE_HINT, params)
+                        .await
+                })
+            }),
+            Self::Marginalia => engine_source(name, |query, start, params| {
+                page(Marginalia.search_results(query, start, ENGINE_PAGE_HINT, params))
+            }),
+            Self::Declarative(_) => engine_source(name, move |query, start, params| {
+                Box::pin(async move {
+                    let engine = registered_declarative_engine(name)?;
+                    page(engine.search_results(query, start, ENGINE_PAGE_HINT, params)).await
+                })
+            }),
         }
     }
 }
 
+/// Case-insensitive, by the engine's display name (e.g. `"duckduckgo"`).
+/// [`Declarative`](Self::Declarative) engines only parse once registered.
+impl FromStr for SearchEngines {
+    type Err = UnknownEngine;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let mut candidates = Self::all();
+        // Opt-in engines aren't in `all()`, but can still be asked for.
+        candidates.push(Self::Marginalia);
+        parse_engine(name, "text search", candidates, Self::name)
+    }
+}
+
+impl<'de> Deserialize<'de> for SearchEngines {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_engine(deserializer)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ImageEngines {
     Brave,
+    Qwant,
+    DuckDuckGo,
+    /// A [`DeclarativeEngine`] registered under this name (see
+    /// [`register_declarative_engine`]).
+    Declarative(&'static str),
 }
 
 impl ImageEngines {
     /// Every known image-search engine; the default set for [`ImageSearchBuilder`].
     pub fn all() -> Vec<Self> {
-        vec![Self::Brave]
+        let mut all = vec![Self::Brave, Self::Qwant, Self::DuckDuckGo];
+        all.extend(
+            declarative_engines_where(DeclarativeEngine::supports_images)
+                .into_iter()
+                .map(Self::Declarative),
+        );
+        all
     }
 
     fn name(self) -> &'static str {
         match self {
             Self::Brave => Brave.name(),
+            Self::Qwant => Qwant.name(),
+            Self::DuckDuckGo => DuckDuckGo.name(),
+            Self::Declarative(name) => name,
         }
     }
 
-    fn source(self) -> Arc<dyn EngineSource<CachedImage>> {
+    /// See [`SearchEngines::honors`].
+    fn honors(self, params: &SearchParams) -> bool {
         match self {
-            Self::Brave => Arc::new(BraveImageSource),
+            Self::Brave => Brave.honors_images(params),
+            Self::Qwant => Qwant.honors_images(params),
+            Self::DuckDuckGo => DuckDuckGo.honors_images(params),
+            Self::Declarative(name) => {
+                declarative_engine(name).is_none_or(|e| e.honors_images(params))
+            }
         }
     }
-}
-
-static TEXT_CACHE: OnceCell<MergedCache<CachedResult>> = OnceCell::const_new();
 
-async fn text_cache() -> &'static MergedCache<CachedResult> {
-    TEXT_CACHE
-        .get_or_init(|| async {
-            let pool = search_cache::shared_pool().await.clone();
-            MergedCache::new(pool, "text", Arc::new(DomainWordRanker))
-        })
-        .await
+    fn source(self) -> Arc<dyn EngineSource<CachedImage, SearchParams>> {
+        let name = self.name();
+        match self {
+            Self::Brave => engine_source(name, |query, start, params| {
+                page(Brave.search_images(query, start, ENGINE_PAGE_HINT, params))
+            }),
+            Self::Qwant => engine_source(name, |query, start, params| {
+                page(Qwant.search_images(query, start, ENGINE_PAGE_HINT, params))
+            }),
+            Self::DuckDuckGo => engine_source(name, |query, start, params| {
+                page(DuckDuckGo.search_images(query, start, ENGINE_PAGE_HINT, params))
+            }),
+            Self::Declarative(_) => engine_source(name, move |query, start, params| {
+                Box::pin(async move {
+                    let engine = registered_declarative_engine(name)?;
+                    page(engine.search_images(query, start, ENGINE_PAGE_HINT, params)).await
+                })
+            }),
+        }
+    }
 }
 
-static IMAGE_CACHE: OnceCell<MergedCache<CachedImage>> = OnceCell::const_new();
+/// Case-insensitive, by the engine's display name (e.g. `"duckduckgo"`).
+/// [`Declarative`](Self::Declarative) engines only parse once registered.
+impl FromStr for ImageEngines {
+    type Err = UnknownEngine;
 
-async fn image_cache() -> &'static MergedCache<CachedImage> {
-    IMAGE_CACHE
-        .get_or_init(|| async {
-            let pool = search_cache::shared_pool().await.clone();
-            MergedCache::new(pool, "image", Arc::new(UrlSortRanker))
-        })
-        .await
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        parse_engine(name, "image search", Self::all(), Self::name)
+    }
 }
 
-/// True only when there is nothing usable to return: every requested engine
-/// was actually contacted this call, and none of them succeeded.
-fn all_contacted_engines_failed(outcomes: &[(String, EngineOutcome)]) -> bool {
-    !outcomes.is_empty() && outcomes.iter().all(|(_, o)| !matches!(o, EngineOutcome::Ok))
+impl<'de> Deserialize<'de> for ImageEngines {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_engine(deserializer)
+    }
 }
 
-/// Builds and runs a text search across one or more engines.
-///
-/// Defaults: every engine in [`SearchEngines::all`], 10 results from 0, 3s timeout.
-pub struct SearchBuilder {
-    query: String,
-    engines: Vec<SearchEngines>,
-    start: usize,
-    count: usize,
-    timeout: Duration,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NewsEngines {
+    Brave,
+    DuckDuckGo,
 }
 
-impl SearchBuilder {
-    pub fn new(query: impl Into<String>) -> Self {
-        Self {
-            query: query.into(),
-            engines: Vec::new(),
-            start: 0,
-            count: DEFAULT_SEARCH_COUNT,
-            timeout: Duration::from_secs(ENGINE_TIMEOUT),
-        }
+impl NewsEngines {
+    /// Every known news engine; the default set for [`NewsSearchBuilder`].
+    pub fn all() -> Vec<Self> {
+        vec![Self::Brave, Self::DuckDuckGo]
     }
 
-    /// Adds an engine to query; duplicates are ignored. Defaults to all engines if never called.
-    pub fn engine(mut self, engine: SearchEngines) -> Self {
-        if !self.engines.contains(&engine) {
-            self.engines.push(engine);
+    fn name(self) -> &'static str {
+        match self {
+            Self::Brave => Brave.name(),
+            Self::DuckDuckGo => DuckDuckGo.name(),
         }
-        self
     }
 
-    /// Adds several engines at once; same as calling [`engine`](Self::engine) per item.
-    pub fn engines(mut self, engines: impl IntoIterator<Item = SearchEngines>) -> Self {
-        for engine in engines {
-            self = self.engine(engine);
+    /// See [`SearchEngines::honors`].
+    fn honors(self, params: &SearchParams) -> bool {
+        match self {
+            Self::Brave => Brave.honors_news(params),
+            Self::DuckDuckGo => DuckDuckGo.honors_news(params),
         }
-        self
     }
 
-    /// Offset into the merged result list (for pagination). Default 0.
-    pub fn start(mut self, start: usize) -> Self {
-        self.start = start;
-        self
+    fn source(self) -> Arc<dyn EngineSource<CachedNews, SearchParams>> {
+        let name = self.name();
+        match self {
+            Self::Brave => engine_source(name, |query, start, params| {
+                page(Brave.search_news(query, start, ENGINE_PAGE_HINT, params))
+            }),
+            Self::DuckDuckGo => engine_source(name, |query, start, params| {
+                page(DuckDuckGo.search_news(query, start, ENGINE_PAGE_HINT, params))
+            }),
+        }
     }
+}
 
-    /// Number of results to return. Default 10.
-    pub fn count(mut self, count: usize) -> Self {
-        self.count = count;
-        self
+/// Case-insensitive, by the engine's display name (e.g. `"duckduckgo"`).
+impl FromStr for NewsEngines {
+    type Err = UnknownEngine;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        parse_engine(name, "news", Self::all(), Self::name)
     }
+}
 
-    /// Per-engine, per-round timeout. Default 3 seconds.
-    pub fn timeout(mut self, timeout: Duration) -> Self {
-        self.timeout = timeout;
-        self
+impl<'de> Deserialize<'de> for NewsEngines {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_engine(deserializer)
     }
+}
 
-    /// Runs the search, extending the merged cache as needed and ranking any
-    /// newly-discovered results by [`sort_results`].
-    pub async fn search(self) -> Result<SearchResponse<SearchResult>, FetchError> {
-        let engines = if self.engines.is_empty() {
-            SearchEngines::all()
-        } else {
-            self.engines
-        };
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoEngines {
+    Brave,
+}
 
-        let sources: Vec<Arc<dyn EngineSource<CachedResult>>> =
+impl VideoEngines {
+    /// Every known video engine; the default set for [`VideoSearchBuilder`].
+    pub fn all() -> Vec<Self> {
+        vec![Self::Brave]
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Brave => Brave.name(),
+        }
+    }
+
+    /// See [`SearchEngines::honors`].
+    fn honors(self, params: &SearchParams) -> bool {
+        match self {
+            Self::Brave => Brave.honors_videos(params),
+        }
+    }
+
+    fn source(self) -> Arc<dyn EngineSource<CachedVideo, SearchParams>> {
+        let name = self.name();
+        match self {
+            Self::Brave => engine_source(name, |query, start, params| {
+                page(Brave.search_videos(query, start, ENGINE_PAGE_HINT, params))
+            }),
+        }
+    }
+}
+
+/// Case-insensitive, by the engine's display name (e.g. `"duckduckgo"`).
+impl FromStr for VideoEngines {
+    type Err = UnknownEngine;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        parse_engine(name, "video", Self::all(), Self::name)
+    }
+}
+
+impl<'de> Deserialize<'de> for VideoEngines {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_engine(deserializer)
+    }
+}
+
+fn fresh_for<R: CacheableRow>(cache: MergedCache<R>, ttl: Option<Duration>) -> MergedCache<R> {
+    match ttl {
+        Some(ttl) => cache.fresh_for(ttl),
+        None => cache,
+    }
+}
+
+static TEXT_CACHE: OnceCell<MergedCache<CachedResult>> = OnceCell::const_new();
+
+async fn text_cache() -> &'static MergedCache<CachedResult> {
+    TEXT_CACHE
+        .get_or_init(|| async {
+            let cache = MergedCache::new(shared_store().await, "text", Arc::new(DomainWordRanker))
+                .observed_by(Arc::new(metrics::Recorder));
+            fresh_for(cache, CACHE_FRESHNESS.read().unwrap().general)
+        })
+        .await
+}
+
+static IMAGE_CACHE: OnceCell<MergedCache<CachedImage>> = OnceCell::const_new();
+
+async fn image_cache() -> &'static MergedCache<CachedImage> {
+    IMAGE_CACHE
+        .get_or_init(|| async {
+            let cache = MergedCache::new(shared_store().await, "image", Arc::new(UrlSortRanker))
+                .observed_by(Arc::new(metrics::Recorder));
+            fresh_for(cache, CACHE_FRESHNESS.read().unwrap().images)
+        })
+        .await
+}
+
+static NEWS_CACHE: OnceCell<MergedCache<CachedNews>> = OnceCell::const_new();
+
+async fn news_cache() -> &'static MergedCache<CachedNews> {
+    NEWS_CACHE
+        .get_or_init(|| async {
+            let cache = MergedCache::new(shared_store().await, "news", Arc::new(RecencyRanker))
+                .observed_by(Arc::new(metrics::Recorder));
+            fresh_for(cache, CACHE_FRESHNESS.read().unwrap().news)
+        })
+        .await
+}
+
+static VIDEO_CACHE: OnceCell<MergedCache<CachedVideo>> = OnceCell::const_new();
+
+async fn video_cache() -> &'static MergedCache<CachedVideo> {
+    VIDEO_CACHE
+        .get_or_init(|| async {
+            let cache =
+                MergedCache::new(shared_store().await, "video", Arc::new(EngineOrderRanker))
+                    .observed_by(Arc::new(metrics::Recorder));
+            fresh_for(cache, CACHE_FRESHNESS.read().unwrap().videos)
+        })
+        .await
+}
+
+/// Recently-served suggestion lists, keyed by normalized query. Kept in
+/// memory rather than in the SQLite cache: entries are tiny, short-lived,
+/// and losing them on restart costs nothing.
+static SUGGESTIONS: LazyLock<Mutex<SuggestCache>> =
+    LazyLock::new(|| Mutex::new(SuggestCache::new(SUGGEST_TTL, SUGGEST_CACHE_CAPACITY)));
+
+struct SuggestCache {
+    entries: HashMap<String, (Instant, Vec<String>)>,
+    /// Keys oldest first, with when they were inserted; one whose time no
+    /// longer matches its entry has been inserted again since.
+    order: VecDeque<(Instant, String)>,
+    ttl: Duration,
+    capacity: usize,
+}
+
+impl SuggestCache {
+    fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            ttl,
+            capacity,
+        }
+    }
+
+    fn get(&self, key: &str, now: Instant) -> Option<Vec<String>> {
+        self.entries
+            .get(key)
+            .filter(|(at, _)| now.duration_since(*at) < self.ttl)
+            .map(|(_, suggestions)| suggestions.clone())
+    }
+
+    /// Drops expired entries, then the oldest ones while full. Both are at
+    /// the front of `order`, so this only ever looks at what it drops.
+    fn insert(&mut self, key: String, suggestions: Vec<String>, now: Instant) {
+        while let Some((at, _)) = self.order.front() {
+            let full = self.entries.len() >= self.capacity && !self.entries.contains_key(&key);
+            if !full && now.duration_since(*at) < self.ttl {
+                break;
+            }
+            let (at, oldest) = self.order.pop_front().expect("front was just checked");
+            if self
+                .entries
+                .get(&oldest)
+                .is_some_and(|(current, _)| *current == at)
+            {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.order.push_back((now, key.clone()));
+        self.entries.insert(key, (now, suggestions));
+    }
+}
+
+/// Query completions for `query` from every suggest-capable engine, merged
+/// and deduplicated. Best-effort: an engine that fails or is too slow just
+/// contributes nothing, and total failure is an empty list rather than an
+/// error.
+pub async fn suggest(query: &str) -> Vec<String> {
+    let key = query.trim().to_lowercase();
+    if key.is_empty() {
+        return Vec::new();
+    }
+    if let Some(hit) = SUGGESTIONS.lock().unwrap().get(&key, Instant::now()) {
+        return hit;
+    }
+
+    let (brave, ddg) = tokio::join!(
+        engine_suggestions(Brave, query),
+        engine_suggestions(DuckDuckGo, query)
+    );
+    // Don't cache an outage as "no suggestions".
+    if brave.is_none() && ddg.is_none() {
+        return Vec::new();
+    }
+
+    let merged = merge_suggestions(&key, [brave, ddg].into_iter().flatten().collect());
+    SUGGESTIONS
+        .lock()
+        .unwrap()
+        .insert(key, merged.clone(), Instant::now());
+    merged
+}
+
+async fn engine_suggestions(engine: impl SuggestEngine, query: &str) -> Option<Vec<String>> {
+    match tokio::time::timeout(SUGGEST_TIMEOUT, engine.suggestions(query)).await {
+        Ok(Ok(suggestions)) => Some(suggestions),
+        Ok(Err(e)) => {
+            log::debug!("{} suggestions failed: {e}", engine.name());
+            None
+        }
+        Err(_) => {
+            log::debug!("{} suggestions timed out", engine.name());
+            None
+        }
+    }
+}
+
+/// Interleaves the engines' lists so each one's best guesses come first,
+/// dropping case-insensitive duplicates and echoes of the query itself.
+fn merge_suggestions(query: &str, lists: Vec<Vec<String>>) -> Vec<String> {
+    let mut seen = HashSet::from([query.to_string()]);
+    let mut merged = Vec::new();
+    let longest = lists.iter().map(Vec::len).max().unwrap_or(0);
+    for i in 0..longest {
+        for suggestion in lists.iter().filter_map(|list| list.get(i)) {
+            let suggestion = suggestion.trim();
+            if !suggestion.is_empty() && seen.insert(suggestion.to_lowercase()) {
+                merged.push(suggestion.to_string());
+            }
+        }
+    }
+    merged.truncate(MAX_SUGGESTIONS);
+    merged
+}
+
+/// True only when there is nothing usable to return: every requested engine
+/// was actually contacted this call, and none of them succeeded.
+fn all_contacted_engines_failed(outcomes: &[(String, EngineOutcome)]) -> bool {
+    !outcomes.is_empty() && outcomes.iter().all(|(_, o)| !matches!(o, EngineOutcome::Ok))
+}
+
+/// One report per engine in `names`, in that order: its outcome from this
+/// call, or [`EngineStatus::Ok`] if it wasn't contacted (served from cache).
+/// Then one per engine in `skipped`, left out of the search.
+fn engine_reports<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+    skipped: impl IntoIterator<Item = &'a str>,
+    outcomes: &[(String, EngineOutcome)],
+) -> Vec<EngineReport> {
+    let searched = names.into_iter().map(|name| {
+        let status = outcomes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, o)| EngineStatus::from(o))
+            .unwrap_or(EngineStatus::Ok);
+        (name, status)
+    });
+    let skipped = skipped
+        .into_iter()
+        .map(|name| (name, EngineStatus::Skipped));
+    searched
+        .chain(skipped)
+        .map(|(name, status)| EngineReport {
+            engine: name.to_string(),
+            status,
+        })
+        .collect()
+}
+
+/// Runs [`MergedCache::stream_extend`] in the background, translating its
+/// events into [`SearchEvent`]s — the shared half of every builder's
+/// `stream`.
+#[allow(clippy::too_many_arguments)]
+fn stream_search<R, T>(
+    cache: &'static MergedCache<R>,
+    sources: Vec<Arc<dyn EngineSource<R, SearchParams>>>,
+    skipped: Vec<&'static str>,
+    query: String,
+    params: SearchParams,
+    snapshot: Option<i64>,
+    start: usize,
+    count: usize,
+    timeout: Duration,
+) -> UnboundedReceiver<SearchEvent<T>>
+where
+    R: CacheableRow,
+    T: From<MergedRowResult<R>> + Send + 'static,
+{
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let (extend_tx, mut extend_rx) = mpsc::unbounded_channel();
+        let extend = cache.stream_extend(
+            &query, &params, &sources, snapshot, start, count, timeout, extend_tx,
+        );
+        let forward = async {
+            while let Some(event) = extend_rx.recv().await {
+                let event = match event {
+                    ExtendEvent::Rows(rows) => {
+                        SearchEvent::Results(rows.into_iter().map(T::from).collect())
+                    }
+                    ExtendEvent::Engine(engine, outcome) => SearchEvent::Engine(EngineReport {
+                        engine,
+                        status: EngineStatus::from(&outcome),
+                    }),
+                };
+                // Nobody listening anymore is fine — the search still
+                // finishes, so its results are cached for next time.
+                let _ = tx.send(event);
+            }
+        };
+        let (extend, ()) = tokio::join!(extend, forward);
+
+        let last = match extend {
+            Err(e) => SearchEvent::Failed(e.into()),
+            Ok(extend)
+                if extend.rows.is_empty()
+                    && all_contacted_engines_failed(&extend.engine_outcomes) =>
+            {
+                SearchEvent::Failed(FetchError::AllEnginesFailed)
+            }
+            Ok(extend) => SearchEvent::Done {
+                engines: engine_reports(
+                    sources.iter().map(|s| s.name()),
+                    skipped,
+                    &extend.engine_outcomes,
+                ),
+                has_more: extend.has_more,
+                snapshot: extend.snapshot,
+            },
+        };
+        let _ = tx.send(last);
+    });
+    rx
+}
+
+/// Builds and runs a text search across one or more engines.
+///
+/// Defaults: every engine in [`SearchEngines::all`], 10 results from 0, 3s timeout.
+pub struct SearchBuilder {
+    query: String,
+    params: SearchParams,
+    engines: Vec<SearchEngines>,
+    snapshot: Option<i64>,
+    start: usize,
+    count: usize,
+    timeout: Duration,
+}
+
+impl SearchBuilder {
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            params: SearchParams::default(),
+            engines: Vec::new(),
+            snapshot: None,
+            start: 0,
+            count: DEFAULT_SEARCH_COUNT,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Adds an engine to query; duplicates are ignored. Defaults to all engines if never called.
+    pub fn engine(mut self, engine: SearchEngines) -> Self {
+        if !self.engines.contains(&engine) {
+            self.engines.push(engine);
+        }
+        self
+    }
+
+    /// Adds several engines at once; same as calling [`engine`](Self::engine) per item.
+    pub fn engines(mut self, engines: impl IntoIterator<Item = SearchEngines>) -> Self {
+        for engine in engines {
+            self = self.engine(engine);
+        }
+        self
+    }
+
+    /// Pages the snapshot an earlier search returned as
+    /// [`SearchResponse::snapshot`], so pages don't shift if the query is
+    /// refreshed in between. Default (`None`): the query's current snapshot.
+    pub fn snapshot(mut self, snapshot: Option<i64>) -> Self {
+        self.snapshot = snapshot;
+        self
+    }
+
+    /// Offset into the merged result list (for pagination). Default 0.
+    pub fn start(mut self, start: usize) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// Number of results to return. Default 10.
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Per-engine, per-round timeout. Defaults to [`DEFAULT_TIMEOUT`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Language with an optional region (e.g. `"en-US"`) to ask engines
+    /// for. Default: each engine's own.
+    pub fn locale(mut self, locale: impl Into<String>) -> Self {
+        self.params.locale = Some(locale.into());
+        self
+    }
+
+    /// Default [`SafeSearch::Moderate`].
+    pub fn safe_search(mut self, safe_search: SafeSearch) -> Self {
+        self.params.safe_search = safe_search;
+        self
+    }
+
+    /// Only return results from within this range. Default: any time.
+    pub fn time_range(mut self, time_range: TimeRange) -> Self {
+        self.params.time_range = Some(time_range);
+        self
+    }
+
+    /// Replaces every search parameter at once.
+    pub fn params(mut self, params: SearchParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Runs the search, extending the merged cache as needed and ranking any
+    /// newly-discovered results by [`sort_results`].
+    pub async fn search(self) -> Result<SearchResponse<SearchResult>, FetchError> {
+        let engines = if self.engines.is_empty() {
+            SearchEngines::all()
+        } else {
+            self.engines
+        };
+        let (engines, skipped): (Vec<_>, Vec<_>) =
+            engines.into_iter().partition(|e| e.honors(&self.params));
+
+        let sources: Vec<Arc<dyn EngineSource<CachedResult, SearchParams>>> =
             engines.iter().map(|e| e.source()).collect();
 
         let extend = text_cache()
             .await
-            .get_or_extend(&self.query, &sources, self.start, self.count, self.timeout)
+            .get_or_extend(
+                &self.query,
+                &self.params,
+                &sources,
+                self.snapshot,
+                self.start,
+                self.count,
+                self.timeout,
+            )
             .await?;
 
         if extend.rows.is_empty() && all_contacted_engines_failed(&extend.engine_outcomes) {
             return Err(FetchError::AllEnginesFailed);
         }
 
-        let reports = engines
-            .iter()
-            .map(|e| {
-                let name = e.name();
-                let status = extend
-                    .engine_outcomes
-                    .iter()
-                    .find(|(n, _)| n == name)
-                    .map(|(_, o)| EngineStatus::from(o))
-                    .unwrap_or(EngineStatus::Ok);
-                EngineReport {
-                    engine: name.to_string(),
-                    status,
-                }
-            })
-            .collect();
+        let reports = engine_reports(
+            engines.iter().map(|e| e.name()),
+            skipped.iter().map(|e| e.name()),
+            &extend.engine_outcomes,
+        );
 
-        let results = extend
-            .rows
-            .into_iter()
-            .map(|r| SearchResult {
-                url: r.value.url,
-                title: r.value.title,
-                description: r.value.description,
-                engines: r.engines,
-                cached: r.cached,
-            })
-            .collect();
+        let results = extend.rows.into_iter().map(SearchResult::from).collect();
 
         Ok(SearchResponse {
             results,
             engines: reports,
             has_more: extend.has_more,
+            snapshot: extend.snapshot,
         })
     }
+
+    /// Like [`search`](Self::search), but sends results as each engine
+    /// answers rather than once they all have; see [`SearchEvent`].
+    pub async fn stream(self) -> UnboundedReceiver<SearchEvent<SearchResult>> {
+        let engines = if self.engines.is_empty() {
+            SearchEngines::all()
+        } else {
+            self.engines
+        };
+        let (engines, skipped): (Vec<_>, Vec<_>) =
+            engines.into_iter().partition(|e| e.honors(&self.params));
+        let sources = engines.iter().map(|e| e.source()).collect();
+
+        stream_search(
+            text_cache().await,
+            sources,
+            skipped.iter().map(|e| e.name()).collect(),
+            self.query,
+            self.params,
+            self.snapshot,
+            self.start,
+            self.count,
+            self.timeout,
+        )
+    }
 }
 
 /// Builds and runs an image search across one or more engines.
@@ -540,7 +1594,9 @@ impl SearchBuilder {
 /// Defaults: every engine in [`ImageEngines::all`], 50 results from 0, 3s timeout.
 pub struct ImageSearchBuilder {
     query: String,
+    params: SearchParams,
     engines: Vec<ImageEngines>,
+    snapshot: Option<i64>,
     start: usize,
     count: usize,
     timeout: Duration,
@@ -550,10 +1606,12 @@ impl ImageSearchBuilder {
     pub fn new(query: impl Into<String>) -> Self {
         Self {
             query: query.into(),
+            params: SearchParams::default(),
             engines: Vec::new(),
+            snapshot: None,
             start: 0,
             count: DEFAULT_IMAGE_COUNT,
-            timeout: Duration::from_secs(ENGINE_TIMEOUT),
+            timeout: DEFAULT_TIMEOUT,
         }
     }
 
@@ -574,6 +1632,14 @@ impl ImageSearchBuilder {
         self
     }
 
+    /// Pages the snapshot an earlier search returned as
+    /// [`SearchResponse::snapshot`], so pages don't shift if the query is
+    /// refreshed in between. Default (`None`): the query's current snapshot.
+    pub fn snapshot(mut self, snapshot: Option<i64>) -> Self {
+        self.snapshot = snapshot;
+        self
+    }
+
     /// Offset into the merged result list (for pagination). Default 0.
     pub fn start(mut self, start: usize) -> Self {
         self.start = start;
@@ -586,12 +1652,37 @@ impl ImageSearchBuilder {
         self
     }
 
-    /// Per-engine, per-round timeout. Default 3 seconds.
+    /// Per-engine, per-round timeout. Defaults to [`DEFAULT_TIMEOUT`].
     pub fn timeout(mut self, timeout: Duration) -> Self {
         self.timeout = timeout;
         self
     }
 
+    /// Language with an optional region (e.g. `"en-US"`) to ask engines
+    /// for. Default: each engine's own.
+    pub fn locale(mut self, locale: impl Into<String>) -> Self {
+        self.params.locale = Some(locale.into());
+        self
+    }
+
+    /// Default [`SafeSearch::Moderate`].
+    pub fn safe_search(mut self, safe_search: SafeSearch) -> Self {
+        self.params.safe_search = safe_search;
+        self
+    }
+
+    /// Only return results from within this range. Default: any time.
+    pub fn time_range(mut self, time_range: TimeRange) -> Self {
+        self.params.time_range = Some(time_range);
+        self
+    }
+
+    /// Replaces every search parameter at once.
+    pub fn params(mut self, params: SearchParams) -> Self {
+        self.params = params;
+        self
+    }
+
     /// Runs the search, extending the merged cache as needed.
     pub async fn search(self) -> Result<SearchResponse<ImageResult>, FetchError> {
         let engines = if self.engines.is_empty() {
@@ -599,53 +1690,400 @@ impl ImageSearchBuilder {
         } else {
             self.engines
         };
+        let (engines, skipped): (Vec<_>, Vec<_>) =
+            engines.into_iter().partition(|e| e.honors(&self.params));
 
-        let sources: Vec<Arc<dyn EngineSource<CachedImage>>> =
+        let sources: Vec<Arc<dyn EngineSource<CachedImage, SearchParams>>> =
             engines.iter().map(|e| e.source()).collect();
 
         let extend = image_cache()
             .await
-            .get_or_extend(&self.query, &sources, self.start, self.count, self.timeout)
+            .get_or_extend(
+                &self.query,
+                &self.params,
+                &sources,
+                self.snapshot,
+                self.start,
+                self.count,
+                self.timeout,
+            )
             .await?;
 
         if extend.rows.is_empty() && all_contacted_engines_failed(&extend.engine_outcomes) {
             return Err(FetchError::AllEnginesFailed);
         }
 
-        let reports = engines
-            .iter()
-            .map(|e| {
-                let name = e.name();
-                let status = extend
-                    .engine_outcomes
-                    .iter()
-                    .find(|(n, _)| n == name)
-                    .map(|(_, o)| EngineStatus::from(o))
-                    .unwrap_or(EngineStatus::Ok);
-                EngineReport {
-                    engine: name.to_string(),
-                    status,
-                }
-            })
-            .collect();
+        let reports = engine_reports(
+            engines.iter().map(|e| e.name()),
+            skipped.iter().map(|e| e.name()),
+            &extend.engine_outcomes,
+        );
 
-        let results = extend
-            .rows
-            .into_iter()
-            .map(|r| ImageResult {
-                url: r.value.url,
-                title: r.value.title,
-                engines: r.engines,
-                cached: r.cached,
-            })
-            .collect();
+        let results = extend.rows.into_iter().map(ImageResult::from).collect();
+
+        Ok(SearchResponse {
+            results,
+            engines: reports,
+            has_more: extend.has_more,
+            snapshot: extend.snapshot,
+        })
+    }
+
+    /// Like [`search`](Self::search), but sends results as each engine
+    /// answers rather than once they all have; see [`SearchEvent`].
+    pub async fn stream(self) -> UnboundedReceiver<SearchEvent<ImageResult>> {
+        let engines = if self.engines.is_empty() {
+            ImageEngines::all()
+        } else {
+            self.engines
+        };
+        let (engines, skipped): (Vec<_>, Vec<_>) =
+            engines.into_iter().partition(|e| e.honors(&self.params));
+        let sources = engines.iter().map(|e| e.source()).collect();
+
+        stream_search(
+            image_cache().await,
+            sources,
+            skipped.iter().map(|e| e.name()).collect(),
+            self.query,
+            self.params,
+            self.snapshot,
+            self.start,
+            self.count,
+            self.timeout,
+        )
+    }
+}
+
+/// Builds and runs a news search across one or more engines.
+///
+/// Defaults: every engine in [`NewsEngines::all`], 10 stories from 0, 3s timeout.
+pub struct NewsSearchBuilder {
+    query: String,
+    params: SearchParams,
+    engines: Vec<NewsEngines>,
+    snapshot: Option<i64>,
+    start: usize,
+    count: usize,
+    timeout: Duration,
+}
+
+impl NewsSearchBuilder {
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            params: SearchParams::default(),
+            engines: Vec::new(),
+            snapshot: None,
+            start: 0,
+            count: DEFAULT_NEWS_COUNT,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Adds an engine to query; duplicates are ignored. Defaults to all engines if never called.
+    pub fn engine(mut self, engine: NewsEngines) -> Self {
+        if !self.engines.contains(&engine) {
+            self.engines.push(engine);
+        }
+        self
+    }
+
+    /// Adds several engines at once; same as calling [`engine`](Self::engine) per item.
+    pub fn engines(mut self, engines: impl IntoIterator<Item = NewsEngines>) -> Self {
+        for engine in engines {
+            self = self.engine(engine);
+        }
+        self
+    }
+
+    /// Pages the snapshot an earlier search returned as
+    /// [`SearchResponse::snapshot`], so pages don't shift if the query is
+    /// refreshed in between. Default (`None`): the query's current snapshot.
+    pub fn snapshot(mut self, snapshot: Option<i64>) -> Self {
+        self.snapshot = snapshot;
+        self
+    }
+
+    /// Offset into the merged result list (for pagination). Default 0.
+    pub fn start(mut self, start: usize) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// Number of stories to return. Default 10.
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Per-engine, per-round timeout. Defaults to [`DEFAULT_TIMEOUT`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Language with an optional region (e.g. `"en-US"`) to ask engines
+    /// for. Default: each engine's own.
+    pub fn locale(mut self, locale: impl Into<String>) -> Self {
+        self.params.locale = Some(locale.into());
+        self
+    }
+
+    /// Default [`SafeSearch::Moderate`].
+    pub fn safe_search(mut self, safe_search: SafeSearch) -> Self {
+        self.params.safe_search = safe_search;
+        self
+    }
+
+    /// Only return stories from within this range. Default: any time.
+    pub fn time_range(mut self, time_range: TimeRange) -> Self {
+        self.params.time_range = Some(time_range);
+        self
+    }
+
+    /// Replaces every search parameter at once.
+    pub fn params(mut self, params: SearchParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Runs the search, extending the merged cache as needed and ordering
+    /// each newly-fetched batch newest first.
+    pub async fn search(self) -> Result<SearchResponse<NewsResult>, FetchError> {
+        let engines = if self.engines.is_empty() {
+            NewsEngines::all()
+        } else {
+            self.engines
+        };
+        let (engines, skipped): (Vec<_>, Vec<_>) =
+            engines.into_iter().partition(|e| e.honors(&self.params));
+
+        let sources: Vec<Arc<dyn EngineSource<CachedNews, SearchParams>>> =
+            engines.iter().map(|e| e.source()).collect();
+
+        let extend = news_cache()
+            .await
+            .get_or_extend(
+                &self.query,
+                &self.params,
+                &sources,
+                self.snapshot,
+                self.start,
+                self.count,
+                self.timeout,
+            )
+            .await?;
+
+        if extend.rows.is_empty() && all_contacted_engines_failed(&extend.engine_outcomes) {
+            return Err(FetchError::AllEnginesFailed);
+        }
+
+        let reports = engine_reports(
+            engines.iter().map(|e| e.name()),
+            skipped.iter().map(|e| e.name()),
+            &extend.engine_outcomes,
+        );
+
+        let results = extend.rows.into_iter().map(NewsResult::from).collect();
 
         Ok(SearchResponse {
             results,
             engines: reports,
             has_more: extend.has_more,
+            snapshot: extend.snapshot,
         })
     }
+
+    /// Like [`search`](Self::search), but sends results as each engine
+    /// answers rather than once they all have; see [`SearchEvent`].
+    pub async fn stream(self) -> UnboundedReceiver<SearchEvent<NewsResult>> {
+        let engines = if self.engines.is_empty() {
+            NewsEngines::all()
+        } else {
+            self.engines
+        };
+        let (engines, skipped): (Vec<_>, Vec<_>) =
+            engines.into_iter().partition(|e| e.honors(&self.params));
+        let sources = engines.iter().map(|e| e.source()).collect();
+
+        stream_search(
+            news_cache().await,
+            sources,
+            skipped.iter().map(|e| e.name()).collect(),
+            self.query,
+            self.params,
+            self.snapshot,
+            self.start,
+            self.count,
+            self.timeout,
+        )
+    }
+}
+
+/// Builds and runs a video search across one or more engines.
+///
+/// Defaults: every engine in [`VideoEngines::all`], 10 videos from 0, 3s timeout.
+pub struct VideoSearchBuilder {
+    query: String,
+    params: SearchParams,
+    engines: Vec<VideoEngines>,
+    snapshot: Option<i64>,
+    start: usize,
+    count: usize,
+    timeout: Duration,
+}
+
+impl VideoSearchBuilder {
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            params: SearchParams::default(),
+            engines: Vec::new(),
+            snapshot: None,
+            start: 0,
+            count: DEFAULT_VIDEO_COUNT,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Adds an engine to query; duplicates are ignored. Defaults to all engines if never called.
+    pub fn engine(mut self, engine: VideoEngines) -> Self {
+        if !self.engines.contains(&engine) {
+            self.engines.push(engine);
+        }
+        self
+    }
+
+    /// Adds several engines at once; same as calling [`engine`](Self::engine) per item.
+    pub fn engines(mut self, engines: impl IntoIterator<Item = VideoEngines>) -> Self {
+        for engine in engines {
+            self = self.engine(engine);
+        }
+        self
+    }
+
+    /// Pages the snapshot an earlier search returned as
+    /// [`SearchResponse::snapshot`], so pages don't shift if the query is
+    /// refreshed in between. Default (`None`): the query's current snapshot.
+    pub fn snapshot(mut self, snapshot: Option<i64>) -> Self {
+        self.snapshot = snapshot;
+        self
+    }
+
+    /// Offset into the merged result list (for pagination). Default 0.
+    pub fn start(mut self, start: usize) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// Number of videos to return. Default 10.
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Per-engine, per-round timeout. Defaults to [`DEFAULT_TIMEOUT`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Language with an optional region (e.g. `"en-US"`) to ask engines
+    /// for. Default: each engine's own.
+    pub fn locale(mut self, locale: impl Into<String>) -> Self {
+        self.params.locale = Some(locale.into());
+        self
+    }
+
+    /// Default [`SafeSearch::Moderate`].
+    pub fn safe_search(mut self, safe_search: SafeSearch) -> Self {
+        self.params.safe_search = safe_search;
+        self
+    }
+
+    /// Only return videos from within this range. Default: any time.
+    pub fn time_range(mut self, time_range: TimeRange) -> Self {
+        self.params.time_range = Some(time_range);
+        self
+    }
+
+    /// Replaces every search parameter at once.
+    pub fn params(mut self, params: SearchParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Runs the search, extending the merged cache as needed.
+    pub async fn search(self) -> Result<SearchResponse<VideoResult>, FetchError> {
+        let engines = if self.engines.is_empty() {
+            VideoEngines::all()
+        } else {
+            self.engines
+        };
+        let (engines, skipped): (Vec<_>, Vec<_>) =
+            engines.into_iter().partition(|e| e.honors(&self.params));
+
+        let sources: Vec<Arc<dyn EngineSource<CachedVideo, SearchParams>>> =
+            engines.iter().map(|e| e.source()).collect();
+
+        let extend = video_cache()
+            .await
+            .get_or_extend(
+                &self.query,
+                &self.params,
+                &sources,
+                self.snapshot,
+                self.start,
+                self.count,
+                self.timeout,
+            )
+            .await?;
+
+        if extend.rows.is_empty() && all_contacted_engines_failed(&extend.engine_outcomes) {
+            return Err(FetchError::AllEnginesFailed);
+        }
+
+        let reports = engine_reports(
+            engines.iter().map(|e| e.name()),
+            skipped.iter().map(|e| e.name()),
+            &extend.engine_outcomes,
+        );
+
+        let results = extend.rows.into_iter().map(VideoResult::from).collect();
+
+        Ok(SearchResponse {
+            results,
+            engines: reports,
+            has_more: extend.has_more,
+            snapshot: extend.snapshot,
+        })
+    }
+
+    /// Like [`search`](Self::search), but sends results as each engine
+    /// answers rather than once they all have; see [`SearchEvent`].
+    pub async fn stream(self) -> UnboundedReceiver<SearchEvent<VideoResult>> {
+        let engines = if self.engines.is_empty() {
+            VideoEngines::all()
+        } else {
+            self.engines
+        };
+        let (engines, skipped): (Vec<_>, Vec<_>) =
+            engines.into_iter().partition(|e| e.honors(&self.params));
+        let sources = engines.iter().map(|e| e.source()).collect();
+
+        stream_search(
+            video_cache().await,
+            sources,
+            skipped.iter().map(|e| e.name()).collect(),
+            self.query,
+            self.params,
+            self.snapshot,
+            self.start,
+            self.count,
+            self.timeout,
+        )
+    }
 }
 
 #[cfg(test)]
@@ -760,6 +2198,249 @@ mod test {
         assert_eq!(ranked[1].url, "https://totally-unrelated.example/other");
     }
 
+    fn news(url: &str, published: Option<&str>) -> CachedNews {
+        CachedNews {
+            url: url.to_string(),
+            title: "t".into(),
+            snippet: "s".into(),
+            source: "o".into(),
+            published: published.map(|p| p.parse().unwrap()),
+            thumbnail: None,
+        }
+    }
+
+    #[test]
+    fn recency_ranker_puts_newest_first_and_undated_last() {
+        let ranked = RecencyRanker.rank(
+            "rust",
+            vec![
+                news("https://undated.example/", None),
+                news("https://old.example/", Some("2024-01-01T00:00:00Z")),
+                news("https://new.example/", Some("2025-01-01T00:00:00Z")),
+            ],
+        );
+
+        let urls: Vec<_> = ranked.iter().map(|n| n.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://new.example/",
+                "https://old.example/",
+                "https://undated.example/"
+            ]
+        );
+    }
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn merge_suggestions_interleaves_engines_and_drops_duplicates() {
+        let merged = merge_suggestions(
+            "rust",
+            vec![
+                strings(&["rust lang", "rust book", "Rust"]),
+                strings(&["Rust Lang", "rust game"]),
+            ],
+        );
+
+        assert_eq!(merged, ["rust lang", "rust book", "rust game"]);
+    }
+
+    #[test]
+    fn merge_suggestions_caps_the_list() {
+        let many: Vec<String> = (0..MAX_SUGGESTIONS * 2).map(|i| format!("q {i}")).collect();
+        assert_eq!(merge_suggestions("q", vec![many]).len(), MAX_SUGGESTIONS);
+    }
+
+    #[test]
+    fn suggest_cache_expires_entries_and_evicts_the_oldest_when_full() {
+        let ttl = Duration::from_secs(60);
+        let mut cache = SuggestCache::new(ttl, 2);
+        let t0 = Instant::now();
+
+        cache.insert("a".into(), strings(&["a1"]), t0);
+        cache.insert("b".into(), strings(&["b1"]), t0 + Duration::from_secs(1));
+        assert_eq!(cache.get("a", t0), Some(strings(&["a1"])));
+        assert_eq!(cache.get("a", t0 + ttl), None);
+
+        cache.insert("c".into(), strings(&["c1"]), t0 + Duration::from_secs(2));
+        assert_eq!(cache.get("a", t0 + Duration::from_secs(2)), None);
+        assert!(cache.get("b", t0 + Duration::from_secs(2)).is_some());
+        assert!(cache.get("c", t0 + Duration::from_secs(2)).is_some());
+    }
+
+    #[test]
+    fn suggest_cache_evicts_by_latest_insert_and_keeps_its_queue_bounded() {
+        let ttl = Duration::from_secs(60);
+        let mut cache = SuggestCache::new(ttl, 2);
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
+
+        cache.insert("a".into(), strings(&["a1"]), at(0));
+        cache.insert("b".into(), strings(&["b1"]), at(1));
+        cache.insert("a".into(), strings(&["a2"]), at(2));
+        cache.insert("c".into(), strings(&["c1"]), at(3));
+        assert_eq!(cache.get("a", at(3)), Some(strings(&["a2"])));
+        assert_eq!(cache.get("b", at(3)), None);
+
+        // A key refreshed over and over leaves no trail behind it.
+        for i in 1..100 {
+            cache.insert("a".into(), strings(&["a1"]), at(i * 61));
+        }
+        assert!(cache.order.len() <= 2, "{}", cache.order.len());
+    }
+
+    #[test]
+    fn opt_in_engines_are_not_in_the_default_set() {
+        assert!(!SearchEngines::all().contains(&SearchEngines::Marginalia));
+    }
+
+    #[test]
+    fn engines_that_cant_apply_a_param_sit_the_search_out() {
+        let params = SearchParams {
+            time_range: Some(TimeRange::Week),
+            ..Default::default()
+        };
+        let honoring: Vec<_> = SearchEngines::all()
+            .into_iter()
+            .filter(|e| e.honors(&params))
+            .collect();
+        assert!(honoring.contains(&SearchEngines::Mojeek));
+        assert!(!honoring.contains(&SearchEngines::Wikipedia));
+        assert!(!SearchEngines::Marginalia.honors(&params));
+        assert!(SearchEngines::Marginalia.honors(&SearchParams::default()));
+    }
+
+    /// Points the shared cache pool at a throwaway database, for tests that
+    /// search through the builders.
+    async fn init_test_db() {
+        let path = std::env::temp_dir().join(format!(
+            "private-search-engines-test-{}.db",
+            std::process::id()
+        ));
+        init_db(path.to_str().unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn engines_left_out_of_a_search_are_reported_skipped() {
+        init_test_db().await;
+        let only_skipped = |reports: &[EngineReport], name: &str| match reports {
+            [report] => report.engine == name && matches!(report.status, EngineStatus::Skipped),
+            _ => false,
+        };
+
+        // DuckDuckGo's news endpoint has no strict level; nothing is fetched.
+        let news = NewsSearchBuilder::new("rust")
+            .engine(NewsEngines::DuckDuckGo)
+            .safe_search(SafeSearch::Strict)
+            .search()
+            .await
+            .unwrap();
+        assert!(news.results.is_empty() && !news.has_more);
+        assert!(only_skipped(&news.engines, "DuckDuckGo"));
+
+        let mut events = VideoSearchBuilder::new("rust")
+            .engine(VideoEngines::Brave)
+            .locale("de")
+            .stream()
+            .await;
+        let mut done = None;
+        while let Some(event) = events.recv().await {
+            if let SearchEvent::Done { engines, .. } = event {
+                done = Some(engines);
+            }
+        }
+        assert!(only_skipped(&done.unwrap(), "Brave"));
+    }
+
+    #[test]
+    fn engines_parse_from_their_names_ignoring_case() {
+        assert_eq!("duckduckgo".parse(), Ok(SearchEngines::DuckDuckGo));
+        assert_eq!("Marginalia".parse(), Ok(SearchEngines::Marginalia));
+        assert_eq!(" BRAVE ".parse(), Ok(VideoEngines::Brave));
+
+        let err = "Mojeek".parse::<NewsEngines>().unwrap_err().to_string();
+        assert_eq!(
+            err,
+            r#"unknown news engine "Mojeek" (expected one of: Brave, DuckDuckGo)"#
+        );
+
+        let engines: Vec<ImageEngines> = serde_json::from_str(r#"["qwant", "brave"]"#).unwrap();
+        assert_eq!(engines, [ImageEngines::Qwant, ImageEngines::Brave]);
+        assert!(serde_json::from_str::<Vec<ImageEngines>>(r#"["Wikipedia"]"#).is_err());
+    }
+
+    fn search_definition(name: &str) -> DeclarativeEngine {
+        DeclarativeEngine::from_toml(&format!(
+            r#"
+            name = "{name}"
+            [search]
+            url = "https://search.example.com/?q={{query}}&p={{page}}"
+            page_size = 10
+            results = ".r"
+            title = "h3"
+            href = "a"
+            description = "p"
+            "#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn registered_declarative_engines_join_the_matching_default_sets() {
+        register_declarative_engine(search_definition("Registry Test Engine")).unwrap();
+
+        assert!(SearchEngines::all().contains(&SearchEngines::Declarative("Registry Test Engine")));
+        assert!(!ImageEngines::all().contains(&ImageEngines::Declarative("Registry Test Engine")));
+    }
+
+    #[test]
+    fn declarative_engines_cant_take_a_name_already_in_use() {
+        let err = register_declarative_engine(search_definition("brave")).unwrap_err();
+        let err = err.to_string();
+        assert!(err.contains("taken by the Brave engine"), "{err}");
+
+        register_declarative_engine(search_definition("Collision Test Engine")).unwrap();
+        let again = register_declarative_engine(search_definition(" collision test engine "));
+        assert!(again.is_err());
+        let found = declarative_engines_where(|_| true);
+        let same = found
+            .iter()
+            .filter(|n| n.eq_ignore_ascii_case("Collision Test Engine"));
+        assert_eq!(same.count(), 1);
+    }
+
+    #[test]
+    fn engine_status_keeps_blocks_and_rate_limits_apart_from_failures() {
+        let status = |e: EngineError| {
+            serde_json::to_value(EngineStatus::from(&EngineOutcome::Failed(source_error(e))))
+                .unwrap()
+        };
+
+        assert_eq!(
+            status(EngineError::Blocked {
+                reason: "captcha".into()
+            }),
+            serde_json::json!({"status": "blocked", "detail": "captcha"})
+        );
+        assert_eq!(
+            status(EngineError::RateLimited {
+                retry_after: Some(Duration::from_secs(30))
+            }),
+            serde_json::json!({"status": "rate_limited", "detail": 30})
+        );
+        assert_eq!(
+            status(EngineError::HttpStatus(502)),
+            serde_json::json!({"status": "http_status", "detail": 502})
+        );
+        assert_eq!(
+            status(EngineError::ParseError("bad markup".into())),
+            serde_json::json!({"status": "failed", "detail": "parse error: bad markup"})
+        );
+    }
+
     /// Regression guard for "encoding/JSON support": unicode titles/
     /// descriptions must survive a `serde_json` round trip unchanged.
     #[test]
@@ -783,15 +2464,11 @@ mod test {
     #[ignore]
     #[tokio::test]
     async fn test_search_builder_pagination_live() {
-        // Safe: run in isolation via `--ignored --test-threads=1`, so nothing
-        // else touches the process-global cache pool concurrently.
-        unsafe {
-            std::env::set_var(
-                "CACHE_DB_PATH",
-                "/tmp/private-search-engines-pagination-test.db",
-            );
-        }
-        let _ = std::fs::remove_file("/tmp/private-search-engines-pagination-test.db");
+        // The shared pool is set up once per process, so run this on its
+        // own (`--ignored`) for its first fetch to really be cold.
+        let path = std::env::temp_dir().join("private-search-engines-pagination-test.db");
+        let _ = std::fs::remove_file(&path);
+        init_db(path.to_str().unwrap()).await;
 
         let query = "rust async";
 
@@ -836,6 +2513,6 @@ mod test {
             "revisiting page 1 should be served entirely from cache"
         );
 
-        let _ = std::fs::remove_file("/tmp/private-search-engines-pagination-test.db");
+        let _ = std::fs::remove_file(&path);
     }
 }
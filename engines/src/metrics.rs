@@ -0,0 +1,137 @@
+//! Counters and histograms for engine requests and the cache, plus the
+//! building blocks to render a binary's own metrics alongside them.
+//!
+//! The caches report to [`Recorder`], which counts into the series below.
+
+use std::time::Duration;
+
+pub use search_cache::metrics::{CounterVec, HistogramVec};
+use search_cache::{
+    EngineOutcome, SourceError,
+    metrics::{CacheObserver, Refresh},
+};
+
+/// Every page fetch from an engine, by engine, namespace and outcome —
+/// `ok`, `timed_out`, or the kind of failure (`blocked`, `rate_limited`,
+/// `http_status`, `failed`).
+pub static ENGINE_REQUESTS: CounterVec = CounterVec::new(
+    "private_search_engine_requests_total",
+    "Requests made to each search engine, by outcome.",
+    &["engine", "namespace", "outcome"],
+);
+
+/// How long each engine took to answer (or to time out).
+pub static ENGINE_LATENCY: HistogramVec = HistogramVec::new(
+    "private_search_engine_request_duration_seconds",
+    "Time taken by each search engine to answer.",
+    &["engine", "namespace"],
+    &[0.1, 0.25, 0.5, 1.0, 2.0, 3.0, 5.0, 10.0],
+);
+
+/// New rows each fetch round added to a query's merged results.
+pub static ROWS_APPENDED: HistogramVec = HistogramVec::new(
+    "private_search_cache_rows_appended",
+    "Rows appended to a query's merged results per fetch round.",
+    &["namespace"],
+    &[0.0, 1.0, 5.0, 10.0, 20.0, 50.0, 100.0],
+);
+
+/// Rows returned to callers, by whether they were already cached (`hit`)
+/// or fetched by that call (`miss`).
+pub static CACHE_ROWS: CounterVec = CounterVec::new(
+    "private_search_cache_rows_total",
+    "Result rows returned, by whether they came from the cache.",
+    &["namespace", "result"],
+);
+
+/// Background refreshes of stale queries, by whether the new snapshot was
+/// `published`, `discarded` (no engine had anything) or hit an `error`.
+pub static SNAPSHOT_REFRESHES: CounterVec = CounterVec::new(
+    "private_search_cache_refreshes_total",
+    "Background refreshes of stale cached queries, by result.",
+    &["namespace", "result"],
+);
+
+/// Appends this crate's metrics to `out`.
+pub fn render(out: &mut String) {
+    ENGINE_REQUESTS.render(out);
+    ENGINE_LATENCY.render(out);
+    ROWS_APPENDED.render(out);
+    CACHE_ROWS.render(out);
+    SNAPSHOT_REFRESHES.render(out);
+}
+
+/// Counts what the caches report into the series above.
+pub(crate) struct Recorder;
+
+impl CacheObserver for Recorder {
+    fn fetched(&self, namespace: &str, engine: &str, outcome: &EngineOutcome, elapsed: Duration) {
+        let outcome = match outcome {
+            EngineOutcome::Ok => "ok",
+            EngineOutcome::Failed(SourceError::Blocked(_)) => "blocked",
+            EngineOutcome::Failed(SourceError::RateLimited { .. }) => "rate_limited",
+            EngineOutcome::Failed(SourceError::HttpStatus(_)) => "http_status",
+            EngineOutcome::Failed(SourceError::Other(_)) => "failed",
+            EngineOutcome::TimedOut => "timed_out",
+        };
+        ENGINE_REQUESTS.inc(&[engine, namespace, outcome]);
+        ENGINE_LATENCY.observe(&[engine, namespace], elapsed.as_secs_f64());
+    }
+
+    fn appended(&self, namespace: &str, rows: usize) {
+        ROWS_APPENDED.observe(&[namespace], rows as f64);
+    }
+
+    fn returned(&self, namespace: &str, hits: usize, misses: usize) {
+        CACHE_ROWS.add(&[namespace, "hit"], hits as u64);
+        CACHE_ROWS.add(&[namespace, "miss"], misses as u64);
+    }
+
+    fn refreshed(&self, namespace: &str, refresh: Refresh) {
+        let result = match refresh {
+            Refresh::Published => "published",
+            Refresh::Discarded => "discarded",
+            Refresh::Failed => "error",
+        };
+        SNAPSHOT_REFRESHES.inc(&[namespace, result]);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Metrics are process-wide, so this uses a namespace no other test does.
+    #[test]
+    fn recorder_counts_into_the_engine_and_cache_series() {
+        let blocked = EngineOutcome::Failed(SourceError::Blocked("captcha".into()));
+        Recorder.fetched(
+            "metered",
+            "Brave",
+            &EngineOutcome::Ok,
+            Duration::from_millis(300),
+        );
+        Recorder.fetched("metered", "Mojeek", &blocked, Duration::from_millis(200));
+        Recorder.appended("metered", 2);
+        Recorder.returned("metered", 1, 2);
+        Recorder.refreshed("metered", Refresh::Discarded);
+
+        assert_eq!(ENGINE_REQUESTS.get(&["Brave", "metered", "ok"]), 1);
+        assert_eq!(ENGINE_REQUESTS.get(&["Mojeek", "metered", "blocked"]), 1);
+        assert_eq!(CACHE_ROWS.get(&["metered", "hit"]), 1);
+        assert_eq!(CACHE_ROWS.get(&["metered", "miss"]), 2);
+        assert_eq!(SNAPSHOT_REFRESHES.get(&["metered", "discarded"]), 1);
+
+        let mut rendered = String::new();
+        render(&mut rendered);
+        for line in [
+            r#"private_search_engine_request_duration_seconds_bucket{engine="Brave",namespace="metered",le="0.25"} 0"#,
+            r#"private_search_engine_request_duration_seconds_bucket{engine="Brave",namespace="metered",le="0.5"} 1"#,
+            r#"private_search_cache_rows_appended_bucket{namespace="metered",le="1"} 0"#,
+            r#"private_search_cache_rows_appended_bucket{namespace="metered",le="5"} 1"#,
+            r#"private_search_cache_rows_appended_count{namespace="metered"} 1"#,
+        ] {
+            assert!(rendered.lines().any(|l| l == line), "{line}");
+        }
+    }
+}
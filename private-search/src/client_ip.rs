@@ -0,0 +1,198 @@
+use std::net::{IpAddr, SocketAddr};
+
+use ipnet::IpNet;
+use rocket::{Request, http::HeaderMap, serde::Deserialize};
+
+/// The header the trusted proxies append the client's address to. Only
+/// that one is read: a proxy passes any other through untouched, so it
+/// holds whatever the client put there.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(crate = "rocket::serde", rename_all = "kebab-case")]
+pub enum ForwardingHeader {
+    /// `X-Forwarded-For`, as nginx and Caddy set it by default.
+    #[default]
+    XForwardedFor,
+    /// `Forwarded` (RFC 7239).
+    Forwarded,
+}
+
+/// The reverse proxies (e.g. nginx, Caddy) allowed to say who the client
+/// is. Rocket's own `client_ip()` believes `X-Real-IP` from anyone, so any
+/// client could pick its own rate-limit bucket; and without a header every
+/// client behind a proxy looks like the proxy.
+pub struct TrustedProxies {
+    networks: Vec<IpNet>,
+    header: ForwardingHeader,
+}
+
+impl TrustedProxies {
+    pub fn new(networks: Vec<IpNet>, header: ForwardingHeader) -> Self {
+        Self { networks, header }
+    }
+
+    fn trusts(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        self.networks.iter().any(|net| net.contains(&ip))
+    }
+
+    /// The address of whoever made `req`: the connecting peer, unless that
+    /// is a trusted proxy, in which case the proxies' forwarding header is
+    /// walked from the right (each proxy appends the address it got the
+    /// request from) to the first hop that isn't a trusted proxy itself.
+    /// Anything further left was supplied by the client and can't be
+    /// believed.
+    ///
+    /// `None` if the peer isn't known, e.g. in local tests.
+    pub fn client_ip(&self, req: &Request<'_>) -> Option<IpAddr> {
+        let peer = req.remote()?.ip();
+        Some(self.resolve(peer, forwarded_hops(req.headers(), self.header)))
+    }
+
+    fn resolve(&self, peer: IpAddr, hops: Vec<Option<IpAddr>>) -> IpAddr {
+        let mut client = peer;
+        let mut hops = hops.into_iter().rev();
+        while self.trusts(client) {
+            match hops.next() {
+                Some(Some(hop)) => client = hop,
+                // Out of hops, or one we can't parse (`unknown`, an
+                // obfuscated identifier, garbage): the last proxy is as far
+                // as we can trust.
+                _ => break,
+            }
+        }
+        client
+    }
+}
+
+/// The hops listed in `header`, left to right across all lines of it. A
+/// hop that isn't an IP address is `None`.
+fn forwarded_hops(headers: &HeaderMap<'_>, header: ForwardingHeader) -> Vec<Option<IpAddr>> {
+    match header {
+        ForwardingHeader::Forwarded => headers
+            .get("Forwarded")
+            .flat_map(|line| line.split(','))
+            .map(|element| {
+                element
+                    .split(';')
+                    .filter_map(|pair| pair.split_once('='))
+                    .find(|(key, _)| key.trim().eq_ignore_ascii_case("for"))
+                    .and_then(|(_, node)| parse_node(node))
+            })
+            .collect(),
+        ForwardingHeader::XForwardedFor => headers
+            .get("X-Forwarded-For")
+            .flat_map(|line| line.split(','))
+            .map(parse_node)
+            .collect(),
+    }
+}
+
+/// An address as proxies write it: bare, quoted, with a port, or (IPv6)
+/// in brackets with an optional port.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    if let Some(bracketed) = node.strip_prefix('[') {
+        return bracketed.split_once(']')?.0.parse().ok();
+    }
+    node.parse()
+        .ok()
+        .or_else(|| node.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rocket::http::Header;
+
+    fn proxies() -> TrustedProxies {
+        proxies_writing(ForwardingHeader::XForwardedFor)
+    }
+
+    fn proxies_writing(header: ForwardingHeader) -> TrustedProxies {
+        TrustedProxies::new(vec!["10.0.0.0/8".parse().unwrap()], header)
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn headers(headers: &[(&'static str, &'static str)]) -> HeaderMap<'static> {
+        let mut map = HeaderMap::new();
+        for &(name, value) in headers {
+            map.add(Header::new(name, value));
+        }
+        map
+    }
+
+    fn hops(headers: &[(&'static str, &'static str)]) -> Vec<Option<IpAddr>> {
+        forwarded_hops(&self::headers(headers), ForwardingHeader::XForwardedFor)
+    }
+
+    #[test]
+    fn only_trusted_peers_are_believed() {
+        let spoofed = hops(&[("X-Forwarded-For", "192.0.2.1")]);
+        assert_eq!(
+            proxies().resolve(ip("203.0.113.5"), spoofed.clone()),
+            ip("203.0.113.5")
+        );
+        assert_eq!(proxies().resolve(ip("10.0.0.1"), spoofed), ip("192.0.2.1"));
+    }
+
+    #[test]
+    fn walks_from_the_right_past_trusted_proxies_only() {
+        // The client prepended 192.0.2.1 itself; 203.0.113.5 is what our
+        // outermost proxy (10.0.0.2) actually saw.
+        let chain = hops(&[
+            ("X-Forwarded-For", "192.0.2.1, 203.0.113.5"),
+            ("X-Forwarded-For", "10.0.0.2"),
+        ]);
+        assert_eq!(proxies().resolve(ip("10.0.0.1"), chain), ip("203.0.113.5"));
+
+        let unparseable = hops(&[("X-Forwarded-For", "192.0.2.1, unknown")]);
+        assert_eq!(
+            proxies().resolve(ip("10.0.0.1"), unparseable),
+            ip("10.0.0.1")
+        );
+    }
+
+    #[test]
+    fn only_the_header_the_proxies_write_is_read() {
+        // nginx appends to `X-Forwarded-For` and passes the client's own
+        // `Forwarded` through untouched.
+        let request = headers(&[
+            ("Forwarded", "for=192.0.2.1"),
+            ("X-Forwarded-For", "203.0.113.5"),
+        ]);
+        let xff = forwarded_hops(&request, ForwardingHeader::XForwardedFor);
+        assert_eq!(proxies().resolve(ip("10.0.0.1"), xff), ip("203.0.113.5"));
+
+        let spoofed = headers(&[("Forwarded", "for=192.0.2.1")]);
+        let xff = forwarded_hops(&spoofed, ForwardingHeader::XForwardedFor);
+        assert_eq!(proxies().resolve(ip("10.0.0.1"), xff), ip("10.0.0.1"));
+    }
+
+    #[test]
+    fn parses_rfc_7239_nodes() {
+        let chain = forwarded_hops(
+            &headers(&[
+                ("X-Forwarded-For", "192.0.2.99"),
+                (
+                    "Forwarded",
+                    r#"for=192.0.2.60;proto=http, For="[2001:db8:cafe::17]:4711";by=10.0.0.3"#,
+                ),
+            ]),
+            ForwardingHeader::Forwarded,
+        );
+        assert_eq!(
+            chain,
+            [Some(ip("192.0.2.60")), Some(ip("2001:db8:cafe::17"))]
+        );
+        assert_eq!(
+            proxies_writing(ForwardingHeader::Forwarded).resolve(ip("10.0.0.1"), chain),
+            ip("2001:db8:cafe::17")
+        );
+
+        assert_eq!(parse_node("198.51.100.7:8080"), Some(ip("198.51.100.7")));
+        assert_eq!(parse_node("_hidden"), None);
+    }
+}
@@ -0,0 +1,482 @@
+use std::{collections::HashMap, time::Duration};
+
+use ipnet::IpNet;
+use rocket::{
+    figment::{Figment, providers::Env},
+    serde::Deserialize,
+};
+
+use crate::client_ip::ForwardingHeader;
+use private_search_engines::{
+    CacheFreshness, DEFAULT_TIMEOUT, ImageEngines, NewsEngines, SearchEngines, VideoEngines,
+};
+
+/// The env vars settings used to come from before there was a config file.
+/// Still honoured (over the file) so existing deployments — e.g. the
+/// Dockerfile's `STATIC_DIR`/`TEMPLATE_DIR` — keep working unchanged.
+const LEGACY_ENV: [&str; 4] = [
+    "static_dir",
+    "template_dir",
+    "public_url",
+    "engine_definitions_dir",
+];
+/// `CACHE_`-prefixed, e.g. `CACHE_DB_PATH`.
+const LEGACY_CACHE_ENV: [&str; 3] = ["db_path", "max_age_secs", "clean_interval_secs"];
+
+/// Everything an instance can be configured with, read from the same figment
+/// as Rocket's own settings: `Rocket.toml` (or the file `ROCKET_CONFIG`
+/// points at), profile sections and all, then the legacy env vars. Every
+/// field has a default, so an instance runs with no config file at all. See
+/// `Rocket.example.toml` for a commented example.
+#[derive(Deserialize, Debug)]
+#[serde(crate = "rocket::serde", default)]
+pub struct Config {
+    /// Where static assets and templates are served from. Default to this
+    /// crate's own dirs (baked in at compile time), which makes `cargo run`
+    /// work from anywhere; deployments (e.g. Docker) point them at wherever
+    /// the assets actually land, since the compile-time path won't exist
+    /// outside the machine that built the binary.
+    pub static_dir: String,
+    pub template_dir: String,
+    /// The externally-visible base URL (e.g. `https://search.example.com`)
+    /// advertised in `/opensearch.xml`. Without it that page is a 404:
+    /// the request's `Host` header can't be trusted to build it from.
+    pub public_url: Option<String>,
+    /// Optional directory of `.toml`/`.json` engine definitions (see
+    /// `private_search_engines::DeclarativeEngine`). Read before the rest of
+    /// the config, so its engines can be named in `engines`.
+    pub engine_definitions_dir: Option<String>,
+    /// Reverse proxies (CIDR, e.g. `"127.0.0.1/32"`) whose
+    /// `trusted_proxy_header` says who the client is. Requests from
+    /// anywhere else are attributed to their peer address, whatever headers
+    /// they carry.
+    pub trusted_proxies: Vec<IpNet>,
+    /// Which header those proxies write: `"x-forwarded-for"` or
+    /// `"forwarded"`.
+    pub trusted_proxy_header: ForwardingHeader,
+    pub engines: EnginesConfig,
+    pub proxies: ProxiesConfig,
+    pub rate_limit: RateLimitConfig,
+    pub cache: CacheConfig,
+    pub paging: PagingConfig,
+}
+
+/// Which engines each tab fans out to (by name, case-insensitive), and how
+/// long to wait on them.
+#[derive(Deserialize, Debug)]
+#[serde(crate = "rocket::serde", default, deny_unknown_fields)]
+pub struct EnginesConfig {
+    pub general: Vec<SearchEngines>,
+    pub images: Vec<ImageEngines>,
+    pub news: Vec<NewsEngines>,
+    pub videos: Vec<VideoEngines>,
+    /// Per-engine timeout for a search; engines slower than this are
+    /// reported as timed out rather than waited on.
+    pub timeout_secs: u64,
+    pub wikipedia: WikipediaConfig,
+}
+
+/// Where the `Wikipedia` engine searches. A search with a `lang` goes to
+/// that language's edition; `language` is for the rest.
+#[derive(Deserialize, Debug)]
+#[serde(crate = "rocket::serde", default, deny_unknown_fields)]
+pub struct WikipediaConfig {
+    /// Site root, with `{lang}` where the language edition goes.
+    pub url: String,
+    pub language: String,
+}
+
+/// Outbound proxy pools for engine requests, rotated per request — e.g.
+/// `socks5h://127.0.0.1:9050` to go through Tor. `ENGINE_PROXIES` and
+/// `ENGINE_PROXIES_<ENGINE>` add to these (see `resolve_proxies`).
+#[derive(Deserialize, Debug, Default)]
+#[serde(crate = "rocket::serde", default, deny_unknown_fields)]
+pub struct ProxiesConfig {
+    /// Used by every engine without a pool of its own.
+    pub default: Vec<String>,
+    /// Per-engine pools, keyed by engine name (e.g. `DuckDuckGo`).
+    pub engines: HashMap<String, Vec<String>>,
+}
+
+/// Per-client limits on searches and suggestions (see `rate_limit`).
+#[derive(Deserialize, Debug)]
+#[serde(crate = "rocket::serde", default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub window_secs: u64,
+    /// Searches (`/query`, `/query/stream` and `/search`) per window.
+    pub max_requests: u32,
+    /// Suggestions are requested as the user types, so they get a budget of
+    /// their own — a few searches' worth of typing shouldn't use up the
+    /// search one.
+    pub max_suggestions: u32,
+    /// How many client IPs each limiter tracks at most. Past this, idle
+    /// clients are forgotten first (see `RateLimiter`).
+    pub max_tracked_ips: usize,
+    /// Clients share a budget with their whole network of this size: one
+    /// IPv6 subscriber typically gets a /64 (or a /56 or /48) to pick
+    /// addresses from.
+    pub ipv4_prefix: u8,
+    pub ipv6_prefix: u8,
+    /// Networks (CIDR, e.g. `"192.0.2.0/24"`) that are never limited.
+    pub allow: Vec<IpNet>,
+    /// Networks refused with `403 Forbidden`, even if also in `allow`.
+    pub deny: Vec<IpNet>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(crate = "rocket::serde", default, deny_unknown_fields)]
+pub struct CacheConfig {
+    /// The SQLite result cache; created (parent dirs too) if missing.
+    pub db_path: String,
+    /// Cached queries older than this are purged.
+    pub max_age_secs: u64,
+    /// How often the purge runs.
+    pub clean_interval_secs: u64,
+    /// How long each tab's cached results are served as they are; past
+    /// that, a search still gets them but also refreshes them for later
+    /// searches.
+    pub fresh_for_secs: FreshnessConfig,
+}
+
+/// Seconds per tab; 0 never refreshes, leaving results cached until
+/// they're purged.
+#[derive(Deserialize, Debug)]
+#[serde(crate = "rocket::serde", default, deny_unknown_fields)]
+pub struct FreshnessConfig {
+    pub general: u64,
+    pub images: u64,
+    pub news: u64,
+    pub videos: u64,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(crate = "rocket::serde", default, deny_unknown_fields)]
+pub struct PagingConfig {
+    /// Results per server-rendered `/search` page — the same batch
+    /// `search.js` asks `/query` for at a time.
+    pub page_size: usize,
+    /// Largest `count` a client may ask for at once.
+    pub max_count: usize,
+    /// Deepest `start` a client may page to. Rejected outright rather than
+    /// handed to the engines — keeps `start + count` from ever overflowing
+    /// and caps how deep a client can push pagination in one request.
+    pub max_start: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            static_dir: concat!(env!("CARGO_MANIFEST_DIR"), "/static").to_string(),
+            template_dir: concat!(env!("CARGO_MANIFEST_DIR"), "/templates").to_string(),
+            public_url: None,
+            engine_definitions_dir: None,
+            trusted_proxies: Vec::new(),
+            trusted_proxy_header: ForwardingHeader::default(),
+            engines: EnginesConfig::default(),
+            proxies: ProxiesConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            cache: CacheConfig::default(),
+            paging: PagingConfig::default(),
+        }
+    }
+}
+
+/// Every engine of each kind — including any declarative engines registered
+/// by the time this runs, which is why the definitions dir is loaded first.
+impl Default for EnginesConfig {
+    fn default() -> Self {
+        Self {
+            general: SearchEngines::all(),
+            images: ImageEngines::all(),
+            news: NewsEngines::all(),
+            videos: VideoEngines::all(),
+            timeout_secs: DEFAULT_TIMEOUT.as_secs(),
+            wikipedia: WikipediaConfig::default(),
+        }
+    }
+}
+
+impl Default for WikipediaConfig {
+    fn default() -> Self {
+        Self {
+            url: "https://{lang}.wikipedia.org".to_string(),
+            language: "en".to_string(),
+        }
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            window_secs: 60,
+            max_requests: 30,
+            max_suggestions: 120,
+            max_tracked_ips: 100_000,
+            ipv4_prefix: 32,
+            ipv6_prefix: 64,
+            allow: Vec::new(),
+            deny: Vec::new(),
+        }
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            db_path: "data/cache.db".to_string(),
+            max_age_secs: 7 * 24 * 60 * 60, // 7 days
+            clean_interval_secs: 60 * 60,   // hourly
+            fresh_for_secs: FreshnessConfig::default(),
+        }
+    }
+}
+
+impl Default for FreshnessConfig {
+    fn default() -> Self {
+        Self {
+            general: 24 * 60 * 60,
+            images: 24 * 60 * 60,
+            news: 60 * 60,
+            videos: 24 * 60 * 60,
+        }
+    }
+}
+
+impl Default for PagingConfig {
+    fn default() -> Self {
+        Self {
+            page_size: 10,
+            max_count: 25,
+            max_start: 10_000,
+        }
+    }
+}
+
+/// Rocket's figment (`Rocket.toml` + `ROCKET_*`), with the legacy env vars
+/// layered on top — `CACHE_DB_PATH` becomes `cache.db_path` and so on.
+pub fn figment() -> Figment {
+    rocket::Config::figment()
+        .merge(Env::raw().only(&LEGACY_ENV).global())
+        .merge(
+            Env::prefixed("CACHE_")
+                .only(&LEGACY_CACHE_ENV)
+                .map(|key| format!("cache.{key}").into())
+                .global(),
+        )
+}
+
+impl Config {
+    /// Extracts and validates the config, so a bad value stops the server
+    /// at startup instead of surfacing on the first search. The error is
+    /// boxed: `figment::Error` carries its whole metadata trail.
+    pub fn from_figment(figment: &Figment) -> Result<Self, Box<rocket::figment::Error>> {
+        let mut config: Self = figment.extract()?;
+        config.public_url = config
+            .public_url
+            .map(|url| url.trim().trim_end_matches('/').to_string())
+            .filter(|url| !url.is_empty());
+        config
+            .validate()
+            .map_err(|e| Box::new(rocket::figment::Error::from(e)))?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let engines = &self.engines;
+        for (tab, empty) in [
+            ("general", engines.general.is_empty()),
+            ("images", engines.images.is_empty()),
+            ("news", engines.news.is_empty()),
+            ("videos", engines.videos.is_empty()),
+        ] {
+            if empty {
+                return Err(format!("engines.{tab} must list at least one engine"));
+            }
+        }
+
+        for (key, value) in [
+            ("engines.timeout_secs", engines.timeout_secs),
+            ("rate_limit.window_secs", self.rate_limit.window_secs),
+            (
+                "rate_limit.max_requests",
+                self.rate_limit.max_requests.into(),
+            ),
+            (
+                "rate_limit.max_suggestions",
+                self.rate_limit.max_suggestions.into(),
+            ),
+            ("cache.max_age_secs", self.cache.max_age_secs),
+            ("cache.clean_interval_secs", self.cache.clean_interval_secs),
+            (
+                "rate_limit.max_tracked_ips",
+                self.rate_limit.max_tracked_ips as u64,
+            ),
+            ("paging.max_count", self.paging.max_count as u64),
+        ] {
+            if value == 0 {
+                return Err(format!("{key} must be greater than 0"));
+            }
+        }
+
+        for (key, prefix, max) in [
+            ("rate_limit.ipv4_prefix", self.rate_limit.ipv4_prefix, 32),
+            ("rate_limit.ipv6_prefix", self.rate_limit.ipv6_prefix, 128),
+        ] {
+            if prefix == 0 || prefix > max {
+                return Err(format!("{key} must be between 1 and {max}"));
+            }
+        }
+
+        let paging = &self.paging;
+        if paging.page_size == 0 || paging.page_size > paging.max_count {
+            return Err(format!(
+                "paging.page_size must be between 1 and paging.max_count ({})",
+                paging.max_count
+            ));
+        }
+        if self.cache.db_path.trim().is_empty() {
+            return Err("cache.db_path must not be empty".to_string());
+        }
+        if let Some(url) = &self.public_url
+            && !url.starts_with("http://")
+            && !url.starts_with("https://")
+        {
+            return Err(format!("public_url must be an http(s) URL, got {url:?}"));
+        }
+        let wikipedia = &engines.wikipedia;
+        if !wikipedia.url.starts_with("http://") && !wikipedia.url.starts_with("https://") {
+            return Err(format!(
+                "engines.wikipedia.url must be an http(s) URL, got {:?}",
+                wikipedia.url
+            ));
+        }
+        let language = &wikipedia.language;
+        if !(2..=3).contains(&language.len()) || !language.bytes().all(|b| b.is_ascii_lowercase()) {
+            return Err(format!(
+                "engines.wikipedia.language must be a language code like \"en\", got {language:?}"
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn engine_timeout(&self) -> Duration {
+        Duration::from_secs(self.engines.timeout_secs)
+    }
+
+    pub fn cache_freshness(&self) -> CacheFreshness {
+        let fresh_for = &self.cache.fresh_for_secs;
+        let ttl = |secs| (secs > 0).then(|| Duration::from_secs(secs));
+        CacheFreshness {
+            general: ttl(fresh_for.general),
+            images: ttl(fresh_for.images),
+            news: ttl(fresh_for.news),
+            videos: ttl(fresh_for.videos),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rocket::figment::providers::{Format, Toml};
+
+    /// Parses `toml` on its own, without `Rocket.toml` or the environment.
+    fn from_toml(toml: &str) -> Result<Config, Box<rocket::figment::Error>> {
+        Config::from_figment(&Figment::from(Toml::string(toml)))
+    }
+
+    #[test]
+    fn an_empty_file_gives_the_defaults() {
+        let config = from_toml("").unwrap();
+        assert_eq!(config.engines.general, SearchEngines::all());
+        assert_eq!(config.rate_limit.max_requests, 30);
+        assert_eq!(config.paging.page_size, 10);
+        assert_eq!(config.cache.db_path, "data/cache.db");
+    }
+
+    #[test]
+    fn sections_override_only_what_they_set() {
+        let config = from_toml(
+            r#"
+            public_url = "https://search.example.com/"
+            trusted_proxy_header = "forwarded"
+
+            [engines]
+            general = ["duckduckgo", "Marginalia"]
+            timeout_secs = 5
+
+            [engines.wikipedia]
+            language = "de"
+
+            [paging]
+            max_count = 50
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.engines.general,
+            [SearchEngines::DuckDuckGo, SearchEngines::Marginalia]
+        );
+        assert_eq!(config.engines.news, NewsEngines::all());
+        assert_eq!(config.engine_timeout(), Duration::from_secs(5));
+        assert_eq!(config.engines.wikipedia.language, "de");
+        assert_eq!(config.trusted_proxy_header, ForwardingHeader::Forwarded);
+        assert_eq!(config.engines.wikipedia.url, "https://{lang}.wikipedia.org");
+        assert_eq!((config.paging.page_size, config.paging.max_count), (10, 50));
+        assert_eq!(
+            config.public_url.as_deref(),
+            Some("https://search.example.com")
+        );
+    }
+
+    #[test]
+    fn cache_freshness_is_per_tab_and_zero_turns_it_off() {
+        let config = from_toml("[cache.fresh_for_secs]\nnews = 600\nimages = 0").unwrap();
+        let freshness = config.cache_freshness();
+        assert_eq!(freshness.news, Some(Duration::from_secs(600)));
+        assert_eq!(freshness.images, None);
+        assert_eq!(freshness.general, Some(Duration::from_secs(24 * 60 * 60)));
+    }
+
+    #[test]
+    fn bad_values_are_rejected_with_the_offending_key() {
+        for (toml, needle) in [
+            (
+                "[engines]\nnews = [\"Mojeek\"]",
+                r#"unknown news engine "Mojeek""#,
+            ),
+            (
+                "[engines]\nimages = []",
+                "engines.images must list at least one engine",
+            ),
+            (
+                "[rate_limit]\nwindow_secs = 0",
+                "rate_limit.window_secs must be greater than 0",
+            ),
+            (
+                "[rate_limit]\nipv6_prefix = 129",
+                "rate_limit.ipv6_prefix must be between 1 and 128",
+            ),
+            (
+                "[rate_limit]\ndeny = [\"10.0.0.0/33\"]",
+                "invalid IP address syntax",
+            ),
+            (
+                "[paging]\npage_size = 30",
+                "paging.page_size must be between 1 and",
+            ),
+            ("[cache]\nmax_age = 60", "unknown field: found `max_age`"),
+            (
+                "public_url = \"search.example.com\"",
+                "public_url must be an http(s) URL",
+            ),
+            (
+                "[engines.wikipedia]\nlanguage = \"en&x=1\"",
+                "engines.wikipedia.language must be a language code",
+            ),
+        ] {
+            let err = from_toml(toml).unwrap_err().to_string();
+            assert!(err.contains(needle), "{toml:?}: {err}");
+        }
+    }
+}
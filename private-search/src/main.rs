@@ -1,87 +1,184 @@
 use std::time::Duration;
 
 use rocket::{
-    Build, Orbit, Request, Response, Rocket,
+    Build, Orbit, Request, Response, Rocket, State,
     fairing::{Fairing, Info, Kind},
+    figment::Figment,
     fs::FileServer,
-    http::Status,
-    response::Redirect,
-    serde::{Deserialize, Serialize, json::Json},
+    futures::stream::{self, BoxStream, StreamExt},
+    http::{ContentType, Status},
+    response::{
+        Redirect,
+        stream::{Event, EventStream},
+    },
+    serde::{
+        Deserialize, Serialize,
+        json::{Json, json},
+    },
+    tokio::sync::mpsc::UnboundedReceiver,
 };
 use rocket_dyn_templates::{Template, context};
 
 use private_search_engines::{
-    FetchError, ImageEngines, ImageResult, ImageSearchBuilder, SearchBuilder, SearchEngines,
-    SearchResponse, SearchResult, init_db,
+    ClientConfig, FetchError, ImageResult, ImageSearchBuilder, NewsResult, NewsSearchBuilder,
+    SearchBuilder, SearchEvent, SearchParams, SearchResponse, SearchResult, VideoResult,
+    VideoSearchBuilder, configure_cache_freshness, configure_clients, configure_wikipedia, init_db,
 };
 
+mod client_ip;
+use client_ip::TrustedProxies;
+
+mod config;
+use config::Config;
+
+mod metrics;
+
 mod rate_limit;
-use rate_limit::{RateLimited, RateLimiter};
+use rate_limit::{
+    ClientPolicy, RateLimited, RateLimiter, Rejected, SuggestLimiter, SuggestRateLimited,
+};
+
+mod results_page;
+use results_page::ResultsPage;
 
 #[macro_use]
 extern crate rocket;
 
-/// Rejected outright rather than handed to the engines — keeps `start + count`
-/// from ever overflowing and caps how deep a client can push pagination in
-/// one request.
-const MAX_START: usize = 10_000;
-const MAX_COUNT: usize = 25;
-
 /// Cache-busts static assets referenced from templates (`?v={{version}}`):
 /// `CacheFairing` sets a 24h `max-age` on `/static/*`, so without this a
 /// deploy that changes `search.js`/`styles.css` could leave stale copies in
 /// clients' caches for up to a day.
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-/// Resolves an asset dir from `env_var` if set, otherwise falls back to a path
-/// relative to this crate (baked in at compile time via `CARGO_MANIFEST_DIR`).
-/// The fallback makes `cargo run` work from anywhere (repo root or this crate's
-/// dir); the env var lets deployments (e.g. Docker) point at wherever the
-/// assets actually land at runtime, since the compile-time path won't exist
-/// outside the machine/container that built the binary.
-fn resolve_dir(env_var: &str, manifest_relative_default: &str) -> String {
-    std::env::var(env_var).unwrap_or_else(|_| manifest_relative_default.to_string())
-}
-
-/// Reads `env_var` as a `u64` seconds count, falling back to `default_secs`
-/// if unset or unparseable.
-fn resolve_secs(env_var: &str, default_secs: u64) -> Duration {
-    std::env::var(env_var)
-        .ok()
-        .and_then(|v| v.parse::<u64>().ok())
-        .map(Duration::from_secs)
-        .unwrap_or_else(|| Duration::from_secs(default_secs))
+/// Outbound proxy pools for engine requests: the config's `proxies` section,
+/// plus `ENGINE_PROXIES` for every engine and `ENGINE_PROXIES_<ENGINE>`
+/// (e.g. `ENGINE_PROXIES_DUCKDUCKGO`) to give one engine its own. Each env
+/// var is a comma-separated list of proxy URLs.
+fn resolve_proxies(proxies: &config::ProxiesConfig) -> ClientConfig {
+    let mut config = ClientConfig::new();
+    for url in &proxies.default {
+        config = config.proxy(url);
+    }
+    for (engine, urls) in &proxies.engines {
+        for url in urls {
+            config = config.engine_proxy(engine, url);
+        }
+    }
+    for (key, value) in std::env::vars() {
+        let urls = value.split(',').map(str::trim).filter(|u| !u.is_empty());
+        if key == "ENGINE_PROXIES" {
+            for url in urls {
+                config = config.proxy(url);
+            }
+        } else if let Some(engine) = key.strip_prefix("ENGINE_PROXIES_") {
+            for url in urls {
+                config = config.engine_proxy(engine, url);
+            }
+        }
+    }
+    config
 }
 
-fn build_rocket() -> Rocket<Build> {
-    let static_dir = resolve_dir("STATIC_DIR", concat!(env!("CARGO_MANIFEST_DIR"), "/static"));
-    let template_dir = resolve_dir(
-        "TEMPLATE_DIR",
-        concat!(env!("CARGO_MANIFEST_DIR"), "/templates"),
-    );
-
-    let cache_clean_interval = resolve_secs("CACHE_CLEAN_INTERVAL_SECS", 60 * 60); // hourly
-    let cache_max_age = resolve_secs("CACHE_MAX_AGE_SECS", 7 * 24 * 60 * 60); // 7 days
-
-    let figment = rocket::Config::figment().merge(("template_dir", template_dir));
+/// `figment` is what `config` was read from; Rocket reads its own settings
+/// (address, port, …) from it too.
+fn build_rocket(figment: Figment, config: Config) -> Rocket<Build> {
+    let figment = figment.merge(("template_dir", &config.template_dir));
+    let limits = &config.rate_limit;
+    let window = Duration::from_secs(limits.window_secs);
 
     rocket::custom(figment)
         .attach(Template::fairing())
         .attach(CacheFairing)
         .attach(CacheCleanupFairing {
-            interval: cache_clean_interval,
-            max_age: cache_max_age,
+            interval: Duration::from_secs(config.cache.clean_interval_secs),
+            max_age: Duration::from_secs(config.cache.max_age_secs),
         })
-        .manage(RateLimiter::default())
-        .mount("/static", FileServer::from(static_dir))
-        .mount("/", routes![index, empty_search, search, query, health])
+        .manage(RateLimiter::new(
+            window,
+            limits.max_requests,
+            limits.max_tracked_ips,
+        ))
+        .manage(SuggestLimiter::new(
+            window,
+            limits.max_suggestions,
+            limits.max_tracked_ips,
+        ))
+        .manage(TrustedProxies::new(
+            config.trusted_proxies.clone(),
+            config.trusted_proxy_header,
+        ))
+        .manage(ClientPolicy::new(
+            limits.ipv4_prefix,
+            limits.ipv6_prefix,
+            limits.allow.clone(),
+            limits.deny.clone(),
+        ))
+        .mount("/static", FileServer::from(&config.static_dir))
+        .manage(config)
+        .mount(
+            "/",
+            routes![
+                index,
+                empty_search,
+                search,
+                query,
+                query_stream,
+                suggest,
+                opensearch,
+                health,
+                prometheus_metrics
+            ],
+        )
 }
 
 #[rocket::main]
 async fn main() -> Result<(), rocket::Error> {
-    init_db().await;
+    let figment = config::figment();
+
+    // Engine definitions are loaded before the rest of the config is read,
+    // so `engines` can name them. A bad definition refuses to start rather
+    // than silently running without it.
+    let definitions_dir = figment.extract_inner::<String>("engine_definitions_dir");
+    let mut loaded = None;
+    if let Ok(dir) = definitions_dir {
+        match private_search_engines::load_declarative_engines(&dir) {
+            Ok(names) => loaded = Some((dir, names)),
+            Err(e) => {
+                eprintln!("failed to load engine definitions from {dir}: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let config = match Config::from_figment(&figment) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("invalid configuration: {e}");
+            std::process::exit(1);
+        }
+    };
 
-    build_rocket().ignite().await?.launch().await?;
+    if let Err(e) = configure_clients(resolve_proxies(&config.proxies)) {
+        eprintln!("invalid engine proxy configuration: {e}");
+        std::process::exit(1);
+    }
+
+    let no_public_url = config.public_url.is_none();
+    init_db(&config.cache.db_path).await;
+    configure_cache_freshness(config.cache_freshness());
+    let wikipedia = &config.engines.wikipedia;
+    configure_wikipedia(&wikipedia.url, &wikipedia.language);
+
+    // Rocket's logger is only installed on ignite; everything above that
+    // isn't fatal waits until then to be reported.
+    let rocket = build_rocket(figment, config).ignite().await?;
+    if let Some((dir, names)) = loaded {
+        log::info!("loaded declarative engines from {dir}: {names:?}");
+    }
+    if no_public_url {
+        log::warn!("public_url isn't set, so /opensearch.xml is disabled");
+    }
+    rocket.launch().await?;
 
     Ok(())
 }
@@ -116,11 +213,20 @@ impl Fairing for CacheCleanupFairing {
             loop {
                 ticker.tick().await;
                 match private_search_engines::clean_cache(max_age).await {
-                    Ok(purged) if purged > 0 => {
-                        log::info!("cache cleanup: purged {purged} stale quer{}", if purged == 1 { "y" } else { "ies" });
+                    Ok(purged) => {
+                        metrics::CACHE_CLEANUPS.inc(&["ok"]);
+                        metrics::CACHE_PURGED.add(&[], purged);
+                        if purged > 0 {
+                            log::info!(
+                                "cache cleanup: purged {purged} stale quer{}",
+                                if purged == 1 { "y" } else { "ies" }
+                            );
+                        }
+                    }
+                    Err(e) => {
+                        metrics::CACHE_CLEANUPS.inc(&["error"]);
+                        log::error!("cache cleanup failed: {e}");
                     }
-                    Ok(_) => {}
-                    Err(e) => log::error!("cache cleanup failed: {e}"),
                 }
             }
         });
@@ -139,10 +245,15 @@ impl Fairing for CacheFairing {
     }
 
     async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
-        if req.uri().path().starts_with("/static/")
-            || req.uri().path().starts_with("/search")
-            || req.uri().path() == "/"
-        {
+        let path = req.uri().path();
+        // `/search` renders one user's results (or their rate-limit page):
+        // no shared cache may keep it, and no browser should either.
+        if path.starts_with("/search") {
+            res.set_header(rocket::http::Header::new(
+                "Cache-Control",
+                "private, no-store",
+            ));
+        } else if res.status() == Status::Ok && (path.starts_with("/static/") || path == "/") {
             res.set_header(rocket::http::Header::new(
                 "Cache-Control",
                 "public, max-age=86400 ",
@@ -176,23 +287,149 @@ fn health() -> Status {
     Status::Ok
 }
 
-#[allow(unused_variables)]
-#[get("/search?<t>&<q>")]
-fn search(t: Option<String>, q: &str) -> Template {
-    Template::render(
-        "search",
-        context! {
-            title: "Search",
-            version: VERSION,
-        },
+/// Prometheus scrape target: engine requests/latency/outcomes, cache
+/// hits/misses, rows appended and refreshes, rate-limit rejections and
+/// cache cleanups (see [`metrics`]). Like `/health`, meant for the operator's network;
+/// restrict it at the reverse proxy if the server is public.
+#[get("/metrics")]
+fn prometheus_metrics() -> (ContentType, String) {
+    let content_type = ContentType::new("text", "plain").with_params(("version", "0.0.4"));
+    (content_type, metrics::render())
+}
+
+/// The tabs in `search.html.hbs`'s nav, as (`t` value, label).
+const TABS: [(&str, &str); 4] = [
+    ("general", "General"),
+    ("images", "Images"),
+    ("news", "News"),
+    ("videos", "Videos"),
+];
+
+/// Renders the first page of results server-side, so the page works
+/// without JavaScript; `search.js` picks up from the rendered page rather
+/// than fetching it again. Goes through the same rate limit as `/query`,
+/// but over the limit (or if the search fails) it still renders the page,
+/// just with the error instead of results — `search.js` then retries
+/// through `/query` as usual (and gives up if the client is denied).
+#[allow(clippy::too_many_arguments)]
+#[get("/search?<t>&<q>&<start>&<snapshot>&<lang>&<safe>&<time>")]
+async fn search(
+    config: &State<Config>,
+    limit: Result<RateLimited, Rejected>,
+    t: Option<&str>,
+    q: &str,
+    start: Option<usize>,
+    snapshot: Option<i64>,
+    lang: Option<&str>,
+    safe: Option<&str>,
+    time: Option<&str>,
+) -> (Status, Template) {
+    let tab = t.unwrap_or("general");
+    let start = start.unwrap_or(0);
+    let page_size = config.paging.page_size;
+
+    let outcome = match limit {
+        Ok(_) => run_query(config, tab, q, snapshot, start, page_size, lang, safe, time)
+            .await
+            .map(|results| ResultsPage::new(&results, chrono::Utc::now()))
+            .map_err(|(status, Json(body))| (status, format!("Search failed: {}", body.error))),
+        Err(Rejected::OverLimit) => Err((
+            Status::TooManyRequests,
+            "Too many searches — wait a minute and try again.".to_string(),
+        )),
+        Err(Rejected::Denied) => Err((
+            Status::Forbidden,
+            "Searching is not available from your network.".to_string(),
+        )),
+    };
+    let (status, page, error) = match outcome {
+        Ok(page) => (Status::Ok, Some(page), None),
+        Err((status, error)) => (status, None, Some(error)),
+    };
+
+    let next_start = page.as_ref().map(|p| start + p.results.len());
+    let next_url = page
+        .as_ref()
+        .filter(|p| p.has_more && !p.results.is_empty())
+        .map(|p| {
+            uri!(search(
+                t = Some(tab),
+                q = q,
+                start = next_start,
+                snapshot = Some(p.snapshot),
+                lang = lang,
+                safe = safe,
+                time = time
+            ))
+            .to_string()
+        });
+    let tabs: Vec<_> = TABS
+        .iter()
+        .map(|&(id, label)| {
+            context! {
+                id,
+                label,
+                active: id == tab,
+                href: uri!(search(
+                    t = Some(id),
+                    q = q,
+                    start = _,
+                    snapshot = _,
+                    lang = lang,
+                    safe = safe,
+                    time = time
+                ))
+                .to_string(),
+            }
+        })
+        .collect();
+    let empty_message = match tab {
+        "images" => "No images found.",
+        "news" => "No news found.",
+        "videos" => "No videos found.",
+        _ => "No results found.",
+    };
+
+    (
+        status,
+        Template::render(
+            "search",
+            context! {
+                title: "Search",
+                version: VERSION,
+                query: q,
+                tab,
+                tabs,
+                page,
+                next_start,
+                next_url,
+                empty_message,
+                error,
+            },
+        ),
     )
 }
 
+/// OpenSearch description, so browsers can offer the instance as a search
+/// engine (linked from every page via `layout.html.hbs`). Its URLs have to
+/// be absolute, and the `Host` header is the client's to pick, so without a
+/// configured `public_url` there's no description at all.
+#[get("/opensearch.xml")]
+fn opensearch(config: &State<Config>) -> Option<(ContentType, Template)> {
+    let base_url = config.public_url.as_deref()?;
+    Some((
+        ContentType::new("application", "opensearchdescription+xml"),
+        Template::render("opensearch", context! { base_url }),
+    ))
+}
+
 #[derive(Serialize, Debug)]
 #[serde(crate = "rocket::serde")]
 pub enum QueryResults {
     General(SearchResponse<SearchResult>),
     Images(SearchResponse<ImageResult>),
+    News(SearchResponse<NewsResult>),
+    Videos(SearchResponse<VideoResult>),
 }
 
 /// Everything `/query` returns on failure is JSON too — no bare-string
@@ -213,77 +450,300 @@ fn api_error(status: Status, message: impl Into<String>) -> (Status, Json<ApiErr
     )
 }
 
-#[get("/query?<tab>&<query>&<start>&<count>")]
+/// Parses an optional query param, turning an unrecognized value into a 400
+/// rather than silently falling back to the engines' defaults.
+fn parse_param<T: std::str::FromStr>(
+    name: &str,
+    value: Option<&str>,
+) -> Result<Option<T>, (Status, Json<ApiErrorBody>)>
+where
+    T::Err: std::fmt::Display,
+{
+    value
+        .filter(|v| !v.is_empty())
+        .map(|v| v.parse())
+        .transpose()
+        .map_err(|e| api_error(Status::BadRequest, format!("{name}: {e}")))
+}
+
+#[allow(clippy::too_many_arguments)]
+#[get("/query?<tab>&<query>&<snapshot>&<start>&<count>&<lang>&<safe>&<time>")]
 async fn query(
+    config: &State<Config>,
     _limit: RateLimited,
     tab: &str,
     query: &str,
+    snapshot: Option<i64>,
     start: usize,
     count: usize,
+    lang: Option<&str>,
+    safe: Option<&str>,
+    time: Option<&str>,
 ) -> Result<Json<QueryResults>, (Status, Json<ApiErrorBody>)> {
-    if count == 0 || count > MAX_COUNT {
-        return Err(api_error(
-            Status::BadRequest,
-            format!("count must be between 1 and {MAX_COUNT}"),
-        ));
-    }
-    if start > MAX_START {
-        return Err(api_error(
-            Status::BadRequest,
-            format!("start must not exceed {MAX_START}"),
-        ));
-    }
+    run_query(config, tab, query, snapshot, start, count, lang, safe, time)
+        .await
+        .map(Json)
+}
+
+/// Validates `/query`'s parameters and runs the search for `tab` — shared
+/// with the server-rendered `/search` page. `snapshot` is the one a
+/// previous page's response named, if this is a later page.
+#[allow(clippy::too_many_arguments)]
+async fn run_query(
+    config: &Config,
+    tab: &str,
+    query: &str,
+    snapshot: Option<i64>,
+    start: usize,
+    count: usize,
+    lang: Option<&str>,
+    safe: Option<&str>,
+    time: Option<&str>,
+) -> Result<QueryResults, (Status, Json<ApiErrorBody>)> {
+    let params = validate_query(&config.paging, start, count, lang, safe, time)?;
 
     let results = match tab {
         "General" | "general" => SearchBuilder::new(query)
-            .engines([SearchEngines::Brave, SearchEngines::DuckDuckGo])
+            .engines(config.engines.general.iter().copied())
+            .params(params)
+            .timeout(config.engine_timeout())
+            .snapshot(snapshot)
             .start(start)
             .count(count)
             .search()
             .await
             .map(QueryResults::General),
         "Images" | "images" => ImageSearchBuilder::new(query)
-            .engine(ImageEngines::Brave)
+            .engines(config.engines.images.iter().copied())
+            .params(params)
+            .timeout(config.engine_timeout())
+            .snapshot(snapshot)
             .start(start)
             .count(count)
             .search()
             .await
             .map(QueryResults::Images),
+        "News" | "news" => NewsSearchBuilder::new(query)
+            .engines(config.engines.news.iter().copied())
+            .params(params)
+            .timeout(config.engine_timeout())
+            .snapshot(snapshot)
+            .start(start)
+            .count(count)
+            .search()
+            .await
+            .map(QueryResults::News),
+        "Videos" | "videos" => VideoSearchBuilder::new(query)
+            .engines(config.engines.videos.iter().copied())
+            .params(params)
+            .timeout(config.engine_timeout())
+            .snapshot(snapshot)
+            .start(start)
+            .count(count)
+            .search()
+            .await
+            .map(QueryResults::Videos),
         _ => return Err(api_error(Status::BadRequest, "unknown tab requested")),
     }
-    .map_err(|e| {
-        let status = match &e {
-            FetchError::Cache(_) => Status::InternalServerError,
-            FetchError::AllEnginesFailed => Status::BadGateway,
-        };
-        match &e {
-            FetchError::Cache(cache_err) => log::error!("cache db error: {cache_err}"),
-            FetchError::AllEnginesFailed => {
-                log::error!("all engines failed: tab={tab} query={query:?}")
-            }
-        }
-        api_error(status, "query failed")
-    })?;
+    .map_err(|e| query_failed(tab, query, &e))?;
 
     log::debug!("query ok: tab={tab} query={query:?} results={}", results_len(&results));
 
-    Ok(Json(results))
+    Ok(results)
+}
+
+/// Checks `/query`'s window and turns its search params into
+/// [`SearchParams`], rejecting anything out of range or unrecognized.
+fn validate_query(
+    paging: &config::PagingConfig,
+    start: usize,
+    count: usize,
+    lang: Option<&str>,
+    safe: Option<&str>,
+    time: Option<&str>,
+) -> Result<SearchParams, (Status, Json<ApiErrorBody>)> {
+    if count == 0 || count > paging.max_count {
+        return Err(api_error(
+            Status::BadRequest,
+            format!("count must be between 1 and {}", paging.max_count),
+        ));
+    }
+    if start > paging.max_start {
+        return Err(api_error(
+            Status::BadRequest,
+            format!("start must not exceed {}", paging.max_start),
+        ));
+    }
+    Ok(SearchParams {
+        locale: lang.filter(|l| !l.is_empty()).map(str::to_string),
+        safe_search: parse_param("safe", safe)?.unwrap_or_default(),
+        time_range: parse_param("time", time)?,
+    })
+}
+
+/// Logs a failed search and turns it into the error clients see.
+fn query_failed(tab: &str, query: &str, e: &FetchError) -> (Status, Json<ApiErrorBody>) {
+    let status = match e {
+        FetchError::Cache(_) => Status::InternalServerError,
+        FetchError::AllEnginesFailed => Status::BadGateway,
+    };
+    match e {
+        FetchError::Cache(cache_err) => log::error!("cache db error: {cache_err}"),
+        FetchError::AllEnginesFailed => {
+            log::error!("all engines failed: tab={tab} query={query:?}")
+        }
+    }
+    api_error(status, "query failed")
+}
+
+/// `/query` as Server-Sent Events: the page is fanned out once and results
+/// are pushed as each engine answers, rather than the client polling. Sends
+/// `results` (an array, in merged order) and `engine` (one engine's report)
+/// events as they happen, then either `done` (`{engines, hasMore, snapshot}`, as in
+/// `/query`'s response) or `failed` (an [`ApiErrorBody`]) — not `error`,
+/// which `EventSource` already fires for connection failures.
+#[allow(clippy::too_many_arguments)]
+#[get("/query/stream?<tab>&<query>&<snapshot>&<start>&<count>&<lang>&<safe>&<time>")]
+async fn query_stream(
+    config: &State<Config>,
+    _limit: RateLimited,
+    tab: &str,
+    query: &str,
+    snapshot: Option<i64>,
+    start: usize,
+    count: usize,
+    lang: Option<&str>,
+    safe: Option<&str>,
+    time: Option<&str>,
+) -> Result<EventStream<BoxStream<'static, Event>>, (Status, Json<ApiErrorBody>)> {
+    let params = validate_query(&config.paging, start, count, lang, safe, time)?;
+
+    let events = match tab {
+        "General" | "general" => search_events(
+            tab,
+            query,
+            SearchBuilder::new(query)
+                .engines(config.engines.general.iter().copied())
+                .params(params)
+                .timeout(config.engine_timeout())
+                .snapshot(snapshot)
+                .start(start)
+                .count(count)
+                .stream()
+                .await,
+        ),
+        "Images" | "images" => search_events(
+            tab,
+            query,
+            ImageSearchBuilder::new(query)
+                .engines(config.engines.images.iter().copied())
+                .params(params)
+                .timeout(config.engine_timeout())
+                .snapshot(snapshot)
+                .start(start)
+                .count(count)
+                .stream()
+                .await,
+        ),
+        "News" | "news" => search_events(
+            tab,
+            query,
+            NewsSearchBuilder::new(query)
+                .engines(config.engines.news.iter().copied())
+                .params(params)
+                .timeout(config.engine_timeout())
+                .snapshot(snapshot)
+                .start(start)
+                .count(count)
+                .stream()
+                .await,
+        ),
+        "Videos" | "videos" => search_events(
+            tab,
+            query,
+            VideoSearchBuilder::new(query)
+                .engines(config.engines.videos.iter().copied())
+                .params(params)
+                .timeout(config.engine_timeout())
+                .snapshot(snapshot)
+                .start(start)
+                .count(count)
+                .stream()
+                .await,
+        ),
+        _ => return Err(api_error(Status::BadRequest, "unknown tab requested")),
+    };
+
+    Ok(EventStream::from(events))
+}
+
+fn search_events<T: Serialize + Send + 'static>(
+    tab: &str,
+    query: &str,
+    events: UnboundedReceiver<SearchEvent<T>>,
+) -> BoxStream<'static, Event> {
+    let state = (events, tab.to_string(), query.to_string());
+    stream::unfold(state, |(mut events, tab, query)| async move {
+        let event = match events.recv().await? {
+            SearchEvent::Results(results) => Event::json(&results).event("results"),
+            SearchEvent::Engine(report) => Event::json(&report).event("engine"),
+            SearchEvent::Done {
+                engines,
+                has_more,
+                snapshot,
+            } => {
+                let done = json!({ "engines": engines, "hasMore": has_more, "snapshot": snapshot });
+                Event::json(&done).event("done")
+            }
+            SearchEvent::Failed(e) => {
+                let (_, Json(body)) = query_failed(&tab, &query, &e);
+                Event::json(&body).event("failed")
+            }
+        };
+        Some((event, (events, tab, query)))
+    })
+    .boxed()
+}
+
+/// Longest partial query `/suggest` passes upstream; anything longer is
+/// past the point where completions help.
+const MAX_SUGGEST_QUERY_CHARS: usize = 100;
+
+/// Query completions in the OpenSearch suggestions format
+/// (`["query", ["completion", ...]]`), so browsers can use it directly.
+#[get("/suggest?<q>")]
+async fn suggest(_limit: SuggestRateLimited, q: &str) -> Json<(String, Vec<String>)> {
+    let suggestions = if q.chars().count() > MAX_SUGGEST_QUERY_CHARS {
+        Vec::new()
+    } else {
+        private_search_engines::suggest(q).await
+    };
+    Json((q.to_string(), suggestions))
 }
 
 fn results_len(results: &QueryResults) -> usize {
     match results {
         QueryResults::General(r) => r.results.len(),
         QueryResults::Images(r) => r.results.len(),
+        QueryResults::News(r) => r.results.len(),
+        QueryResults::Videos(r) => r.results.len(),
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use rocket::local::asynchronous::Client;
+    use rocket::{
+        http::{Header, uri::Host},
+        local::asynchronous::Client,
+    };
 
     async fn client() -> Client {
-        Client::tracked(build_rocket())
+        client_with(Config::default()).await
+    }
+
+    async fn client_with(config: Config) -> Client {
+        Client::tracked(build_rocket(rocket::Config::figment(), config))
             .await
             .expect("failed to build test rocket instance")
     }
@@ -302,11 +762,144 @@ mod test {
         assert_eq!(res.status(), Status::Ok);
     }
 
+    /// A bogus tab fails validation before any engine is called, so this
+    /// exercises the server-rendered page without touching the network.
     #[rocket::async_test]
-    async fn search_page_renders() {
+    async fn search_page_renders_errors_in_place_of_results() {
         let client = client().await;
+        let res = client.get("/search?q=a%26b&t=bogus").dispatch().await;
+        assert_eq!(res.status(), Status::BadRequest);
+        let body = res.into_string().await.unwrap();
+        assert!(body.contains("Search failed: unknown tab requested"));
+        assert!(body.contains(r#"value="a&amp;b""#));
+        // Handlebars escapes `=` too; browsers decode it back.
+        assert!(body.contains(r#"href="/search?t&#x3D;images&amp;q&#x3D;a%26b""#));
+        // No rendered page for `search.js` to resume from — it polls instead.
+        assert!(!body.contains("data-next-start"));
+        assert!(body.contains("static/search.js"));
+    }
+
+    #[rocket::async_test]
+    async fn search_pages_are_never_cached_but_the_homepage_is() {
+        let client = client().await;
+        let res = client.get("/search?q=rust&t=bogus").dispatch().await;
+        let cache_control = res.headers().get_one("Cache-Control");
+        assert_eq!(cache_control, Some("private, no-store"));
+        let res = client.get("/").dispatch().await;
+        let cache_control = res.headers().get_one("Cache-Control");
+        assert_eq!(cache_control, Some("public, max-age=86400 "));
+    }
+
+    #[rocket::async_test]
+    async fn rate_limited_search_page_still_renders_the_shell() {
+        let client = client().await;
+        for _ in 0..30 {
+            client
+                .get("/query?tab=bogus&query=rust&start=0&count=1")
+                .dispatch()
+                .await;
+        }
         let res = client.get("/search?q=rust&t=general").dispatch().await;
+        assert_eq!(res.status(), Status::TooManyRequests);
+        let cache_control = res.headers().get_one("Cache-Control");
+        assert_eq!(cache_control, Some("private, no-store"));
+        let body = res.into_string().await.unwrap();
+        assert!(body.contains("Too many searches"));
+        assert!(body.contains("static/search.js"));
+    }
+
+    #[rocket::async_test]
+    async fn denied_networks_get_403_and_allowed_ones_skip_the_limit() {
+        let mut config = Config::default();
+        config.rate_limit.max_requests = 1;
+        config.rate_limit.deny = vec!["203.0.113.0/24".parse().unwrap()];
+        config.rate_limit.allow = vec!["198.51.100.0/24".parse().unwrap()];
+        let client = client_with(config).await;
+        let get = |url: &'static str, remote: &str| {
+            client.get(url).remote(remote.parse().unwrap()).dispatch()
+        };
+
+        let query = "/query?tab=bogus&query=rust&start=0&count=1";
+        let denied = "203.0.113.9:4000";
+        assert_eq!(get(query, denied).await.status(), Status::Forbidden);
+        let page = get("/search?q=rust", denied).await;
+        assert_eq!(page.status(), Status::Forbidden);
+        let body = page.into_string().await.unwrap();
+        assert!(body.contains("not available from your network"));
+
+        // Over `max_requests`, but never limited.
+        for _ in 0..3 {
+            let res = get(query, "198.51.100.5:4000").await;
+            assert_eq!(res.status(), Status::BadRequest);
+        }
+    }
+
+    #[rocket::async_test]
+    async fn forwarded_for_is_only_believed_from_trusted_proxies() {
+        let mut config = Config::default();
+        config.rate_limit.max_requests = 1;
+        config.trusted_proxies = vec!["10.0.0.0/8".parse().unwrap()];
+        let client = client_with(config).await;
+        let status = |remote: &str, forwarded_for: &'static str| {
+            let req = client
+                .get("/query?tab=bogus&query=rust&start=0&count=1")
+                .remote(remote.parse().unwrap())
+                .header(Header::new("X-Forwarded-For", forwarded_for));
+            async move { req.dispatch().await.status() }
+        };
+
+        // Behind the proxy, each client gets its own budget...
+        let proxy = "10.0.0.1:4000";
+        assert_eq!(status(proxy, "192.0.2.1").await, Status::BadRequest);
+        assert_eq!(status(proxy, "192.0.2.2").await, Status::BadRequest);
+        assert_eq!(status(proxy, "192.0.2.1").await, Status::TooManyRequests);
+
+        // ...but a client can't pick a fresh one by sending the header itself.
+        let direct = "203.0.113.5:4000";
+        assert_eq!(status(direct, "192.0.2.3").await, Status::BadRequest);
+        assert_eq!(status(direct, "192.0.2.4").await, Status::TooManyRequests);
+
+        // ...nor through the proxy, with a `Forwarded` it passes on untouched.
+        for (spoofed, expected) in [
+            ("for=192.0.2.6", Status::BadRequest),
+            ("for=192.0.2.7", Status::TooManyRequests),
+        ] {
+            let req = client
+                .get("/query?tab=bogus&query=rust&start=0&count=1")
+                .remote(proxy.parse().unwrap())
+                .header(Header::new("Forwarded", spoofed))
+                .header(Header::new("X-Forwarded-For", "192.0.2.5"));
+            assert_eq!(req.dispatch().await.status(), expected);
+        }
+    }
+
+    #[rocket::async_test]
+    async fn opensearch_description_advertises_search_and_suggest_urls() {
+        let client = client_with(Config {
+            public_url: Some("https://search.example.com".into()),
+            ..Config::default()
+        })
+        .await;
+        let mut req = client.get("/opensearch.xml");
+        req.set_host(Host::parse("evil.example").unwrap());
+        let res = req.dispatch().await;
         assert_eq!(res.status(), Status::Ok);
+        assert_eq!(
+            res.content_type(),
+            Some(ContentType::new("application", "opensearchdescription+xml"))
+        );
+        let body = res.into_string().await.unwrap();
+        assert!(body.contains(r#"template="https://search.example.com/search?q={searchTerms}"#));
+        assert!(body.contains(r#"template="https://search.example.com/suggest?q={searchTerms}""#));
+        assert!(!body.contains("evil.example"));
+    }
+
+    #[rocket::async_test]
+    async fn opensearch_description_needs_a_public_url() {
+        let client = client().await;
+        let mut req = client.get("/opensearch.xml");
+        req.set_host(Host::parse("search.example.com").unwrap());
+        assert_eq!(req.dispatch().await.status(), Status::NotFound);
     }
 
     #[rocket::async_test]
@@ -363,11 +956,55 @@ mod test {
         assert!(body.error.contains("tab"));
     }
 
+    #[rocket::async_test]
+    async fn query_rejects_unknown_search_params() {
+        let client = client().await;
+        for param in ["safe=sometimes", "time=fortnight"] {
+            let res = client
+                .get(format!(
+                    "/query?tab=general&query=rust&start=0&count=10&{param}"
+                ))
+                .dispatch()
+                .await;
+            assert_eq!(res.status(), Status::BadRequest, "{param}");
+            let body: ApiErrorBody = res.into_json().await.expect("expected a JSON error body");
+            assert!(body.error.contains("unknown search parameter"), "{param}");
+        }
+    }
+
+    /// Bad parameters are rejected up front, as plain JSON errors — nothing
+    /// is streamed.
+    #[rocket::async_test]
+    async fn query_stream_validates_like_query() {
+        let client = client().await;
+        for (url, needle) in [
+            ("/query/stream?tab=bogus&query=rust&start=0&count=10", "tab"),
+            (
+                "/query/stream?tab=general&query=rust&start=0&count=999",
+                "count",
+            ),
+        ] {
+            let res = client.get(url).dispatch().await;
+            assert_eq!(res.status(), Status::BadRequest, "{url}");
+            let body: ApiErrorBody = res.into_json().await.expect("expected a JSON error body");
+            assert!(body.error.contains(needle), "{url}");
+        }
+    }
+
+    #[rocket::async_test]
+    async fn suggest_skips_blank_queries_in_opensearch_format() {
+        let client = client().await;
+        let res = client.get("/suggest?q=%20").dispatch().await;
+        assert_eq!(res.status(), Status::Ok);
+        let body: (String, Vec<String>) = res.into_json().await.expect("expected a JSON array");
+        assert_eq!(body, (" ".to_string(), Vec::new()));
+    }
+
     #[rocket::async_test]
     async fn query_enforces_rate_limit() {
         let client = client().await;
         let mut saw_429 = false;
-        // MAX_REQUESTS_PER_WINDOW is 30; a bogus tab short-circuits before
+        // `rate_limit.max_requests` defaults to 30; a bogus tab short-circuits before
         // any network call, so this stays fast and hits the limiter directly.
         for _ in 0..40 {
             let res = client
@@ -381,4 +1018,39 @@ mod test {
         }
         assert!(saw_429, "expected to eventually be rate limited");
     }
+
+    #[rocket::async_test]
+    async fn metrics_count_rate_limit_rejections() {
+        let mut config = Config::default();
+        config.rate_limit.max_requests = 1;
+        let client = client_with(config).await;
+        let before = metrics::RATE_LIMITED.get(&["search", "over_limit"]);
+        for _ in 0..2 {
+            client
+                .get("/query?tab=bogus&query=rust&start=0&count=1")
+                .dispatch()
+                .await;
+        }
+        // Other tests share the counter, so only a lower bound holds.
+        assert!(metrics::RATE_LIMITED.get(&["search", "over_limit"]) > before);
+
+        let res = client.get("/metrics").dispatch().await;
+        assert_eq!(res.status(), Status::Ok);
+        let content_type = res.content_type().unwrap();
+        assert_eq!(content_type.to_string(), "text/plain; version=0.0.4");
+        let body = res.into_string().await.unwrap();
+        assert!(body.contains(
+            "private_search_rate_limited_total{limiter=\"search\",reason=\"over_limit\"}"
+        ));
+        for name in [
+            "private_search_engine_requests_total",
+            "private_search_engine_request_duration_seconds",
+            "private_search_cache_rows_total",
+            "private_search_cache_rows_appended",
+            "private_search_cache_refreshes_total",
+            "private_search_cache_cleanups_total",
+        ] {
+            assert!(body.contains(&format!("# TYPE {name} ")), "missing {name}");
+        }
+    }
 }
@@ -0,0 +1,37 @@
+//! The server's own metrics, rendered at `/metrics` together with the
+//! engine and cache metrics from [`private_search_engines::metrics`].
+
+use private_search_engines::metrics::{self, CounterVec};
+
+/// Requests turned away by [`RateLimiter`](crate::rate_limit::RateLimiter),
+/// by limiter (`search` or `suggest`) and reason (`over_limit` for a 429,
+/// `denied` for a 403).
+pub static RATE_LIMITED: CounterVec = CounterVec::new(
+    "private_search_rate_limited_total",
+    "Requests rejected by the rate limiter.",
+    &["limiter", "reason"],
+);
+
+/// Runs of the cache cleanup task, by `result` (`ok` or `error`).
+pub static CACHE_CLEANUPS: CounterVec = CounterVec::new(
+    "private_search_cache_cleanups_total",
+    "Cache cleanup runs, by result.",
+    &["result"],
+);
+
+/// Stale queries purged by the cache cleanup task.
+pub static CACHE_PURGED: CounterVec = CounterVec::new(
+    "private_search_cache_purged_queries_total",
+    "Stale cached queries purged by cache cleanup.",
+    &[],
+);
+
+/// Everything, in the Prometheus text exposition format.
+pub fn render() -> String {
+    let mut out = String::new();
+    metrics::render(&mut out);
+    RATE_LIMITED.render(&mut out);
+    CACHE_CLEANUPS.render(&mut out);
+    CACHE_PURGED.render(&mut out);
+    out
+}
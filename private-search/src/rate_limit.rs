@@ -1,10 +1,11 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     net::IpAddr,
     sync::Mutex,
     time::{Duration, Instant},
 };
 
+use ipnet::IpNet;
 use rocket::{
     Request,
     http::Status,
@@ -12,88 +13,292 @@ use rocket::{
     request::{self, FromRequest},
 };
 
-const WINDOW: Duration = Duration::from_secs(60);
-const MAX_REQUESTS_PER_WINDOW: u32 = 30;
+use crate::{client_ip::TrustedProxies, metrics::RATE_LIMITED};
 
-/// Per-IP fixed-window limiter. `/query` fans a single request out to
+/// Per-IP token-bucket limiter. `/query` fans a single request out to
 /// multiple upstream search engines, so letting it be hit unbounded means
 /// letting *those* engines be hit unbounded through us — risking an IP ban
 /// on the whole deployment, or the instance being used as a free scraping
 /// proxy.
+///
+/// Each IP gets a bucket of `max_per_window` requests that refills steadily
+/// over `window`, so a client can burst up to the limit but never spend two
+/// windows' worth back to back the way a fixed window allows at its
+/// boundary.
 pub struct RateLimiter {
-    hits: Mutex<HashMap<IpAddr, (Instant, u32)>>,
+    buckets: Mutex<Buckets>,
     window: Duration,
     max_per_window: u32,
+    max_tracked: usize,
 }
 
-impl Default for RateLimiter {
-    fn default() -> Self {
-        Self::new(WINDOW, MAX_REQUESTS_PER_WINDOW)
-    }
+/// Keyed by the client's network (see [`ClientPolicy`]), not its exact
+/// address.
+struct Buckets {
+    by_ip: HashMap<IpNet, Bucket>,
+    /// Every touch of a bucket, oldest first, tagged with the bucket's
+    /// `touch` at the time. An entry whose tag no longer matches was
+    /// superseded by a later touch and is skipped, so eviction never has to
+    /// search the map for the least recently used bucket.
+    touches: VecDeque<(IpNet, u64)>,
+    next_touch: u64,
+    last_sweep: Instant,
+}
+
+#[derive(Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    touch: u64,
 }
 
 impl RateLimiter {
-    /// Exposed (rather than hardcoding `WINDOW`/`MAX_REQUESTS_PER_WINDOW`
-    /// everywhere) so tests can use a short window instead of waiting on the
-    /// real 60s production one.
-    fn new(window: Duration, max_per_window: u32) -> Self {
+    /// Allows `max_per_window` hits per IP per `window`, tracking at most
+    /// `max_tracked` IPs at once — all from the `rate_limit` config section.
+    pub fn new(window: Duration, max_per_window: u32, max_tracked: usize) -> Self {
         Self {
-            hits: Mutex::new(HashMap::new()),
+            buckets: Mutex::new(Buckets {
+                by_ip: HashMap::new(),
+                touches: VecDeque::new(),
+                next_touch: 0,
+                last_sweep: Instant::now(),
+            }),
             window,
             max_per_window,
+            max_tracked,
         }
     }
 
-    /// Returns `true` if `ip` is still under the limit for its current
-    /// window (and records the hit), `false` if it should be rejected.
-    fn allow(&self, ip: IpAddr) -> bool {
-        let mut hits = self.hits.lock().unwrap();
-        let now = Instant::now();
-
-        match hits.get_mut(&ip) {
-            Some((window_start, count)) if now.duration_since(*window_start) < self.window => {
-                if *count >= self.max_per_window {
-                    false
-                } else {
-                    *count += 1;
-                    true
-                }
+    /// Returns `true` if `ip` still has a request left in its bucket (and
+    /// takes it), `false` if it should be rejected.
+    fn allow(&self, ip: IpNet) -> bool {
+        self.allow_at(ip, Instant::now())
+    }
+
+    fn allow_at(&self, ip: IpNet, now: Instant) -> bool {
+        let mut buckets = self.buckets.lock().unwrap();
+
+        // A bucket that has refilled is no different from having none, so
+        // those are dropped once a window instead of piling up forever.
+        if now.duration_since(buckets.last_sweep) >= self.window {
+            buckets.by_ip.retain(|_, b| !self.is_full(b, now));
+            buckets.drop_stale_touches();
+            buckets.last_sweep = now;
+        }
+
+        let tokens = match buckets.by_ip.get(&ip) {
+            Some(bucket) => self.refilled(bucket, now),
+            None => {
+                buckets.make_room(self.max_tracked);
+                f64::from(self.max_per_window)
             }
-            _ => {
-                hits.insert(ip, (now, 1));
-                true
+        };
+        let mut bucket = Bucket {
+            tokens,
+            updated: now,
+            touch: buckets.next_touch,
+        };
+        buckets.next_touch += 1;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+        buckets.by_ip.insert(ip, bucket);
+        buckets.touches.push_back((ip, bucket.touch));
+        // Stale touches pile up for busy IPs; once they outnumber the
+        // tracked ones, a pass costs no more than the pushes since the last.
+        if buckets.touches.len() > 2 * self.max_tracked {
+            buckets.drop_stale_touches();
+        }
+        allowed
+    }
+
+    /// `bucket`'s tokens as of `now`, topped up for the time since it was
+    /// last touched.
+    fn refilled(&self, bucket: &Bucket, now: Instant) -> f64 {
+        let per_sec = f64::from(self.max_per_window) / self.window.as_secs_f64();
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        (bucket.tokens + elapsed * per_sec).min(f64::from(self.max_per_window))
+    }
+
+    fn is_full(&self, bucket: &Bucket, now: Instant) -> bool {
+        self.refilled(bucket, now) >= f64::from(self.max_per_window)
+    }
+
+    #[cfg(test)]
+    fn tracked(&self) -> usize {
+        self.buckets.lock().unwrap().by_ip.len()
+    }
+}
+
+impl Buckets {
+    /// Keeps the map under `max_tracked` before a new IP is added by
+    /// dropping the least recently used buckets. Whoever is evicted just
+    /// starts over with a full bucket, so under a flood of fresh addresses
+    /// the cost is some leniency, not unbounded memory.
+    fn make_room(&mut self, max_tracked: usize) {
+        while self.by_ip.len() >= max_tracked {
+            let Some((ip, touch)) = self.touches.pop_front() else {
+                break;
+            };
+            if self.by_ip.get(&ip).is_some_and(|b| b.touch == touch) {
+                self.by_ip.remove(&ip);
             }
         }
     }
+
+    fn drop_stale_touches(&mut self) {
+        let by_ip = &self.by_ip;
+        self.touches
+            .retain(|(ip, touch)| by_ip.get(ip).is_some_and(|b| b.touch == *touch));
+    }
+}
+
+/// The separate, larger budget for `/suggest`, managed alongside the
+/// main [`RateLimiter`].
+pub struct SuggestLimiter(RateLimiter);
+
+impl SuggestLimiter {
+    pub fn new(window: Duration, max_per_window: u32, max_tracked: usize) -> Self {
+        Self(RateLimiter::new(window, max_per_window, max_tracked))
+    }
+}
+
+/// What a client address counts as for rate limiting. Addresses are
+/// limited per network rather than one by one: a single IPv6 subscriber
+/// usually gets a whole /64 (or more), so per-address buckets would give
+/// them billions of budgets.
+pub struct ClientPolicy {
+    ipv4_prefix: u8,
+    ipv6_prefix: u8,
+    /// Never limited (e.g. our own monitoring).
+    allow: Vec<IpNet>,
+    /// Refused outright, ahead of `allow`.
+    deny: Vec<IpNet>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Client {
+    Denied,
+    Exempt,
+    /// Limited under this network's bucket.
+    Limited(IpNet),
+}
+
+impl ClientPolicy {
+    /// Prefix lengths must fit their address family (at most 32 and 128);
+    /// the config is validated for that at startup.
+    pub fn new(ipv4_prefix: u8, ipv6_prefix: u8, allow: Vec<IpNet>, deny: Vec<IpNet>) -> Self {
+        Self {
+            ipv4_prefix,
+            ipv6_prefix,
+            allow,
+            deny,
+        }
+    }
+
+    fn classify(&self, ip: IpAddr) -> Client {
+        // IPv4 clients on a dual-stack socket show up as `::ffff:a.b.c.d`.
+        let ip = ip.to_canonical();
+        if self.deny.iter().any(|net| net.contains(&ip)) {
+            return Client::Denied;
+        }
+        if self.allow.iter().any(|net| net.contains(&ip)) {
+            return Client::Exempt;
+        }
+        let prefix = match ip {
+            IpAddr::V4(_) => self.ipv4_prefix,
+            IpAddr::V6(_) => self.ipv6_prefix,
+        };
+        let network = IpNet::new(ip, prefix).expect("prefix length out of range for address");
+        Client::Limited(network.trunc())
+    }
+}
+
+/// Why a [`RateLimited`]/[`SuggestRateLimited`] guard turned a request
+/// away — for routes that take the guard as a `Result` to report it
+/// themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejected {
+    /// `429 Too Many Requests`: over budget for now.
+    OverLimit,
+    /// `403 Forbidden`: on the deny-list.
+    Denied,
+}
+
+/// Records a hit from `req`'s caller against `limiter`, rejecting with
+/// `429 Too Many Requests` once it's over budget, or `403 Forbidden` if the
+/// caller is denied altogether. Rejections are counted in
+/// [`RATE_LIMITED`] under `name`.
+fn admit<T>(
+    req: &Request<'_>,
+    name: &str,
+    limiter: &RateLimiter,
+    guard: T,
+) -> request::Outcome<T, Rejected> {
+    let policy = req
+        .rocket()
+        .state::<ClientPolicy>()
+        .expect("ClientPolicy must be managed state");
+    let proxies = req
+        .rocket()
+        .state::<TrustedProxies>()
+        .expect("TrustedProxies must be managed state");
+    // Falls back to a fixed key if we genuinely can't determine the
+    // caller's address, so that fails closed (shared rate limit) rather
+    // than open (no limit at all).
+    let ip = proxies
+        .client_ip(req)
+        .unwrap_or_else(|| IpAddr::from([0, 0, 0, 0]));
+
+    match policy.classify(ip) {
+        Client::Denied => {
+            RATE_LIMITED.inc(&[name, "denied"]);
+            Outcome::Error((Status::Forbidden, Rejected::Denied))
+        }
+        Client::Exempt => Outcome::Success(guard),
+        Client::Limited(network) if limiter.allow(network) => Outcome::Success(guard),
+        Client::Limited(_) => {
+            RATE_LIMITED.inc(&[name, "over_limit"]);
+            Outcome::Error((Status::TooManyRequests, Rejected::OverLimit))
+        }
+    }
 }
 
 /// Request guard that enforces [`RateLimiter`] on whichever route declares
-/// it as a parameter. Rejects with `429 Too Many Requests` once an IP
-/// exceeds [`MAX_REQUESTS_PER_WINDOW`] in a rolling [`WINDOW`].
+/// it as a parameter. Rejects with `429 Too Many Requests` once a client's
+/// network has used up its `rate_limit.max_requests` bucket, and with
+/// `403 Forbidden` if it's in `rate_limit.deny`.
 pub struct RateLimited;
 
 #[rocket::async_trait]
 impl<'r> FromRequest<'r> for RateLimited {
-    type Error = ();
+    type Error = Rejected;
 
     async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
         let limiter = req
             .rocket()
             .state::<RateLimiter>()
             .expect("RateLimiter must be managed state");
+        admit(req, "search", limiter, RateLimited)
+    }
+}
 
-        // Falls back to a fixed key if we genuinely can't determine the
-        // caller's address, so a misconfigured proxy fails closed (shared
-        // rate limit) rather than open (no limit at all).
-        let ip = req
-            .client_ip()
-            .unwrap_or_else(|| IpAddr::from([0, 0, 0, 0]));
-
-        if limiter.allow(ip) {
-            Outcome::Success(RateLimited)
-        } else {
-            Outcome::Error((Status::TooManyRequests, ()))
-        }
+/// Like [`RateLimited`], but against the [`SuggestLimiter`] budget
+/// (`rate_limit.max_suggestions`).
+pub struct SuggestRateLimited;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for SuggestRateLimited {
+    type Error = Rejected;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let limiter = req
+            .rocket()
+            .state::<SuggestLimiter>()
+            .expect("SuggestLimiter must be managed state");
+        admit(req, "suggest", &limiter.0, SuggestRateLimited)
     }
 }
 
@@ -101,13 +306,13 @@ impl<'r> FromRequest<'r> for RateLimited {
 mod test {
     use super::*;
 
-    fn ip(n: u8) -> IpAddr {
-        IpAddr::from([127, 0, 0, n])
+    fn ip(n: u8) -> IpNet {
+        IpNet::from(IpAddr::from([127, 0, 0, n]))
     }
 
     #[test]
     fn allows_requests_under_the_limit() {
-        let limiter = RateLimiter::new(Duration::from_secs(60), 3);
+        let limiter = RateLimiter::new(Duration::from_secs(60), 3, 100);
         assert!(limiter.allow(ip(1)));
         assert!(limiter.allow(ip(1)));
         assert!(limiter.allow(ip(1)));
@@ -115,7 +320,7 @@ mod test {
 
     #[test]
     fn rejects_once_the_limit_is_exceeded_within_a_window() {
-        let limiter = RateLimiter::new(Duration::from_secs(60), 3);
+        let limiter = RateLimiter::new(Duration::from_secs(60), 3, 100);
         for _ in 0..3 {
             assert!(limiter.allow(ip(1)));
         }
@@ -124,7 +329,7 @@ mod test {
 
     #[test]
     fn tracks_each_ip_independently() {
-        let limiter = RateLimiter::new(Duration::from_secs(60), 1);
+        let limiter = RateLimiter::new(Duration::from_secs(60), 1, 100);
         assert!(limiter.allow(ip(1)));
         assert!(!limiter.allow(ip(1)));
         // A different IP has its own budget, unaffected by ip(1)'s.
@@ -133,7 +338,7 @@ mod test {
 
     #[test]
     fn resets_once_the_window_elapses() {
-        let limiter = RateLimiter::new(Duration::from_millis(20), 1);
+        let limiter = RateLimiter::new(Duration::from_millis(20), 1, 100);
         assert!(limiter.allow(ip(1)));
         assert!(!limiter.allow(ip(1)), "still inside the first window");
 
@@ -141,4 +346,102 @@ mod test {
 
         assert!(limiter.allow(ip(1)), "a new window should reset the budget");
     }
+
+    #[test]
+    fn refills_gradually_instead_of_resetting_at_a_boundary() {
+        let limiter = RateLimiter::new(Duration::from_secs(60), 4, 100);
+        let t0 = Instant::now();
+        for _ in 0..4 {
+            assert!(limiter.allow_at(ip(1), t0));
+        }
+        // A fixed window starting just before `t0` would allow another 4
+        // here; the bucket has only refilled a fraction of one.
+        assert!(!limiter.allow_at(ip(1), t0 + Duration::from_secs(2)));
+        assert!(limiter.allow_at(ip(1), t0 + Duration::from_secs(17)));
+        assert!(!limiter.allow_at(ip(1), t0 + Duration::from_secs(18)));
+    }
+
+    #[test]
+    fn forgets_ips_whose_buckets_have_refilled() {
+        let limiter = RateLimiter::new(Duration::from_secs(60), 2, 100);
+        let t0 = Instant::now();
+        assert!(limiter.allow_at(ip(1), t0));
+        assert!(limiter.allow_at(ip(2), t0));
+        assert_eq!(limiter.tracked(), 2);
+
+        assert!(limiter.allow_at(ip(3), t0 + Duration::from_secs(61)));
+        assert_eq!(limiter.tracked(), 1);
+    }
+
+    #[test]
+    fn never_tracks_more_than_the_cap() {
+        let limiter = RateLimiter::new(Duration::from_secs(60), 1, 2);
+        let t0 = Instant::now();
+        for n in 1..=5 {
+            assert!(limiter.allow_at(ip(n), t0 + Duration::from_secs(n.into())));
+            assert!(limiter.tracked() <= 2);
+        }
+        // The most recent IPs are the ones kept, still limited.
+        assert!(!limiter.allow_at(ip(5), t0 + Duration::from_secs(6)));
+        assert!(limiter.allow_at(ip(1), t0 + Duration::from_secs(6)));
+    }
+
+    #[test]
+    fn evicts_the_least_recently_used_ip_and_keeps_the_queue_bounded() {
+        let limiter = RateLimiter::new(Duration::from_secs(60), 100, 2);
+        let t0 = Instant::now();
+        limiter.allow_at(ip(1), t0);
+        limiter.allow_at(ip(2), t0);
+        for _ in 0..10 {
+            limiter.allow_at(ip(1), t0);
+        }
+        assert!(limiter.buckets.lock().unwrap().touches.len() <= 4);
+
+        // ip(2) was touched least recently, despite ip(1) going first.
+        limiter.allow_at(ip(3), t0);
+        let buckets = limiter.buckets.lock().unwrap();
+        assert!(buckets.by_ip.contains_key(&ip(1)));
+        assert!(!buckets.by_ip.contains_key(&ip(2)));
+    }
+
+    fn policy(ipv4_prefix: u8, allow: &[&str], deny: &[&str]) -> ClientPolicy {
+        let nets = |nets: &[&str]| nets.iter().map(|n| n.parse().unwrap()).collect();
+        ClientPolicy::new(ipv4_prefix, 64, nets(allow), nets(deny))
+    }
+
+    fn limited(network: &str) -> Client {
+        Client::Limited(network.parse().unwrap())
+    }
+
+    #[test]
+    fn clients_are_keyed_by_their_network() {
+        let policy = policy(24, &[], &[]);
+        assert_eq!(
+            policy.classify("2001:db8:1:2:aaaa::1".parse().unwrap()),
+            limited("2001:db8:1:2::/64")
+        );
+        assert_eq!(
+            policy.classify("2001:db8:1:2:bbbb::2".parse().unwrap()),
+            limited("2001:db8:1:2::/64")
+        );
+        assert_eq!(
+            policy.classify("198.51.100.77".parse().unwrap()),
+            limited("198.51.100.0/24")
+        );
+        assert_eq!(
+            policy.classify("::ffff:198.51.100.9".parse().unwrap()),
+            limited("198.51.100.0/24")
+        );
+    }
+
+    #[test]
+    fn deny_list_wins_over_allow_list() {
+        let policy = policy(32, &["10.0.0.0/8"], &["10.6.6.0/24"]);
+        assert_eq!(policy.classify("10.1.2.3".parse().unwrap()), Client::Exempt);
+        assert_eq!(policy.classify("10.6.6.6".parse().unwrap()), Client::Denied);
+        assert_eq!(
+            policy.classify("192.0.2.1".parse().unwrap()),
+            limited("192.0.2.1/32")
+        );
+    }
 }
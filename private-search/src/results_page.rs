@@ -0,0 +1,290 @@
+use chrono::{DateTime, Utc};
+use rocket::serde::Serialize;
+
+use private_search_engines::{EngineReport, EngineStatus};
+
+use crate::QueryResults;
+
+/// One page of results shaped for `search.html.hbs`, so `/search` can render
+/// them server-side for clients without JavaScript. Formatting mirrors what
+/// `search.js` does with the same data from `/query` (see `search-core.js`),
+/// so a page looks the same whichever side rendered it.
+#[derive(Serialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct ResultsPage {
+    pub results: Vec<ResultView>,
+    pub engines: Vec<EngineView>,
+    pub has_more: bool,
+    /// For the next page's link, so it continues the same results.
+    pub snapshot: i64,
+}
+
+/// A single result, flattened across tabs — each tab's markup only reads
+/// the fields it has.
+#[derive(Serialize, Debug, Default)]
+#[serde(crate = "rocket::serde")]
+pub struct ResultView {
+    href: String,
+    url: String,
+    title: String,
+    description: String,
+    engines: Vec<String>,
+    cached: bool,
+    thumbnail: Option<String>,
+    source: Option<String>,
+    /// RFC 3339, for `<time datetime>`.
+    published: Option<String>,
+    age: Option<String>,
+    /// Uploader and age, already joined for display.
+    meta: Option<String>,
+    duration: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct EngineView {
+    engine: String,
+    /// `EngineStatus`'s serialized tag, doubling as the status dot's class.
+    status: &'static str,
+    label: String,
+    detail: Option<String>,
+}
+
+impl ResultsPage {
+    /// `now` is what news/video ages are relative to; passed in so tests
+    /// don't depend on the clock.
+    pub fn new(results: &QueryResults, now: DateTime<Utc>) -> Self {
+        let (results, engines, has_more, snapshot) = match results {
+            QueryResults::General(r) => (
+                r.results
+                    .iter()
+                    .map(|r| ResultView {
+                        href: safe_href(&r.url),
+                        url: r.url.clone(),
+                        title: r.title.clone(),
+                        description: r.description.clone(),
+                        engines: r.engines.clone(),
+                        cached: r.cached,
+                        ..Default::default()
+                    })
+                    .collect(),
+                &r.engines,
+                r.has_more,
+                r.snapshot,
+            ),
+            QueryResults::Images(r) => (
+                r.results
+                    .iter()
+                    .map(|r| ResultView {
+                        href: safe_href(&r.url),
+                        url: r.url.clone(),
+                        title: r.title.clone(),
+                        engines: r.engines.clone(),
+                        cached: r.cached,
+                        ..Default::default()
+                    })
+                    .collect(),
+                &r.engines,
+                r.has_more,
+                r.snapshot,
+            ),
+            QueryResults::News(r) => (
+                r.results
+                    .iter()
+                    .map(|r| ResultView {
+                        href: safe_href(&r.url),
+                        url: r.url.clone(),
+                        title: r.title.clone(),
+                        description: r.snippet.clone(),
+                        engines: r.engines.clone(),
+                        cached: r.cached,
+                        thumbnail: r.thumbnail.as_deref().map(safe_href),
+                        source: Some(r.source.clone()).filter(|s| !s.is_empty()),
+                        published: r.published.map(|p| p.to_rfc3339()),
+                        age: r.published.map(|p| time_ago(p, now)),
+                        ..Default::default()
+                    })
+                    .collect(),
+                &r.engines,
+                r.has_more,
+                r.snapshot,
+            ),
+            QueryResults::Videos(r) => (
+                r.results
+                    .iter()
+                    .map(|r| {
+                        let age = r.published.map(|p| time_ago(p, now));
+                        let meta = [r.uploader.clone(), age]
+                            .into_iter()
+                            .flatten()
+                            .filter(|s| !s.is_empty())
+                            .collect::<Vec<_>>()
+                            .join(" · ");
+                        ResultView {
+                            href: safe_href(&r.url),
+                            url: r.url.clone(),
+                            title: r.title.clone(),
+                            engines: r.engines.clone(),
+                            cached: r.cached,
+                            thumbnail: r.thumbnail.as_deref().map(safe_href),
+                            meta: Some(meta).filter(|m| !m.is_empty()),
+                            duration: r.duration.map(format_duration),
+                            ..Default::default()
+                        }
+                    })
+                    .collect(),
+                &r.engines,
+                r.has_more,
+                r.snapshot,
+            ),
+        };
+
+        Self {
+            results,
+            engines: engines.iter().map(engine_view).collect(),
+            has_more,
+            snapshot,
+        }
+    }
+}
+
+/// Result urls come from scraped, untrusted third-party HTML: only http(s)
+/// links make it into an `href`/`src` (blocks `javascript:`/`data:` etc.),
+/// like `safeUrl` in `search-core.js`. Handlebars escapes the rest.
+fn safe_href(url: &str) -> String {
+    match url.split_once("://") {
+        Some((scheme, _))
+            if scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https") =>
+        {
+            url.to_string()
+        }
+        _ => "#".to_string(),
+    }
+}
+
+/// Same labels as `engineStatusLabel` in `search-core.js`.
+fn engine_view(report: &EngineReport) -> EngineView {
+    let (status, label, detail) = match &report.status {
+        EngineStatus::Ok => ("ok", "responded".to_string(), None),
+        EngineStatus::TimedOut => ("timed_out", "timed out".to_string(), None),
+        EngineStatus::Failed(detail) => ("failed", "failed".to_string(), Some(detail.clone())),
+        EngineStatus::Blocked(detail) => ("blocked", "blocked".to_string(), Some(detail.clone())),
+        EngineStatus::RateLimited(Some(secs)) => (
+            "rate_limited",
+            format!("rate limited (retry in {secs}s)"),
+            Some(secs.to_string()),
+        ),
+        EngineStatus::RateLimited(None) => ("rate_limited", "rate limited".to_string(), None),
+        EngineStatus::HttpStatus(code) => (
+            "http_status",
+            format!("HTTP {code}"),
+            Some(code.to_string()),
+        ),
+        EngineStatus::Skipped => ("skipped", "skipped (can't apply filters)".to_string(), None),
+    };
+    EngineView {
+        engine: report.engine.clone(),
+        status,
+        label,
+        detail,
+    }
+}
+
+/// "5m ago"/"3h ago"/"2d ago", or the date for anything over a week old —
+/// `timeAgo` in `search-core.js`.
+fn time_ago(published: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let minutes = (now - published).num_minutes().max(0);
+    if minutes < 60 {
+        return format!("{minutes}m ago");
+    }
+    let hours = minutes / 60;
+    if hours < 24 {
+        return format!("{hours}h ago");
+    }
+    let days = hours / 24;
+    if days < 7 {
+        return format!("{days}d ago");
+    }
+    published.format("%Y-%m-%d").to_string()
+}
+
+/// "4:05"/"1:02:03" — `formatDuration` in `search-core.js`.
+fn format_duration(secs: u64) -> String {
+    let (h, m, s) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m}:{s:02}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+    use private_search_engines::{SearchResponse, VideoResult};
+
+    #[test]
+    fn only_http_urls_become_links() {
+        assert_eq!(
+            safe_href("https://example.com/a?b=c"),
+            "https://example.com/a?b=c"
+        );
+        assert_eq!(safe_href("HTTP://example.com"), "HTTP://example.com");
+        assert_eq!(safe_href("javascript:alert(1)"), "#");
+        assert_eq!(safe_href("data:text/html,hi"), "#");
+        assert_eq!(safe_href("/relative"), "#");
+    }
+
+    #[test]
+    fn formats_ages_and_durations_like_the_client() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 10, 12, 0, 0).unwrap();
+        assert_eq!(time_ago(now - chrono::Duration::minutes(5), now), "5m ago");
+        assert_eq!(time_ago(now - chrono::Duration::hours(3), now), "3h ago");
+        assert_eq!(time_ago(now - chrono::Duration::days(2), now), "2d ago");
+        assert_eq!(
+            time_ago(now - chrono::Duration::days(30), now),
+            "2024-05-11"
+        );
+        // Clock skew upstream shouldn't produce "-2m ago".
+        assert_eq!(time_ago(now + chrono::Duration::minutes(2), now), "0m ago");
+
+        assert_eq!(format_duration(245), "4:05");
+        assert_eq!(format_duration(3723), "1:02:03");
+    }
+
+    #[test]
+    fn videos_flatten_into_display_fields() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 10, 12, 0, 0).unwrap();
+        let page = ResultsPage::new(
+            &QueryResults::Videos(SearchResponse {
+                results: vec![VideoResult {
+                    url: "https://video.example/watch?v=1".into(),
+                    title: "Talk".into(),
+                    duration: Some(3723),
+                    uploader: Some("RustConf".into()),
+                    published: Some(now - chrono::Duration::hours(3)),
+                    thumbnail: Some("javascript:alert(1)".into()),
+                    engines: vec!["Brave".into()],
+                    cached: false,
+                }],
+                engines: vec![EngineReport {
+                    engine: "Brave".into(),
+                    status: EngineStatus::RateLimited(Some(30)),
+                }],
+                has_more: true,
+                snapshot: 7,
+            }),
+            now,
+        );
+
+        let video = &page.results[0];
+        assert_eq!(video.href, "https://video.example/watch?v=1");
+        assert_eq!(video.meta.as_deref(), Some("RustConf · 3h ago"));
+        assert_eq!(video.duration.as_deref(), Some("1:02:03"));
+        assert_eq!(video.thumbnail.as_deref(), Some("#"));
+        assert_eq!(page.engines[0].status, "rate_limited");
+        assert_eq!(page.engines[0].label, "rate limited (retry in 30s)");
+        assert!(page.has_more);
+    }
+}
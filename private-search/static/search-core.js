@@ -27,10 +27,10 @@ export function safeUrl(url, base) {
 }
 
 export function unwrapPayload(obj) {
-  const empty = { results: [], engines: [], hasMore: false };
+  const empty = { results: [], engines: [], hasMore: false, snapshot: null };
   if (!obj || typeof obj !== "object") return empty;
 
-  const payload = obj.General || obj.Images;
+  const payload = obj.General || obj.Images || obj.News || obj.Videos;
   if (!payload) {
     console.warn("Unknown response variant:", obj);
     return empty;
@@ -40,9 +40,66 @@ export function unwrapPayload(obj) {
     results: payload.results || [],
     engines: payload.engines || [],
     hasMore: !!payload.hasMore,
+    snapshot: payload.snapshot ?? null,
   };
 }
 
+// Short label for one engine's `{status, detail}` report (see
+// `EngineStatus` in the engines crate). Blocked/rate-limited are called out
+// separately from "failed" — they mean the engine is refusing us, not that
+// it's broken.
+export function engineStatusLabel(status, detail) {
+  switch (status) {
+    case "ok":
+      return "responded";
+    case "timed_out":
+      return "timed out";
+    case "blocked":
+      return "blocked";
+    case "rate_limited":
+      return detail != null ? `rate limited (retry in ${detail}s)` : "rate limited";
+    case "http_status":
+      return `HTTP ${detail}`;
+    case "skipped":
+      return "skipped (can't apply filters)";
+    default:
+      return "failed";
+  }
+}
+
+// Compact age for a news story's RFC 3339 `published` timestamp ("5m ago",
+// "3h ago", "2d ago"), falling back to the date for anything over a week
+// old. `now` is a millisecond timestamp, passed in for the same reason as
+// `safeUrl`'s `base`.
+export function timeAgo(published, now) {
+  const then = Date.parse(published);
+  if (Number.isNaN(then)) return "";
+
+  const minutes = Math.max(0, Math.floor((now - then) / 60000));
+  if (minutes < 60) return `${minutes}m ago`;
+  const hours = Math.floor(minutes / 60);
+  if (hours < 24) return `${hours}h ago`;
+  const days = Math.floor(hours / 24);
+  if (days < 7) return `${days}d ago`;
+  return new Date(then).toISOString().slice(0, 10);
+}
+
+// "4:05" / "1:02:03" for a video length in whole seconds.
+export function formatDuration(secs) {
+  if (typeof secs !== "number" || secs < 0) return "";
+  const h = Math.floor(secs / 3600);
+  const m = Math.floor((secs % 3600) / 60);
+  const s = String(secs % 60).padStart(2, "0");
+  return h > 0 ? `${h}:${String(m).padStart(2, "0")}:${s}` : `${m}:${s}`;
+}
+
+// The completions out of an OpenSearch suggestions payload
+// (`["query", ["completion", ...]]`), or `[]` for anything else.
+export function suggestionList(payload) {
+  if (!Array.isArray(payload) || !Array.isArray(payload[1])) return [];
+  return payload[1].filter(s => typeof s === "string");
+}
+
 // `search` is a `location.search`-shaped string (e.g. "?q=rust&t=general"),
 // passed explicitly rather than read from `location` so this is callable
 // from Node tests with no DOM.
@@ -5,6 +5,10 @@ import {
   safeUrl,
   unwrapPayload,
   getQueryParam,
+  engineStatusLabel,
+  timeAgo,
+  formatDuration,
+  suggestionList,
   SkeletonQueue,
 } from "./search-core.js";
 
@@ -51,27 +55,38 @@ test("safeUrl preserves non-ASCII paths", () => {
 
 test("unwrapPayload extracts the General variant", () => {
   const result = unwrapPayload({
-    General: { results: [{ url: "https://a.com" }], engines: [{ engine: "Brave" }], hasMore: true },
+    General: { results: [{ url: "https://a.com" }], engines: [{ engine: "Brave" }], hasMore: true, snapshot: 3 },
   });
   assert.deepEqual(result, {
     results: [{ url: "https://a.com" }],
     engines: [{ engine: "Brave" }],
     hasMore: true,
+    snapshot: 3,
   });
 });
 
 test("unwrapPayload extracts the Images variant", () => {
   const result = unwrapPayload({ Images: { results: [], engines: [], hasMore: false } });
-  assert.deepEqual(result, { results: [], engines: [], hasMore: false });
+  assert.deepEqual(result, { results: [], engines: [], hasMore: false, snapshot: null });
+});
+
+test("unwrapPayload extracts the News variant", () => {
+  const result = unwrapPayload({ News: { results: [{ url: "https://a.com" }], engines: [], hasMore: true } });
+  assert.deepEqual(result, { results: [{ url: "https://a.com" }], engines: [], hasMore: true, snapshot: null });
+});
+
+test("unwrapPayload extracts the Videos variant", () => {
+  const result = unwrapPayload({ Videos: { results: [], engines: [{ engine: "Brave" }], hasMore: false } });
+  assert.deepEqual(result, { results: [], engines: [{ engine: "Brave" }], hasMore: false, snapshot: null });
 });
 
 test("unwrapPayload defaults missing fields safely", () => {
   const result = unwrapPayload({ General: {} });
-  assert.deepEqual(result, { results: [], engines: [], hasMore: false });
+  assert.deepEqual(result, { results: [], engines: [], hasMore: false, snapshot: null });
 });
 
 test("unwrapPayload returns an empty default for malformed input", () => {
-  const empty = { results: [], engines: [], hasMore: false };
+  const empty = { results: [], engines: [], hasMore: false, snapshot: null };
   assert.deepEqual(unwrapPayload(null), empty);
   assert.deepEqual(unwrapPayload(undefined), empty);
   assert.deepEqual(unwrapPayload("not an object"), empty);
@@ -121,3 +136,40 @@ test("SkeletonQueue never returns the same item twice", () => {
   const seen = [queue.next(() => "new"), queue.next(() => "new"), queue.next(() => "new")];
   assert.deepEqual(seen, ["only", "new", "new"]);
 });
+
+test("engineStatusLabel tells blocks and rate limits apart from failures", () => {
+  assert.equal(engineStatusLabel("ok"), "responded");
+  assert.equal(engineStatusLabel("blocked", "captcha"), "blocked");
+  assert.equal(engineStatusLabel("rate_limited", 30), "rate limited (retry in 30s)");
+  assert.equal(engineStatusLabel("rate_limited", null), "rate limited");
+  assert.equal(engineStatusLabel("http_status", 502), "HTTP 502");
+  assert.equal(engineStatusLabel("skipped"), "skipped (can't apply filters)");
+  assert.equal(engineStatusLabel("failed", "parse error"), "failed");
+});
+
+test("timeAgo picks the coarsest unit under a week, then shows the date", () => {
+  const now = Date.parse("2025-01-10T12:00:00Z");
+  assert.equal(timeAgo("2025-01-10T11:55:00Z", now), "5m ago");
+  assert.equal(timeAgo("2025-01-10T09:00:00Z", now), "3h ago");
+  assert.equal(timeAgo("2025-01-08T12:00:00Z", now), "2d ago");
+  assert.equal(timeAgo("2024-12-01T08:00:00Z", now), "2024-12-01");
+});
+
+test("timeAgo returns an empty string for a missing timestamp", () => {
+  assert.equal(timeAgo(null, Date.now()), "");
+  assert.equal(timeAgo("not a date", Date.now()), "");
+});
+
+test("formatDuration pads minutes and seconds only when an hour is shown", () => {
+  assert.equal(formatDuration(145), "2:25");
+  assert.equal(formatDuration(5), "0:05");
+  assert.equal(formatDuration(3723), "1:02:03");
+  assert.equal(formatDuration(null), "");
+});
+
+test("suggestionList reads completions from an OpenSearch payload", () => {
+  assert.deepEqual(suggestionList(["rus", ["rust", "rust lang"]]), ["rust", "rust lang"]);
+  assert.deepEqual(suggestionList(["rus", ["rust", 42, null]]), ["rust"]);
+  assert.deepEqual(suggestionList({ error: "rate limited" }), []);
+  assert.deepEqual(suggestionList(null), []);
+});
@@ -3,6 +3,9 @@ import {
   safeUrl,
   unwrapPayload,
   getQueryParam,
+  engineStatusLabel,
+  timeAgo,
+  formatDuration,
   SkeletonQueue,
 } from "./search-core.js";
 
@@ -18,6 +21,7 @@ let batchLoading = false; // prevents multiple skeleton triggers
 let currentTab = "general";
 let consecutiveFailures = 0;
 let hasMoreResults = true; // server said there could be another page; only fetch it once the user scrolls for it
+let currentSnapshot = null; // which snapshot of the results the pages so far came from; later pages ask for the same one
 
 const searchSkeletons = new SkeletonQueue();
 const imageSkeletons = new SkeletonQueue();
@@ -26,6 +30,16 @@ function get_query() {
   return getQueryParam(location.search, "q");
 }
 
+// Search params (`lang`, `safe`, `time`) given on the page URL are passed
+// through to `/query` as-is; the server validates them.
+function searchParamsSuffix() {
+  const params = new URLSearchParams(location.search);
+  return ["lang", "safe", "time"]
+    .filter(name => params.get(name))
+    .map(name => `&${name}=${encodeURIComponent(params.get(name))}`)
+    .join("");
+}
+
 function url(u) {
   return safeUrl(u, location.href);
 }
@@ -46,6 +60,22 @@ function setActiveTab() {
 
 addEventListener("DOMContentLoaded", (event) => {
   setActiveTab()
+
+  let query = get_query();
+  document.querySelector(".search-input").value = query;
+
+  // The server already rendered the first page (see `search` in main.rs):
+  // carry on from where it stopped instead of fetching it again. Without
+  // these attributes it couldn't (rate limited, engines down) and the page
+  // is just the shell, so poll for it as before.
+  const rendered = document.querySelector(".results-container").dataset;
+  if (rendered.nextStart !== undefined) {
+    lastFetched = Number(rendered.nextStart);
+    hasMoreResults = rendered.hasMore === "true";
+    currentSnapshot = rendered.snapshot ?? null;
+    return;
+  }
+
   if (currentTab === "images") {
       createImageSkeletons(numImageSkels);
   } else {
@@ -53,15 +83,12 @@ addEventListener("DOMContentLoaded", (event) => {
   }
   window.scrollTo(0, 0);
 
-  let query = get_query();
-  document.querySelector(".search-input").value = query;
-
   startPolling(query);
 });
 
 async function startPolling(query) {
   polling = true;
-  await pollResults(query);
+  await streamResults(query);
 }
 
 function stopPolling() {
@@ -82,13 +109,10 @@ function renderEngineStatus(engines) {
     .map(report => {
       const status = (report.status && report.status.status) || "ok";
       const detail = report.status && report.status.detail;
-      const label =
-        status === "ok" ? "responded"
-        : status === "timed_out" ? "timed out"
-        : "failed";
+      const label = engineStatusLabel(status, detail);
 
       return `
-        <div class="engine-status-row" title="${detail ? escapeHtml(detail) : ""}">
+        <div class="engine-status-row" title="${detail != null ? escapeHtml(detail) : ""}">
           <span class="engine-status-dot ${escapeHtml(status)}"></span>
           <span class="engine-status-name">${escapeHtml(report.engine)}</span>
           <span class="engine-status-detail">${label}</span>
@@ -135,16 +159,109 @@ function onPollSuccess() {
   setErrorBanner(null);
 }
 
+function queryString(query) {
+  const snapshot = currentSnapshot === null ? "" : `&snapshot=${encodeURIComponent(currentSnapshot)}`;
+  return `tab=${currentTab}&query=${encodeURIComponent(query)}${snapshot}&start=${lastFetched}&count=${numSearchSkels}${searchParamsSuffix()}`;
+}
+
+// Loads the next page over SSE (`/query/stream`, see `query_stream` in
+// main.rs): one request per page, with results rendered as each engine
+// answers instead of after the slowest one. Falls back to polling `/query`
+// if `EventSource` is missing, the stream can't be opened (e.g. a 429), or
+// it drops before finishing — left alone, `EventSource` would reconnect by
+// itself and rerun the whole search.
+function streamResults(query) {
+  if (!polling || query === undefined || query === null) return Promise.resolve();
+  if (typeof EventSource === "undefined") return pollResults(query);
+
+  return new Promise(resolve => {
+    const source = new EventSource(`/query/stream?${queryString(query)}`);
+    const reports = new Map();
+    let finished = false;
+
+    function finish() {
+      finished = true;
+      source.close();
+    }
+
+    source.addEventListener("results", e => {
+      renderResults(JSON.parse(e.data));
+    });
+
+    source.addEventListener("engine", e => {
+      const report = JSON.parse(e.data);
+      reports.set(report.engine, report);
+      renderEngineStatus([...reports.values()]);
+    });
+
+    source.addEventListener("done", e => {
+      finish();
+      const { engines, hasMore, snapshot } = JSON.parse(e.data);
+      onPollSuccess();
+      renderEngineStatus(engines);
+      pageLoaded(hasMore, snapshot);
+      resolve();
+    });
+
+    source.addEventListener("failed", e => {
+      finish();
+      onPollFailure(JSON.parse(e.data).error);
+      setTimeout(() => resolve(pollResults(query)), 1000);
+    });
+
+    source.onerror = () => {
+      if (finished) return;
+      finish();
+      resolve(pollResults(query));
+    };
+  });
+}
+
+function renderResults(results) {
+  if (currentTab === "images") {
+    renderImageResults(results);
+  } else if (currentTab === "news") {
+    renderNewsResults(results);
+  } else if (currentTab === "videos") {
+    renderVideoResults(results);
+  } else {
+    renderSearchResults(results);
+  }
+}
+
+// Only fetch this page — the next one is loaded when the user scrolls for
+// it (see the `scroll` listener below), not automatically. Without this, a
+// single search would recursively page through the *entire* result set
+// every `POLL_INTERVAL`, hammering the upstream engines with requests no
+// one asked for.
+//
+// `snapshot` pins later pages to the results this one came from, so they
+// carry on where it left off even if the server refreshes the query's
+// results in the meantime.
+function pageLoaded(hasMore, snapshot) {
+  hasMoreResults = hasMore;
+  currentSnapshot = snapshot ?? currentSnapshot;
+  if (hasMore) {
+    stopPolling();
+  } else {
+    finishSearch();
+  }
+}
+
 async function pollResults(query) {
   if (!polling || query === undefined || query === null) return;
 
   try {
-    const res = await fetch(
-      `/query?tab=${currentTab}&query=${encodeURIComponent(query)}&start=${lastFetched}&count=${numSearchSkels}`
-    );
+    const res = await fetch(`/query?${queryString(query)}`);
 
     if (!res.ok) {
       const message = await describeError(res);
+      if (res.status === 403) {
+        // Denied outright (see `rate_limit.deny`) — retrying won't help.
+        stopPolling();
+        setErrorBanner(`Search is not available from your network (${message}).`);
+        return;
+      }
       onPollFailure(message);
       // A 429 means we're rate limited — back off longer than the normal
       // retry interval instead of hammering the server further.
@@ -163,27 +280,11 @@ async function pollResults(query) {
 
     onPollSuccess();
 
-    const { results, engines, hasMore } = unwrapPayload(data);
+    const { results, engines, hasMore, snapshot } = unwrapPayload(data);
 
     renderEngineStatus(engines);
-
-    if (currentTab === "images") {
-      renderImageResults(results);
-    } else {
-      renderSearchResults(results);
-    }
-
-    // Only fetch this page — the next one is loaded when the user scrolls
-    // for it (see the `scroll` listener below), not automatically. Without
-    // this, a single search would recursively page through the *entire*
-    // result set every `POLL_INTERVAL`, hammering the upstream engines with
-    // requests no one asked for.
-    hasMoreResults = hasMore;
-    if (hasMore) {
-      stopPolling();
-    } else {
-      finishSearch();
-    }
+    renderResults(results);
+    pageLoaded(hasMore, snapshot);
   } catch (err) {
     onPollFailure("network error");
     setTimeout(() => pollResults(query), 1000);
@@ -205,7 +306,11 @@ function finishSearch() {
     const container = document.querySelector(currentTab === "images" ? ".image-gallery" : ".results-container");
     const empty = document.createElement("p");
     empty.className = "empty-state";
-    empty.textContent = currentTab === "images" ? "No images found." : "No results found.";
+    empty.textContent = {
+      images: "No images found.",
+      news: "No news found.",
+      videos: "No videos found.",
+    }[currentTab] || "No results found.";
     container.appendChild(empty);
   }
 }
@@ -283,6 +388,70 @@ function renderSearchResults(results) {
   lastFetched += results.length;
 }
 
+function renderNewsResults(results) {
+  results.forEach((result) => {
+    const skeleton = searchSkeletons.next(makeSearchSkeleton);
+
+    const enginesHtml = result.engines
+      .map(e => `<span class="engine-tag">${escapeHtml(e)}</span>`)
+      .join(" ");
+    const href = url(result.url);
+    const age = result.published ? timeAgo(result.published, Date.now()) : "";
+    const thumb = result.thumbnail
+      ? `<img class="news-thumb" src="${url(result.thumbnail)}" alt="" loading="lazy" decoding="async">`
+      : "";
+
+    skeleton.innerHTML = `
+      ${thumb}
+      <div class="news-meta">
+        <span class="news-source">${escapeHtml(result.source || "")}</span>
+        ${age ? `<time datetime="${escapeHtml(result.published)}">${escapeHtml(age)}</time>` : ""}
+      </div>
+      <h3><a class="name" target="_blank" rel="noopener noreferrer" href="${href}">${escapeHtml(result.title)}</a></h3>
+      <p class="description">${escapeHtml(result.snippet)}</p>
+      <div class="engines">
+        ${enginesHtml}
+        ${result.cached ? '<span class="engine-tag cached">Cached ✓</span>' : ''}
+      </div>
+    `;
+    skeleton.className = "result news-result";
+  });
+
+  lastFetched += results.length;
+}
+
+function renderVideoResults(results) {
+  results.forEach((result) => {
+    const skeleton = searchSkeletons.next(makeSearchSkeleton);
+
+    const enginesHtml = result.engines
+      .map(e => `<span class="engine-tag">${escapeHtml(e)}</span>`)
+      .join(" ");
+    const href = url(result.url);
+    const duration = formatDuration(result.duration);
+    const age = result.published ? timeAgo(result.published, Date.now()) : "";
+    const meta = [result.uploader, age].filter(Boolean).map(escapeHtml).join(" · ");
+
+    skeleton.innerHTML = `
+      <a class="video-thumb" target="_blank" rel="noopener noreferrer" href="${href}">
+        ${result.thumbnail ? `<img src="${url(result.thumbnail)}" alt="" loading="lazy" decoding="async">` : ""}
+        ${duration ? `<span class="video-duration">${escapeHtml(duration)}</span>` : ""}
+      </a>
+      <div class="video-info">
+        <h3><a class="name" target="_blank" rel="noopener noreferrer" href="${href}">${escapeHtml(result.title)}</a></h3>
+        <div class="video-meta">${meta}</div>
+        <div class="engines">
+          ${enginesHtml}
+          ${result.cached ? '<span class="engine-tag cached">Cached ✓</span>' : ''}
+        </div>
+      </div>
+    `;
+    skeleton.className = "result video-result";
+  });
+
+  lastFetched += results.length;
+}
+
 function renderImageResults(results) {
   results.forEach((result) => {
     const skeleton = imageSkeletons.next(makeImageSkeleton);
@@ -341,9 +510,8 @@ function onSearchSubmit() {
   return true;
 }
 
-// `search.html.hbs` wires these up via inline `onsubmit`/`onclick` handlers,
-// which look functions up on `window` — plain top-level `function`
-// declarations aren't implicitly global anymore now that this file is an ES
-// module, so they need to be attached explicitly.
+// `search.html.hbs` wires this up via an inline `onsubmit` handler, which
+// looks functions up on `window` — plain top-level `function` declarations
+// aren't implicitly global anymore now that this file is an ES module, so
+// it needs to be attached explicitly.
 window.onSearchSubmit = onSearchSubmit;
-window.get_query = get_query;
@@ -0,0 +1,41 @@
+import { suggestionList } from "./search-core.js";
+
+// Fills each search box's `<datalist>` with `/suggest` completions as the
+// user types. Debounced so a burst of keystrokes costs one request, and
+// stale responses (for text the user has since changed) are dropped.
+const DEBOUNCE_MS = 150;
+
+function attach(input) {
+  const list = document.getElementById(input.getAttribute("list"));
+  if (!list) return;
+
+  let timer = null;
+  let latest = "";
+
+  input.addEventListener("input", () => {
+    clearTimeout(timer);
+    timer = setTimeout(async () => {
+      const query = input.value.trim();
+      latest = query;
+      if (!query) {
+        list.replaceChildren();
+        return;
+      }
+
+      try {
+        const res = await fetch(`/suggest?q=${encodeURIComponent(query)}`);
+        if (!res.ok || query !== latest) return;
+        const options = suggestionList(await res.json()).map(s => {
+          const option = document.createElement("option");
+          option.value = s;
+          return option;
+        });
+        list.replaceChildren(...options);
+      } catch (e) {
+        // suggestions are a nicety — a failure just means none are shown
+      }
+    }, DEBOUNCE_MS);
+  });
+}
+
+document.querySelectorAll(".search-input[list]").forEach(attach);
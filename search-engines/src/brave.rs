@@ -1,9 +1,13 @@
 use crate::{
-    EngineError, EngineInfo, ImageEngine, RawImage, RawResult, SearchEngine, new_rand_client,
-    parse_images, parse_search,
+    EngineError, EngineInfo, ImageEngine, NewsEngine, RawImage, RawNews, RawResult, RawVideo,
+    SafeSearch, SearchEngine, SearchParams, SuggestEngine, TimeRange, VideoEngine, check_status,
+    client::client_for, parse_images, parse_opensearch_suggestions, parse_search,
 };
 use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
 use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use scraper::{ElementRef, Html, Selector};
+use std::time::Duration;
 
 #[derive(Clone)]
 pub struct Brave;
@@ -12,25 +16,74 @@ impl EngineInfo for Brave {
     fn name(&self) -> &'static str {
         "Brave"
     }
+
+    /// A locale is only applied through its region (see [`push_params`]).
+    fn honors(&self, params: &SearchParams) -> bool {
+        params.language().is_none() || params.region().is_some()
+    }
 }
 
 /// Results per `offset` increment on Brave's web results page — confirmed
 /// empirically, not documented by Brave.
 const BRAVE_RESULTS_PER_PAGE: usize = 20;
 
-fn build_search_url(query: &str, start: usize) -> String {
+fn build_search_url(query: &str, start: usize, params: &SearchParams) -> String {
+    build_paged_url("search", query, start, params)
+}
+
+fn build_news_search_url(query: &str, start: usize, params: &SearchParams) -> String {
+    build_paged_url("news", query, start, params)
+}
+
+fn build_video_search_url(query: &str, start: usize, params: &SearchParams) -> String {
+    build_paged_url("videos", query, start, params)
+}
+
+/// The web, news and video pages all page by the same `offset` and take
+/// the same params; only the path differs.
+fn build_paged_url(path: &str, query: &str, start: usize, params: &SearchParams) -> String {
     let page = start / BRAVE_RESULTS_PER_PAGE;
     let query = utf8_percent_encode(query, NON_ALPHANUMERIC);
-    let mut url = format!("https://search.brave.com/search?q={query}");
+    let mut url = format!("https://search.brave.com/{path}?q={query}");
     if page > 0 {
         url.push_str(&format!("&offset={page}"));
     }
+    push_params(&mut url, params);
+    push_time_range(&mut url, params);
     url
 }
 
-fn build_image_search_url(query: &str) -> String {
+fn build_image_search_url(query: &str, params: &SearchParams) -> String {
     let query = utf8_percent_encode(query, NON_ALPHANUMERIC);
-    format!("https://search.brave.com/images?q={query}")
+    let mut url = format!("https://search.brave.com/images?q={query}");
+    push_params(&mut url, params);
+    url
+}
+
+/// `country`/`safesearch`, shared by the web and image pages. Brave has no
+/// separate language param; the region alone picks the index.
+fn push_params(url: &mut String, params: &SearchParams) {
+    if let Some(region) = params.region() {
+        url.push_str(&format!("&country={region}"));
+    }
+    match params.safe_search {
+        SafeSearch::Off => url.push_str("&safesearch=off"),
+        SafeSearch::Moderate => {}
+        SafeSearch::Strict => url.push_str("&safesearch=strict"),
+    }
+}
+
+/// `tf`, understood by every page but the image one.
+fn push_time_range(url: &mut String, params: &SearchParams) {
+    if let Some(range) = params.time_range {
+        let tf = match range {
+            TimeRange::Day => "pd",
+            TimeRange::Week => "pw",
+            TimeRange::Month => "pm",
+            TimeRange::Year => "py",
+        };
+        url.push_str(&format!("&tf={tf}"));
+    }
 }
 
 // A real Brave results page always has this container, even with 0 hits;
@@ -51,18 +104,20 @@ impl SearchEngine for Brave {
         query: &str,
         start: usize,
         _count: usize,
+        params: &SearchParams,
     ) -> Result<Vec<RawResult>, EngineError> {
-        let resp = new_rand_client()
-            .get(build_search_url(query, start))
+        let resp = client_for(self.name())
+            .get(build_search_url(query, start, params))
             .send()
             .await
-            .map_err(EngineError::ReqwestError)?;
+            .map_err(EngineError::ReqwestError)
+            .and_then(check_status)?;
 
         let html = resp.text().await.map_err(EngineError::ReqwestError)?;
         if !looks_like_search_results(&html) {
-            return Err(EngineError::ParseError(
-                "Brave response didn't look like real results (likely blocked)".into(),
-            ));
+            return Err(EngineError::Blocked {
+                reason: "Brave response didn't look like real results".into(),
+            });
         }
 
         parse_search_response(&html)
@@ -81,6 +136,11 @@ pub fn parse_search_response(html: &str) -> Result<Vec<RawResult>, EngineError>
 
 #[async_trait]
 impl ImageEngine for Brave {
+    /// The image page takes no `tf`.
+    fn honors_images(&self, params: &SearchParams) -> bool {
+        self.honors(params) && params.time_range.is_none()
+    }
+
     /// `start`/`count` are unused: Brave's static image page always returns
     /// the same first batch, since deeper pages load via a signed,
     /// session-bound API this doesn't replicate.
@@ -89,18 +149,20 @@ impl ImageEngine for Brave {
         query: &str,
         _start: usize,
         _count: usize,
+        params: &SearchParams,
     ) -> Result<Vec<RawImage>, EngineError> {
-        let resp = new_rand_client()
-            .get(build_image_search_url(query))
+        let resp = client_for(self.name())
+            .get(build_image_search_url(query, params))
             .send()
             .await
-            .map_err(EngineError::ReqwestError)?;
+            .map_err(EngineError::ReqwestError)
+            .and_then(check_status)?;
 
         let html = resp.text().await.map_err(EngineError::ReqwestError)?;
         if !looks_like_image_results(&html) {
-            return Err(EngineError::ParseError(
-                "Brave image response didn't look like real results (likely blocked)".into(),
-            ));
+            return Err(EngineError::Blocked {
+                reason: "Brave image response didn't look like real results".into(),
+            });
         }
 
         parse_image_response(&html)
@@ -116,6 +178,195 @@ pub fn parse_image_response(html: &str) -> Result<Vec<RawImage>, EngineError> {
     ))
 }
 
+#[async_trait]
+impl NewsEngine for Brave {
+    async fn search_news(
+        &self,
+        query: &str,
+        start: usize,
+        _count: usize,
+        params: &SearchParams,
+    ) -> Result<Vec<RawNews>, EngineError> {
+        let resp = client_for(self.name())
+            .get(build_news_search_url(query, start, params))
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)
+            .and_then(check_status)?;
+
+        let html = resp.text().await.map_err(EngineError::ReqwestError)?;
+        if !looks_like_search_results(&html) {
+            return Err(EngineError::Blocked {
+                reason: "Brave news response didn't look like real results".into(),
+            });
+        }
+
+        parse_news_response(&html, Utc::now())
+    }
+}
+
+/// `now` anchors the relative ages ("3 hours ago") Brave shows instead of
+/// timestamps.
+pub fn parse_news_response(html: &str, now: DateTime<Utc>) -> Result<Vec<RawNews>, EngineError> {
+    let html = Html::parse_document(html);
+    let selector = |s: &str| Selector::parse(s).expect(crate::PARSE_ERROR);
+    let results = selector("#results > .snippet[data-type=\"news\"]");
+    let link = selector("a");
+    let title = selector(".title");
+    let description = selector(".snippet-description");
+    let source = selector(".netloc");
+    let age = selector(".attr");
+    let thumbnail = selector(".thumbnail img");
+
+    Ok(html
+        .select(&results)
+        .map(|el| RawNews {
+            url: first_attr(&el, &link, "href").unwrap_or_default(),
+            title: first_text(&el, &title),
+            snippet: first_text(&el, &description),
+            source: first_text(&el, &source),
+            published: parse_age(&first_text(&el, &age), now),
+            thumbnail: first_attr(&el, &thumbnail, "src"),
+        })
+        .collect())
+}
+
+#[async_trait]
+impl VideoEngine for Brave {
+    async fn search_videos(
+        &self,
+        query: &str,
+        start: usize,
+        _count: usize,
+        params: &SearchParams,
+    ) -> Result<Vec<RawVideo>, EngineError> {
+        let resp = client_for(self.name())
+            .get(build_video_search_url(query, start, params))
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)
+            .and_then(check_status)?;
+
+        let html = resp.text().await.map_err(EngineError::ReqwestError)?;
+        if !looks_like_search_results(&html) {
+            return Err(EngineError::Blocked {
+                reason: "Brave video response didn't look like real results".into(),
+            });
+        }
+
+        parse_video_response(&html, Utc::now())
+    }
+}
+
+/// Reads the `.video-snippet` blocks that the web page's
+/// [`parse_search_response`] flattens into plain results, keeping their
+/// metadata. `now` is as for [`parse_news_response`].
+pub fn parse_video_response(html: &str, now: DateTime<Utc>) -> Result<Vec<RawVideo>, EngineError> {
+    let html = Html::parse_document(html);
+    let selector = |s: &str| Selector::parse(s).expect(crate::PARSE_ERROR);
+    let results = selector("#results > .snippet:has(.video-snippet)");
+    let link = selector("a");
+    let title = selector(".title");
+    let duration = selector(".video-duration");
+    let uploader = selector(".video-creator");
+    let age = selector(".attr");
+    let thumbnail = selector(".video-thumb img");
+
+    let non_empty = |s: String| (!s.is_empty()).then_some(s);
+
+    Ok(html
+        .select(&results)
+        .map(|el| RawVideo {
+            url: first_attr(&el, &link, "href").unwrap_or_default(),
+            title: first_text(&el, &title),
+            duration: parse_duration(&first_text(&el, &duration)),
+            uploader: non_empty(first_text(&el, &uploader)),
+            published: parse_age(&first_text(&el, &age), now),
+            thumbnail: first_attr(&el, &thumbnail, "src"),
+        })
+        .collect())
+}
+
+fn build_suggest_url(query: &str) -> String {
+    let query = utf8_percent_encode(query, NON_ALPHANUMERIC);
+    format!("https://search.brave.com/api/suggest?q={query}")
+}
+
+#[async_trait]
+impl SuggestEngine for Brave {
+    async fn suggestions(&self, query: &str) -> Result<Vec<String>, EngineError> {
+        let json = client_for(self.name())
+            .get(build_suggest_url(query))
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)
+            .and_then(check_status)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_opensearch_suggestions(&json)
+    }
+}
+
+/// Trimmed text of the first match of `selector` under `el`, or `""`.
+fn first_text(el: &ElementRef, selector: &Selector) -> String {
+    el.select(selector)
+        .next()
+        .map(|e| e.text().collect::<String>().trim().to_string())
+        .unwrap_or_default()
+}
+
+fn first_attr(el: &ElementRef, selector: &Selector, attr: &str) -> Option<String> {
+    el.select(selector)
+        .next()
+        .and_then(|e| e.value().attr(attr))
+        .map(str::to_string)
+}
+
+/// Reads a `"4:05"` or `"1:02:03"` video length.
+fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    let mut secs = 0u64;
+    for (i, part) in text.split(':').enumerate() {
+        // hours:minutes:seconds at most
+        if i > 2 || part.is_empty() {
+            return None;
+        }
+        secs = secs * 60 + part.parse::<u64>().ok()?;
+    }
+    Some(Duration::from_secs(secs))
+}
+
+/// Reads Brave's "5 minutes ago"/"2 days ago" ages, falling back to the
+/// absolute dates (`"March 3, 2024"`) it shows for older stories.
+fn parse_age(age: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let age = age.trim();
+    if let Some(relative) = age.strip_suffix(" ago") {
+        let (amount, unit) = relative.split_once(' ')?;
+        let amount: i64 = amount.parse().ok()?;
+        let delta = match unit.trim_end_matches('s') {
+            "second" => TimeDelta::seconds(amount),
+            "minute" => TimeDelta::minutes(amount),
+            "hour" => TimeDelta::hours(amount),
+            "day" => TimeDelta::days(amount),
+            "week" => TimeDelta::weeks(amount),
+            "month" => TimeDelta::days(amount * 30),
+            "year" => TimeDelta::days(amount * 365),
+            _ => return None,
+        };
+        return now.checked_sub_signed(delta);
+    }
+    ["%B %d, %Y", "%b %d, %Y"]
+        .iter()
+        .find_map(|fmt| NaiveDate::parse_from_str(age, fmt).ok())
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -123,11 +374,15 @@ mod test {
     #[test]
     fn build_search_url_omits_offset_on_first_page() {
         assert_eq!(
-            build_search_url("rust async", 0),
+            build_search_url("rust async", 0, &SearchParams::default()),
             "https://search.brave.com/search?q=rust%20async"
         );
         assert_eq!(
-            build_search_url("rust async", BRAVE_RESULTS_PER_PAGE - 1),
+            build_search_url(
+                "rust async",
+                BRAVE_RESULTS_PER_PAGE - 1,
+                &SearchParams::default()
+            ),
             "https://search.brave.com/search?q=rust%20async"
         );
     }
@@ -135,11 +390,19 @@ mod test {
     #[test]
     fn build_search_url_adds_offset_for_later_pages() {
         assert_eq!(
-            build_search_url("rust async", BRAVE_RESULTS_PER_PAGE),
+            build_search_url(
+                "rust async",
+                BRAVE_RESULTS_PER_PAGE,
+                &SearchParams::default()
+            ),
             "https://search.brave.com/search?q=rust%20async&offset=1"
         );
         assert_eq!(
-            build_search_url("rust async", BRAVE_RESULTS_PER_PAGE * 2 + 5),
+            build_search_url(
+                "rust async",
+                BRAVE_RESULTS_PER_PAGE * 2 + 5,
+                &SearchParams::default()
+            ),
             "https://search.brave.com/search?q=rust%20async&offset=2"
         );
     }
@@ -147,18 +410,55 @@ mod test {
     #[test]
     fn build_search_url_encodes_reserved_characters() {
         assert_eq!(
-            build_search_url("AT&T c++ #tag", 0),
+            build_search_url("AT&T c++ #tag", 0, &SearchParams::default()),
             "https://search.brave.com/search?q=AT%26T%20c%2B%2B%20%23tag"
         );
     }
 
+    #[test]
+    fn build_search_url_maps_params_to_country_safesearch_and_tf() {
+        let params = SearchParams {
+            locale: Some("de-AT".into()),
+            safe_search: SafeSearch::Strict,
+            time_range: Some(TimeRange::Week),
+        };
+        assert_eq!(
+            build_search_url("rust", 0, &params),
+            "https://search.brave.com/search?q=rust&country=at&safesearch=strict&tf=pw"
+        );
+        assert_eq!(
+            build_image_search_url("rust", &params),
+            "https://search.brave.com/images?q=rust&country=at&safesearch=strict"
+        );
+    }
+
     /// Non-ASCII queries must survive a full percent-encode round trip —
     /// regression guard for "encoding support" (unicode search terms are a
     /// normal, not edge-case, input for a search engine).
+    #[test]
+    fn a_locale_without_a_region_or_dated_images_are_not_honored() {
+        let params = |locale: &str| SearchParams {
+            locale: Some(locale.into()),
+            ..Default::default()
+        };
+        assert!(Brave.honors(&params("de-AT")));
+        assert!(!Brave.honors(&params("de")));
+        assert!(Brave.honors(&SearchParams::default()));
+
+        let dated = SearchParams {
+            time_range: Some(TimeRange::Week),
+            ..Default::default()
+        };
+        assert!(Brave.honors(&dated));
+        assert!(Brave.honors_news(&dated));
+        assert!(!Brave.honors_images(&dated));
+        assert!(Brave.honors_images(&params("de-AT")));
+    }
+
     #[test]
     fn build_search_url_encodes_non_ascii_query() {
         assert_eq!(
-            build_search_url("café 日本語", 0),
+            build_search_url("café 日本語", 0, &SearchParams::default()),
             "https://search.brave.com/search?q=caf%C3%A9%20%E6%97%A5%E6%9C%AC%E8%AA%9E"
         );
     }
@@ -248,6 +548,159 @@ mod test {
         assert_eq!(images[1].title, "Ferris the Crab");
     }
 
+    #[test]
+    fn build_news_search_url_shares_paging_and_params_with_web_search() {
+        let params = SearchParams {
+            locale: Some("en-GB".into()),
+            safe_search: SafeSearch::Moderate,
+            time_range: Some(TimeRange::Day),
+        };
+        assert_eq!(
+            build_news_search_url("rust", BRAVE_RESULTS_PER_PAGE, &params),
+            "https://search.brave.com/news?q=rust&offset=1&country=gb&tf=pd"
+        );
+    }
+
+    const NEWS_FIXTURE: &str = r#"
+        <div id="results">
+            <div class="snippet" data-type="news">
+                <a href="https://news.example.com/rust-2024">
+                    <div class="title">Rust 2024 edition ships</div>
+                </a>
+                <div class="snippet-url"><span class="netloc">Example News</span></div>
+                <span class="attr">3 hours ago</span>
+                <div class="snippet-description">The new edition is out.</div>
+                <div class="thumbnail"><img src="https://imgs.example.com/rust.jpg"></div>
+            </div>
+            <div class="snippet" data-type="news">
+                <a href="https://blog.example.com/old">
+                    <div class="title">An older story</div>
+                </a>
+                <div class="snippet-url"><span class="netloc">Example Blog</span></div>
+                <span class="attr">March 3, 2024</span>
+                <div class="snippet-description">From a while back.</div>
+            </div>
+        </div>
+    "#;
+
+    #[test]
+    fn parse_news_response_extracts_outlet_age_and_thumbnail() {
+        let now = DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let news = parse_news_response(NEWS_FIXTURE, now).unwrap();
+
+        assert_eq!(news.len(), 2);
+        assert_eq!(news[0].url, "https://news.example.com/rust-2024");
+        assert_eq!(news[0].title, "Rust 2024 edition ships");
+        assert_eq!(news[0].snippet, "The new edition is out.");
+        assert_eq!(news[0].source, "Example News");
+        assert_eq!(news[0].published, Some(now - TimeDelta::hours(3)));
+        assert_eq!(
+            news[0].thumbnail.as_deref(),
+            Some("https://imgs.example.com/rust.jpg")
+        );
+
+        assert_eq!(
+            news[1].published.map(|p| p.date_naive()),
+            NaiveDate::from_ymd_opt(2024, 3, 3)
+        );
+        assert_eq!(news[1].thumbnail, None);
+    }
+
+    #[test]
+    fn build_suggest_url_encodes_the_partial_query() {
+        assert_eq!(
+            build_suggest_url("c++ t"),
+            "https://search.brave.com/api/suggest?q=c%2B%2B%20t"
+        );
+    }
+
+    #[test]
+    fn build_video_search_url_uses_the_videos_page() {
+        assert_eq!(
+            build_video_search_url("rust", 0, &SearchParams::default()),
+            "https://search.brave.com/videos?q=rust"
+        );
+    }
+
+    const VIDEO_FIXTURE: &str = r#"
+        <div id="results">
+            <div class="snippet" data-pos="1">
+                <a href="https://www.youtube.com/watch?v=abc">
+                    <div class="title">Rust in 100 Seconds</div>
+                </a>
+                <div class="video-snippet">
+                    <div class="video-thumb">
+                        <img src="https://imgs.example.com/abc.jpg">
+                        <span class="video-duration">2:25</span>
+                    </div>
+                    <span class="video-creator">Fireship</span>
+                    <span class="attr">2 days ago</span>
+                </div>
+            </div>
+            <div class="snippet" data-pos="2">
+                <a href="https://example.com/article">
+                    <div class="title">Not a video</div>
+                </a>
+                <div class="generic-snippet">Plain web result.</div>
+            </div>
+            <div class="snippet" data-pos="3">
+                <a href="https://videos.example.com/talk">
+                    <div class="title">A conference talk</div>
+                </a>
+                <div class="video-snippet">
+                    <span class="video-duration">1:02:03</span>
+                </div>
+            </div>
+        </div>
+    "#;
+
+    #[test]
+    fn parse_video_response_keeps_video_metadata_and_skips_plain_results() {
+        let now = DateTime::parse_from_rfc3339("2025-01-03T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let videos = parse_video_response(VIDEO_FIXTURE, now).unwrap();
+
+        assert_eq!(videos.len(), 2);
+        assert_eq!(videos[0].url, "https://www.youtube.com/watch?v=abc");
+        assert_eq!(videos[0].title, "Rust in 100 Seconds");
+        assert_eq!(videos[0].duration, Some(Duration::from_secs(145)));
+        assert_eq!(videos[0].uploader.as_deref(), Some("Fireship"));
+        assert_eq!(videos[0].published, Some(now - TimeDelta::days(2)));
+        assert_eq!(
+            videos[0].thumbnail.as_deref(),
+            Some("https://imgs.example.com/abc.jpg")
+        );
+
+        assert_eq!(videos[1].duration, Some(Duration::from_secs(3723)));
+        assert_eq!(videos[1].uploader, None);
+        assert_eq!(videos[1].published, None);
+        assert_eq!(videos[1].thumbnail, None);
+    }
+
+    #[test]
+    fn parse_duration_rejects_anything_but_clock_style_lengths() {
+        assert_eq!(parse_duration("0:59"), Some(Duration::from_secs(59)));
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("LIVE"), None);
+        assert_eq!(parse_duration("1::2"), None);
+        assert_eq!(parse_duration("1:2:3:4"), None);
+    }
+
+    #[test]
+    fn parse_age_gives_up_on_unrecognized_text() {
+        let now = Utc::now();
+        assert_eq!(parse_age("", now), None);
+        assert_eq!(parse_age("a while ago", now), None);
+        assert_eq!(parse_age("3 fortnights ago", now), None);
+        assert_eq!(
+            parse_age("1 minute ago", now),
+            Some(now - TimeDelta::minutes(1))
+        );
+    }
+
     use crate::fixtures::cached_html;
 
     #[ignore]
@@ -255,7 +708,7 @@ mod test {
     async fn test_brave_search_live() {
         let html = cached_html(
             "brave/search_p0.html",
-            &build_search_url("rust async", 0),
+            &build_search_url("rust async", 0, &SearchParams::default()),
             looks_like_search_results,
         )
         .await;
@@ -268,13 +721,17 @@ mod test {
     async fn test_brave_search_pagination_live() {
         let page1_html = cached_html(
             "brave/search_p0.html",
-            &build_search_url("rust async", 0),
+            &build_search_url("rust async", 0, &SearchParams::default()),
             looks_like_search_results,
         )
         .await;
         let page2_html = cached_html(
             "brave/search_p1.html",
-            &build_search_url("rust async", BRAVE_RESULTS_PER_PAGE),
+            &build_search_url(
+                "rust async",
+                BRAVE_RESULTS_PER_PAGE,
+                &SearchParams::default(),
+            ),
             looks_like_search_results,
         )
         .await;
@@ -290,12 +747,38 @@ mod test {
         );
     }
 
+    #[ignore]
+    #[tokio::test]
+    async fn test_brave_news_live() {
+        let html = cached_html(
+            "brave/news_p0.html",
+            &build_news_search_url("rust", 0, &SearchParams::default()),
+            looks_like_search_results,
+        )
+        .await;
+        let news = parse_news_response(&html, Utc::now()).unwrap();
+        assert!(!news.is_empty());
+    }
+
+    #[ignore]
+    #[tokio::test]
+    async fn test_brave_videos_live() {
+        let html = cached_html(
+            "brave/videos_p0.html",
+            &build_video_search_url("rust", 0, &SearchParams::default()),
+            looks_like_search_results,
+        )
+        .await;
+        let videos = parse_video_response(&html, Utc::now()).unwrap();
+        assert!(!videos.is_empty());
+    }
+
     #[ignore]
     #[tokio::test]
     async fn test_brave_images_live() {
         let html = cached_html(
             "brave/images_p0.html",
-            &build_image_search_url("rust async"),
+            &build_image_search_url("rust async", &SearchParams::default()),
             looks_like_image_results,
         )
         .await;
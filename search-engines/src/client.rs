@@ -0,0 +1,358 @@
+use rand::seq::IndexedRandom;
+use reqwest::{Client, Proxy, Url};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{
+        Arc, LazyLock, RwLock,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+static USER_AGENTS: &[&str] = &[
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+    "Mozilla/5.0 (X11; Linux x86_64; rv:118.0) Gecko/20100101 Firefox/118.0",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 13_4) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/118.0.5993.72 Safari/537.36",
+];
+
+/// Which proxies outbound engine requests go through. Engines with their own
+/// pool (see [`ClientConfig::engine_proxy`]) use only that; every other
+/// engine uses the global pool, or connects directly if that's empty too.
+/// A pool with more than one proxy is rotated round-robin, one request at a
+/// time.
+///
+/// Proxy URLs may be `http://`, `https://`, `socks5://` or `socks5h://`.
+/// For Tor use `socks5h://127.0.0.1:9050` — the `h` makes the proxy resolve
+/// hostnames, so DNS lookups don't leak around it.
+///
+/// Nothing takes effect until passed to [`configure_clients`].
+#[derive(Debug, Clone, Default)]
+pub struct ClientConfig {
+    proxies: Vec<String>,
+    engine_proxies: HashMap<String, Vec<String>>,
+}
+
+impl ClientConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `url` to the global pool.
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxies.push(url.into());
+        self
+    }
+
+    /// Adds `url` to `engine`'s own pool. `engine` is matched against
+    /// [`EngineInfo::name`](crate::EngineInfo::name) ignoring case and
+    /// punctuation, so `"duckduckgo"` and `"DUCK_DUCK_GO"` both mean
+    /// DuckDuckGo.
+    pub fn engine_proxy(mut self, engine: &str, url: impl Into<String>) -> Self {
+        self.engine_proxies
+            .entry(engine_key(engine))
+            .or_default()
+            .push(url.into());
+        self
+    }
+}
+
+#[derive(Debug)]
+pub enum ProxyError {
+    InvalidUrl { url: String, reason: String },
+    Client(reqwest::Error),
+}
+
+impl fmt::Display for ProxyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProxyError::InvalidUrl { url, reason } => write!(f, "invalid proxy {url:?}: {reason}"),
+            ProxyError::Client(e) => write!(f, "couldn't build proxied client: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ProxyError {}
+
+fn engine_key(engine: &str) -> String {
+    engine
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn parse_proxy(url: &str) -> Result<Proxy, ProxyError> {
+    let invalid = |reason: String| ProxyError::InvalidUrl {
+        url: url.to_string(),
+        reason,
+    };
+    let parsed = Url::parse(url).map_err(|e| invalid(e.to_string()))?;
+    if !matches!(parsed.scheme(), "http" | "https" | "socks5" | "socks5h") {
+        return Err(invalid(format!(
+            "unsupported scheme {:?} (expected http, https, socks5 or socks5h)",
+            parsed.scheme()
+        )));
+    }
+    if parsed.host_str().is_none() {
+        return Err(invalid("missing host".into()));
+    }
+    Proxy::all(url).map_err(|e| invalid(e.to_string()))
+}
+
+/// One set of per-user-agent clients for each proxy in a pool (or a single
+/// set with no explicit proxy for a direct pool).
+struct ProxyPool {
+    routes: Vec<Vec<Client>>,
+    next: AtomicUsize,
+}
+
+impl ProxyPool {
+    fn build(proxies: &[String]) -> Result<Self, ProxyError> {
+        let routes = if proxies.is_empty() {
+            vec![build_clients(None)?]
+        } else {
+            proxies
+                .iter()
+                .map(|url| build_clients(Some(parse_proxy(url)?)))
+                .collect::<Result<_, _>>()?
+        };
+        Ok(Self {
+            routes,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// Next proxy in rotation, with a random user agent on top (rotating user
+    /// agent across requests to avoid looking like a single scripted client
+    /// to the upstream engine).
+    fn pick(&self) -> Client {
+        let route = self.next.fetch_add(1, Ordering::Relaxed) % self.routes.len();
+        self.routes[route]
+            .choose(&mut rand::rng())
+            .expect("USER_AGENTS is non-empty")
+            .clone()
+    }
+}
+
+/// One [`Client`] per user agent, built once and reused so requests actually
+/// benefit from connection pooling/keep-alive instead of paying a fresh
+/// TCP+TLS handshake on every single search. Cloning a `Client` is cheap —
+/// it's just an `Arc` around the shared connection pool.
+fn build_clients(proxy: Option<Proxy>) -> Result<Vec<Client>, ProxyError> {
+    USER_AGENTS
+        .iter()
+        .map(|ua| {
+            let mut builder = Client::builder().user_agent(*ua);
+            if let Some(proxy) = &proxy {
+                builder = builder.proxy(proxy.clone());
+            }
+            builder.build().map_err(ProxyError::Client)
+        })
+        .collect()
+}
+
+/// Every pool built from one [`ClientConfig`].
+struct ClientPools {
+    global: ProxyPool,
+    engines: HashMap<String, ProxyPool>,
+}
+
+impl ClientPools {
+    fn build(config: &ClientConfig) -> Result<Self, ProxyError> {
+        Ok(Self {
+            global: ProxyPool::build(&config.proxies)?,
+            engines: config
+                .engine_proxies
+                .iter()
+                .map(|(engine, proxies)| Ok((engine.clone(), ProxyPool::build(proxies)?)))
+                .collect::<Result<_, ProxyError>>()?,
+        })
+    }
+
+    fn client_for(&self, engine: &str) -> Client {
+        self.engines
+            .get(&engine_key(engine))
+            .unwrap_or(&self.global)
+            .pick()
+    }
+}
+
+static POOLS: LazyLock<RwLock<Arc<ClientPools>>> = LazyLock::new(|| {
+    RwLock::new(Arc::new(
+        ClientPools::build(&ClientConfig::default()).expect("failed to build reqwest client"),
+    ))
+});
+
+/// Replaces the process-wide client configuration. Every proxy is validated
+/// and every client built before anything is swapped in, so an error leaves
+/// the previous configuration in place. Requests already in flight finish on
+/// the clients they started with.
+pub fn configure_clients(config: ClientConfig) -> Result<(), ProxyError> {
+    let pools = Arc::new(ClientPools::build(&config)?);
+    *POOLS.write().unwrap() = pools;
+    Ok(())
+}
+
+/// The client `engine` (an [`EngineInfo::name`](crate::EngineInfo::name))
+/// should send its next request with.
+pub(crate) fn client_for(engine: &str) -> Client {
+    let pools = POOLS.read().unwrap().clone();
+    pools.client_for(engine)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::{TcpListener, TcpStream},
+        thread::{self, JoinHandle},
+    };
+
+    /// Reads one HTTP request off `stream`, answers it with `body`, and
+    /// returns the request line.
+    fn answer_http(stream: TcpStream, body: &str) -> String {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut line = String::new();
+        while reader.read_line(&mut line).unwrap() > 2 {
+            line.clear();
+        }
+        let mut stream = stream;
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+        request_line.trim_end().to_string()
+    }
+
+    /// A stand-in HTTP proxy that serves `connections` requests itself,
+    /// answering each with `body`, and reports the request lines it saw.
+    fn http_proxy(body: &'static str, connections: usize) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            listener
+                .incoming()
+                .take(connections)
+                .map(|stream| answer_http(stream.unwrap(), body))
+                .collect()
+        });
+        (url, handle)
+    }
+
+    /// A stand-in SOCKS5 proxy for one connection: does the no-auth
+    /// handshake, then answers the tunnelled HTTP request itself. Reports the
+    /// destination host as the client sent it.
+    fn socks5_proxy(body: &'static str) -> (String, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("socks5h://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+
+            let mut greeting = [0u8; 2];
+            stream.read_exact(&mut greeting).unwrap();
+            let mut methods = vec![0u8; greeting[1] as usize];
+            stream.read_exact(&mut methods).unwrap();
+            stream.write_all(&[5, 0]).unwrap();
+
+            let mut request = [0u8; 4];
+            stream.read_exact(&mut request).unwrap();
+            assert_eq!(request[3], 3, "socks5h should send the hostname, not an IP");
+            let mut len = [0u8; 1];
+            stream.read_exact(&mut len).unwrap();
+            let mut host = vec![0u8; len[0] as usize];
+            stream.read_exact(&mut host).unwrap();
+            let mut port = [0u8; 2];
+            stream.read_exact(&mut port).unwrap();
+            stream.write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0]).unwrap();
+
+            answer_http(stream, body);
+            String::from_utf8(host).unwrap()
+        });
+        (url, handle)
+    }
+
+    async fn fetch(pools: &ClientPools, engine: &str, url: &str) -> String {
+        pools
+            .client_for(engine)
+            .get(url)
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn engine_pool_routes_through_its_http_proxy() {
+        let (proxy, seen) = http_proxy("via proxy", 1);
+        let pools =
+            ClientPools::build(&ClientConfig::new().engine_proxy("DuckDuckGo", proxy)).unwrap();
+
+        let body = fetch(&pools, "DuckDuckGo", "http://engine.invalid/search?q=rust").await;
+
+        assert_eq!(body, "via proxy");
+        assert_eq!(
+            seen.join().unwrap(),
+            vec!["GET http://engine.invalid/search?q=rust HTTP/1.1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn global_pool_rotates_round_robin() {
+        let (first, first_seen) = http_proxy("first", 2);
+        let (second, second_seen) = http_proxy("second", 2);
+        let pools = ClientPools::build(&ClientConfig::new().proxy(first).proxy(second)).unwrap();
+
+        let mut bodies = Vec::new();
+        for _ in 0..4 {
+            bodies.push(fetch(&pools, "Brave", "http://engine.invalid/").await);
+        }
+
+        assert_eq!(bodies, ["first", "second", "first", "second"]);
+        assert_eq!(first_seen.join().unwrap().len(), 2);
+        assert_eq!(second_seen.join().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn socks5h_proxy_resolves_the_hostname_remotely() {
+        let (proxy, host) = socks5_proxy("via socks");
+        let pools = ClientPools::build(&ClientConfig::new().proxy(proxy)).unwrap();
+
+        let body = fetch(&pools, "Mojeek", "http://engine.invalid/").await;
+
+        assert_eq!(body, "via socks");
+        assert_eq!(host.join().unwrap(), "engine.invalid");
+    }
+
+    #[test]
+    fn engine_pools_are_matched_ignoring_case_and_punctuation() {
+        let config = ClientConfig::new().engine_proxy("DUCK_DUCK_GO", "http://127.0.0.1:9");
+        let pools = ClientPools::build(&config).unwrap();
+
+        assert!(pools.engines.contains_key(&engine_key("DuckDuckGo")));
+        assert!(!pools.engines.contains_key(&engine_key("Brave")));
+    }
+
+    #[test]
+    fn rejects_unsupported_or_malformed_proxy_urls() {
+        for url in ["ftp://127.0.0.1:21", "not a url", "socks4://127.0.0.1:1080"] {
+            let err = ClientPools::build(&ClientConfig::new().proxy(url))
+                .err()
+                .unwrap_or_else(|| panic!("{url} should be rejected"));
+            assert!(matches!(err, ProxyError::InvalidUrl { .. }), "{url}: {err}");
+        }
+    }
+
+    #[test]
+    fn configure_clients_keeps_the_previous_config_on_error() {
+        assert!(configure_clients(ClientConfig::new().proxy("ftp://nope")).is_err());
+        // Still the direct default pool: one route, no proxy.
+        assert_eq!(POOLS.read().unwrap().global.routes.len(), 1);
+    }
+}
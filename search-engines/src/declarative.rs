@@ -0,0 +1,594 @@
+use crate::{
+    EngineError, EngineInfo, ImageEngine, RawImage, RawResult, SafeSearch, SearchEngine,
+    SearchParams, TimeRange, check_status, client::client_for, parse_images, parse_search,
+    unwrap_redirect,
+};
+use async_trait::async_trait;
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use reqwest::Url;
+use scraper::Selector;
+use serde::Deserialize;
+use std::{fmt, path::Path, sync::Arc};
+
+/// An engine described entirely by data — URL template, page size, CSS
+/// selectors and block-page marker — instead of a hand-written module, so a
+/// new engine (or a fix for one whose markup drifted) is a config edit
+/// rather than a new binary. A definition looks like:
+///
+/// ```toml
+/// name = "Example"
+///
+/// [search]
+/// url = "https://search.example.com/?q={query}&page={page}"
+/// page_size = 10
+/// results = ".result"
+/// title = ".result-title"
+/// href = "a.result-link"
+/// description = ".result-snippet"
+/// results_marker = 'id="results"'
+/// redirect_param = "uddg"
+///
+/// [images]
+/// url = "https://search.example.com/images?q={query}&offset={start}"
+/// page_size = 50
+/// results = ".image"
+/// title = ".caption"
+/// image = "img"
+/// results_marker = "image-grid"
+///
+/// [params]
+/// language = "lang"
+/// safe_search = { param = "safe", off = "0", strict = "2" }
+/// time_range = { param = "t", day = "d", week = "w", month = "m", year = "y" }
+/// ```
+///
+/// Either of `[search]`/`[images]` may be left out; a definition needs at
+/// least one. `[params]` is optional, see [`ParamsDefinition`].
+#[derive(Clone)]
+pub struct DeclarativeEngine {
+    definition: Arc<EngineDefinition>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EngineDefinition {
+    pub name: String,
+    pub search: Option<SearchDefinition>,
+    pub images: Option<ImagesDefinition>,
+    #[serde(default)]
+    pub params: ParamsDefinition,
+}
+
+/// The query params [`SearchParams`] map to, appended to both sections'
+/// URLs. A param left out can't be applied, so the engine sits out any
+/// search that sets it; without `safe_search` the engine is taken to never
+/// filter.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParamsDefinition {
+    /// Takes the language subtag, e.g. `en`.
+    pub language: Option<String>,
+    /// Takes the region subtag, e.g. `us`. Only sent alongside `language`.
+    pub region: Option<String>,
+    pub safe_search: Option<SafeSearchDefinition>,
+    pub time_range: Option<TimeRangeDefinition>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SafeSearchDefinition {
+    pub param: String,
+    pub off: String,
+    pub strict: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TimeRangeDefinition {
+    pub param: String,
+    pub day: String,
+    pub week: String,
+    pub month: String,
+    pub year: String,
+}
+
+impl ParamsDefinition {
+    fn honors(&self, params: &SearchParams) -> bool {
+        (params.language().is_none() || self.language.is_some())
+            && (params.safe_search != SafeSearch::Strict || self.safe_search.is_some())
+            && (params.time_range.is_none() || self.time_range.is_some())
+    }
+
+    /// Appends whatever of `params` this definition maps to `url`.
+    fn apply(&self, url: &str, params: &SearchParams) -> String {
+        let Ok(mut url) = Url::parse(url) else {
+            return url.to_string();
+        };
+        {
+            let mut pairs = url.query_pairs_mut();
+            if let (Some(name), Some(lang)) = (&self.language, params.language()) {
+                pairs.append_pair(name, &lang);
+                if let (Some(name), Some(region)) = (&self.region, params.region()) {
+                    pairs.append_pair(name, &region);
+                }
+            }
+            if let Some(safe) = &self.safe_search {
+                let value = match params.safe_search {
+                    SafeSearch::Off => Some(&safe.off),
+                    SafeSearch::Moderate => None,
+                    SafeSearch::Strict => Some(&safe.strict),
+                };
+                if let Some(value) = value {
+                    pairs.append_pair(&safe.param, value);
+                }
+            }
+            if let (Some(time), Some(range)) = (&self.time_range, params.time_range) {
+                let value = match range {
+                    TimeRange::Day => &time.day,
+                    TimeRange::Week => &time.week,
+                    TimeRange::Month => &time.month,
+                    TimeRange::Year => &time.year,
+                };
+                pairs.append_pair(&time.param, value);
+            }
+        }
+        url.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SearchDefinition {
+    /// Request URL. `{query}` is replaced with the percent-encoded query,
+    /// `{start}` with the raw result offset and `{page}` with the page
+    /// number (`start / page_size + page_base`).
+    pub url: String,
+    pub page_size: usize,
+    /// What `{page}` is for the first page. Defaults to 1.
+    #[serde(default = "default_page_base")]
+    pub page_base: usize,
+    pub results: String,
+    pub title: String,
+    pub href: String,
+    pub description: String,
+    /// A string every real results page contains, even with zero hits, but
+    /// a block/captcha page doesn't (see `looks_like_search_results` in the
+    /// hand-written adapters). Without one, a block page can't be told
+    /// apart from genuine exhaustion.
+    pub results_marker: Option<String>,
+    /// Query param of a click-tracking redirect wrapping each result href
+    /// (e.g. DuckDuckGo's `uddg`), unwrapped to the real target URL.
+    pub redirect_param: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ImagesDefinition {
+    /// Same placeholders as [`SearchDefinition::url`].
+    pub url: String,
+    pub page_size: usize,
+    #[serde(default = "default_page_base")]
+    pub page_base: usize,
+    pub results: String,
+    pub title: String,
+    /// Selector for the `<img>` whose `src` is the image URL.
+    pub image: String,
+    pub results_marker: Option<String>,
+}
+
+fn default_page_base() -> usize {
+    1
+}
+
+#[derive(Debug)]
+pub enum DefinitionError {
+    Toml(toml::de::Error),
+    Json(serde_json::Error),
+    Io(std::io::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for DefinitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DefinitionError::Toml(e) => write!(f, "invalid TOML engine definition: {e}"),
+            DefinitionError::Json(e) => write!(f, "invalid JSON engine definition: {e}"),
+            DefinitionError::Io(e) => write!(f, "couldn't read engine definition: {e}"),
+            DefinitionError::Invalid(e) => write!(f, "invalid engine definition: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for DefinitionError {}
+
+impl DeclarativeEngine {
+    pub fn from_toml(src: &str) -> Result<Self, DefinitionError> {
+        Self::from_definition(toml::from_str(src).map_err(DefinitionError::Toml)?)
+    }
+
+    pub fn from_json(src: &str) -> Result<Self, DefinitionError> {
+        Self::from_definition(serde_json::from_str(src).map_err(DefinitionError::Json)?)
+    }
+
+    /// Loads a `.toml` or `.json` definition, picked by file extension.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, DefinitionError> {
+        let path = path.as_ref();
+        let src = std::fs::read_to_string(path).map_err(DefinitionError::Io)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Self::from_json(&src),
+            Some("toml") => Self::from_toml(&src),
+            _ => Err(DefinitionError::Invalid(format!(
+                "{} isn't a .toml or .json file",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Validates up front everything that would otherwise only fail (or
+    /// panic, for selectors) on the first live request.
+    pub fn from_definition(definition: EngineDefinition) -> Result<Self, DefinitionError> {
+        let invalid = |msg: String| DefinitionError::Invalid(format!("{}: {msg}", definition.name));
+
+        if definition.name.trim().is_empty() {
+            return Err(DefinitionError::Invalid("engine name is empty".into()));
+        }
+        if definition.search.is_none() && definition.images.is_none() {
+            return Err(invalid("needs a [search] or [images] section".into()));
+        }
+
+        let mut selectors = Vec::new();
+        let mut templates = Vec::new();
+        if let Some(s) = &definition.search {
+            selectors.extend([&s.results, &s.title, &s.href, &s.description]);
+            templates.push((&s.url, s.page_size));
+        }
+        if let Some(i) = &definition.images {
+            selectors.extend([&i.results, &i.title, &i.image]);
+            templates.push((&i.url, i.page_size));
+        }
+        for selector in selectors {
+            Selector::parse(selector)
+                .map_err(|e| invalid(format!("bad selector {selector:?}: {e}")))?;
+        }
+        for (url, page_size) in templates {
+            if page_size == 0 {
+                return Err(invalid("page_size must be at least 1".into()));
+            }
+            if !url.contains("{query}") {
+                return Err(invalid(format!("url {url:?} has no {{query}} placeholder")));
+            }
+            Url::parse(&fill_template(url, "q", 0, 1, 1))
+                .map_err(|e| invalid(format!("url {url:?} isn't a valid URL: {e}")))?;
+        }
+
+        Ok(Self {
+            definition: Arc::new(definition),
+        })
+    }
+
+    pub fn definition(&self) -> &EngineDefinition {
+        &self.definition
+    }
+
+    pub fn supports_search(&self) -> bool {
+        self.definition.search.is_some()
+    }
+
+    pub fn supports_images(&self) -> bool {
+        self.definition.images.is_some()
+    }
+
+    async fn fetch(&self, url: &str, marker: Option<&str>) -> Result<String, EngineError> {
+        let resp = client_for(self.name())
+            .get(url)
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)
+            .and_then(check_status)?;
+
+        let html = resp.text().await.map_err(EngineError::ReqwestError)?;
+        if let Some(marker) = marker
+            && !html.contains(marker)
+        {
+            return Err(EngineError::Blocked {
+                reason: format!("{} response didn't look like real results", self.name()),
+            });
+        }
+        Ok(html)
+    }
+}
+
+impl EngineInfo for DeclarativeEngine {
+    fn name(&self) -> &str {
+        &self.definition.name
+    }
+
+    fn honors(&self, params: &SearchParams) -> bool {
+        self.definition.params.honors(params)
+    }
+}
+
+fn fill_template(
+    template: &str,
+    query: &str,
+    start: usize,
+    page_size: usize,
+    page_base: usize,
+) -> String {
+    let page = start / page_size + page_base;
+    template
+        .replace(
+            "{query}",
+            &utf8_percent_encode(query, NON_ALPHANUMERIC).to_string(),
+        )
+        .replace("{start}", &start.to_string())
+        .replace("{page}", &page.to_string())
+}
+
+/// Resolves a possibly-relative href against the page it came from, then
+/// unwraps `redirect_param` if the definition has one.
+fn resolve_href(page_url: &str, href: &str, redirect_param: Option<&str>) -> String {
+    let absolute = Url::parse(page_url)
+        .and_then(|base| base.join(href))
+        .map(|u| u.to_string())
+        .unwrap_or_else(|_| href.to_string());
+    match redirect_param {
+        Some(param) => unwrap_redirect(&absolute, page_url, param),
+        None => absolute,
+    }
+}
+
+pub fn parse_search_response(def: &SearchDefinition, page_url: &str, html: &str) -> Vec<RawResult> {
+    parse_search(html, &def.results, &def.title, &def.href, &def.description)
+        .into_iter()
+        .map(|mut r| {
+            r.url = resolve_href(page_url, &r.url, def.redirect_param.as_deref());
+            r.title = r.title.trim().to_string();
+            r.description = r.description.trim().to_string();
+            r
+        })
+        .collect()
+}
+
+pub fn parse_image_response(def: &ImagesDefinition, page_url: &str, html: &str) -> Vec<RawImage> {
+    parse_images(html, &def.results, &def.title, &def.image)
+        .into_iter()
+        .map(|mut i| {
+            i.url = resolve_href(page_url, &i.url, None);
+            i.title = i.title.trim().to_string();
+            i
+        })
+        .collect()
+}
+
+#[async_trait]
+impl SearchEngine for DeclarativeEngine {
+    async fn search_results(
+        &self,
+        query: &str,
+        start: usize,
+        _count: usize,
+        params: &SearchParams,
+    ) -> Result<Vec<RawResult>, EngineError> {
+        let Some(def) = &self.definition.search else {
+            return Err(EngineError::ParseError(format!(
+                "{} has no [search] definition",
+                self.name()
+            )));
+        };
+
+        let url = fill_template(&def.url, query, start, def.page_size, def.page_base);
+        let url = self.definition.params.apply(&url, params);
+        let html = self.fetch(&url, def.results_marker.as_deref()).await?;
+        Ok(parse_search_response(def, &url, &html))
+    }
+}
+
+#[async_trait]
+impl ImageEngine for DeclarativeEngine {
+    async fn search_images(
+        &self,
+        query: &str,
+        start: usize,
+        _count: usize,
+        params: &SearchParams,
+    ) -> Result<Vec<RawImage>, EngineError> {
+        let Some(def) = &self.definition.images else {
+            return Err(EngineError::ParseError(format!(
+                "{} has no [images] definition",
+                self.name()
+            )));
+        };
+
+        let url = fill_template(&def.url, query, start, def.page_size, def.page_base);
+        let url = self.definition.params.apply(&url, params);
+        let html = self.fetch(&url, def.results_marker.as_deref()).await?;
+        Ok(parse_image_response(def, &url, &html))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TOML_DEFINITION: &str = r#"
+        name = "Example"
+
+        [search]
+        url = "https://search.example.com/?q={query}&page={page}"
+        page_size = 10
+        results = ".result"
+        title = ".title"
+        href = "a"
+        description = ".desc"
+        results_marker = 'id="results"'
+        redirect_param = "u"
+
+        [images]
+        url = "https://search.example.com/images?q={query}&offset={start}"
+        page_size = 50
+        page_base = 0
+        results = ".image"
+        title = ".caption"
+        image = "img"
+    "#;
+
+    #[test]
+    fn from_toml_loads_both_sections() {
+        let engine = DeclarativeEngine::from_toml(TOML_DEFINITION).unwrap();
+
+        assert_eq!(engine.name(), "Example");
+        assert!(engine.supports_search());
+        assert!(engine.supports_images());
+        assert_eq!(engine.definition().search.as_ref().unwrap().page_base, 1);
+    }
+
+    #[test]
+    fn from_json_loads_a_search_only_definition() {
+        let engine = DeclarativeEngine::from_json(
+            r#"{
+                "name": "JsonEngine",
+                "search": {
+                    "url": "https://j.example.com/?q={query}&s={start}",
+                    "page_size": 20,
+                    "results": "li",
+                    "title": "h3",
+                    "href": "a",
+                    "description": "p"
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(engine.name(), "JsonEngine");
+        assert!(engine.supports_search());
+        assert!(!engine.supports_images());
+    }
+
+    #[test]
+    fn rejects_an_unparseable_selector_at_load_time() {
+        let err = DeclarativeEngine::from_toml(
+            &TOML_DEFINITION.replace(r#"title = ".title""#, r#"title = "..[[""#),
+        )
+        .err()
+        .unwrap();
+        assert!(err.to_string().contains("bad selector"));
+    }
+
+    #[test]
+    fn rejects_a_url_without_a_query_placeholder() {
+        let err = DeclarativeEngine::from_toml(
+            &TOML_DEFINITION.replace("?q={query}&page={page}", "?page={page}"),
+        )
+        .err()
+        .unwrap();
+        assert!(err.to_string().contains("{query}"));
+    }
+
+    #[test]
+    fn rejects_a_definition_with_no_sections() {
+        assert!(DeclarativeEngine::from_toml(r#"name = "Empty""#).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        let err = DeclarativeEngine::from_toml(
+            &TOML_DEFINITION.replace("page_size = 10", "page_size = 10\nresult = \".typo\""),
+        )
+        .err()
+        .unwrap();
+        assert!(matches!(err, DefinitionError::Toml(_)));
+    }
+
+    #[test]
+    fn fill_template_substitutes_query_start_and_page() {
+        assert_eq!(
+            fill_template(
+                "https://x.example/?q={query}&s={start}&p={page}",
+                "café c++",
+                25,
+                10,
+                1
+            ),
+            "https://x.example/?q=caf%C3%A9%20c%2B%2B&s=25&p=3"
+        );
+        assert_eq!(
+            fill_template("https://x.example/?q={query}&p={page}", "rust", 0, 10, 0),
+            "https://x.example/?q=rust&p=0"
+        );
+    }
+
+    #[test]
+    fn params_are_appended_only_where_the_definition_maps_them() {
+        let engine = DeclarativeEngine::from_toml(&format!(
+            "{TOML_DEFINITION}\n[params]\nlanguage = \"hl\"\nregion = \"gl\"\n\
+             safe_search = {{ param = \"safe\", off = \"0\", strict = \"2\" }}"
+        ))
+        .unwrap();
+        let params = SearchParams {
+            locale: Some("pt-BR".into()),
+            safe_search: SafeSearch::Strict,
+            time_range: None,
+        };
+        assert!(engine.honors(&params));
+        assert_eq!(
+            engine
+                .definition()
+                .params
+                .apply("https://search.example.com/?q=rust&page=1", &params),
+            "https://search.example.com/?q=rust&page=1&hl=pt&gl=br&safe=2"
+        );
+
+        let dated = SearchParams {
+            time_range: Some(TimeRange::Day),
+            ..Default::default()
+        };
+        assert!(!engine.honors(&dated));
+        let plain = DeclarativeEngine::from_toml(TOML_DEFINITION).unwrap();
+        assert!(!plain.honors(&params));
+        assert!(plain.honors(&SearchParams::default()));
+    }
+
+    #[test]
+    fn parse_search_response_resolves_relative_links_and_unwraps_redirects() {
+        let engine = DeclarativeEngine::from_toml(TOML_DEFINITION).unwrap();
+        let def = engine.definition().search.as_ref().unwrap();
+        let html = r#"
+            <div id="results">
+                <div class="result">
+                    <a href="/out?u=https%3A%2F%2Fexample.com%2Frust">
+                        <span class="title"> Rust </span>
+                    </a>
+                    <p class="desc">A language.</p>
+                </div>
+                <div class="result">
+                    <a href="https://example.org/direct"><span class="title">Direct</span></a>
+                    <p class="desc">No redirect.</p>
+                </div>
+            </div>
+        "#;
+
+        let results = parse_search_response(def, "https://search.example.com/?q=rust", html);
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].url, "https://example.com/rust");
+        assert_eq!(results[0].title, "Rust");
+        assert_eq!(results[0].description, "A language.");
+        assert_eq!(results[1].url, "https://example.org/direct");
+    }
+
+    #[test]
+    fn parse_image_response_resolves_relative_image_urls() {
+        let engine = DeclarativeEngine::from_toml(TOML_DEFINITION).unwrap();
+        let def = engine.definition().images.as_ref().unwrap();
+        let html = r#"
+            <div class="image"><img src="/img/ferris.png"><span class="caption">Ferris</span></div>
+        "#;
+
+        let images = parse_image_response(def, "https://search.example.com/images?q=rust", html);
+
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].url, "https://search.example.com/img/ferris.png");
+        assert_eq!(images[0].title, "Ferris");
+    }
+}
@@ -1,8 +1,14 @@
 use async_trait::async_trait;
-use percent_encoding::{NON_ALPHANUMERIC, percent_decode, utf8_percent_encode};
-use reqwest::Url;
+use chrono::{DateTime, Utc};
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use serde::Deserialize;
+use std::sync::LazyLock;
 
-use crate::{EngineError, EngineInfo, RawResult, SearchEngine, new_rand_client, parse_search};
+use crate::{
+    EngineError, EngineInfo, ImageEngine, NewsEngine, RawImage, RawNews, RawResult, SafeSearch,
+    SearchEngine, SearchParams, SuggestEngine, TimeRange, TokenStore, check_status,
+    client::client_for, parse_opensearch_suggestions, parse_search, unwrap_redirect,
+};
 
 #[derive(Clone)]
 pub struct DuckDuckGo;
@@ -11,19 +17,47 @@ impl EngineInfo for DuckDuckGo {
     fn name(&self) -> &'static str {
         "DuckDuckGo"
     }
+
+    /// See [`region_code`]: a language without a region can't be applied.
+    fn honors(&self, params: &SearchParams) -> bool {
+        params.language().is_none() || params.region().is_some()
+    }
 }
 
 // `s`/`dc` mirror DDG's own "More results" link params; unofficial, may need
 // revalidating against `test_duckduckgo_pagination_live` if DDG's markup changes.
-fn build_search_url(query: &str, start: usize) -> String {
+fn build_search_url(query: &str, start: usize, params: &SearchParams) -> String {
     let query = utf8_percent_encode(query, NON_ALPHANUMERIC);
     let mut url = format!("https://html.duckduckgo.com/html?q={query}");
     if start > 0 {
         url.push_str(&format!("&s={start}&dc={}", start + 1));
     }
+    if let Some(kl) = region_code(params) {
+        url.push_str(&format!("&kl={kl}"));
+    }
+    match params.safe_search {
+        SafeSearch::Off => url.push_str("&kp=-2"),
+        SafeSearch::Moderate => {}
+        SafeSearch::Strict => url.push_str("&kp=1"),
+    }
+    if let Some(range) = params.time_range {
+        let df = match range {
+            TimeRange::Day => "d",
+            TimeRange::Week => "w",
+            TimeRange::Month => "m",
+            TimeRange::Year => "y",
+        };
+        url.push_str(&format!("&df={df}"));
+    }
     url
 }
 
+/// DDG's `kl`/`l` region code, region first (`"us-en"`). DDG has no
+/// language-only setting, so a locale without a region maps to nothing.
+fn region_code(params: &SearchParams) -> Option<String> {
+    Some(format!("{}-{}", params.region()?, params.language()?))
+}
+
 // A real DDG results page always has this wrapper, even with 0 hits; the
 // bot-wall "anomaly"/captcha interstitial DDG serves instead has completely
 // different markup. Without this check a block silently parses to an empty
@@ -39,18 +73,20 @@ impl SearchEngine for DuckDuckGo {
         query: &str,
         start: usize,
         _count: usize,
+        params: &SearchParams,
     ) -> Result<Vec<RawResult>, EngineError> {
-        let resp = new_rand_client()
-            .get(build_search_url(query, start))
+        let resp = client_for(self.name())
+            .get(build_search_url(query, start, params))
             .send()
             .await
-            .map_err(EngineError::ReqwestError)?;
+            .map_err(EngineError::ReqwestError)
+            .and_then(check_status)?;
 
         let html = resp.text().await.map_err(EngineError::ReqwestError)?;
         if !looks_like_search_results(&html) {
-            return Err(EngineError::ParseError(
-                "DuckDuckGo response didn't look like real results (likely blocked)".into(),
-            ));
+            return Err(EngineError::Blocked {
+                reason: "DuckDuckGo response didn't look like real results".into(),
+            });
         }
 
         parse_response(&html)
@@ -80,18 +116,310 @@ pub fn parse_response(html: &str) -> Result<Vec<RawResult>, EngineError> {
     Ok(results)
 }
 
-fn extract_ddg_url(ddg_href: &str) -> Option<String> {
-    // Decode the DDG redirect link
-    let url = Url::parse("https://duckduckgo.com")
-        .ok()?
-        .join(ddg_href)
-        .ok()?;
-    for (k, v) in url.query_pairs() {
-        if k == "uddg" {
-            return Some(percent_decode(v.as_bytes()).decode_utf8().ok()?.to_string());
+/// Per-query `vqd` tokens. DDG's JSON endpoints (`i.js` for images,
+/// `news.js` for news) refuse any request without the token its HTML page embeds for that exact query,
+/// so it's fetched once per query and reused across every later page.
+static VQD_TOKENS: LazyLock<TokenStore<String, String>> = LazyLock::new(|| TokenStore::new(1024));
+
+fn build_vqd_url(query: &str) -> String {
+    let query = utf8_percent_encode(query, NON_ALPHANUMERIC);
+    format!("https://duckduckgo.com/?q={query}&iax=images&ia=images")
+}
+
+// `s` is an absolute offset into DDG's image stream (it serves 100 per
+// call). `p=1` is the moderate safe-search default (images have no separate
+// strict level), `-1` turns it off; `f` is the comma-separated filter list,
+// time first.
+fn build_image_search_url(query: &str, vqd: &str, start: usize, params: &SearchParams) -> String {
+    let query = utf8_percent_encode(query, NON_ALPHANUMERIC);
+    let vqd = utf8_percent_encode(vqd, NON_ALPHANUMERIC);
+    let region = region_code(params).unwrap_or_else(|| "us-en".to_string());
+    let time = match params.time_range {
+        None => "",
+        Some(TimeRange::Day) => "time:Day",
+        Some(TimeRange::Week) => "time:Week",
+        Some(TimeRange::Month) => "time:Month",
+        Some(TimeRange::Year) => "time:Year",
+    };
+    let safe = match params.safe_search {
+        SafeSearch::Off => "-1",
+        SafeSearch::Moderate | SafeSearch::Strict => "1",
+    };
+    format!(
+        "https://duckduckgo.com/i.js?l={region}&o=json&q={query}&vqd={vqd}&f={time},,,,,&p={safe}&s={start}"
+    )
+}
+
+/// Pulls the `vqd` token out of a DDG HTML page, where it shows up either
+/// quoted (`vqd="4-123..."`) in inline JS or unquoted in a link's query
+/// string (`vqd=4-123...&`).
+fn extract_vqd(html: &str) -> Option<String> {
+    let after = &html[html.find("vqd=")? + "vqd=".len()..];
+    let token: String = after
+        .trim_start_matches(['"', '\''])
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    (!token.is_empty()).then_some(token)
+}
+
+async fn fetch_vqd(query: &str) -> Result<String, EngineError> {
+    if let Some(vqd) = VQD_TOKENS.get(&query.to_string()) {
+        return Ok(vqd);
+    }
+
+    let html = client_for(DuckDuckGo.name())
+        .get(build_vqd_url(query))
+        .send()
+        .await
+        .map_err(EngineError::ReqwestError)
+        .and_then(check_status)?
+        .text()
+        .await
+        .map_err(EngineError::ReqwestError)?;
+
+    let vqd = extract_vqd(&html).ok_or_else(|| EngineError::Blocked {
+        reason: "DuckDuckGo page had no vqd token".into(),
+    })?;
+    VQD_TOKENS.insert(query.to_string(), vqd.clone());
+    Ok(vqd)
+}
+
+#[derive(Deserialize)]
+struct ImageResponse {
+    #[serde(default)]
+    results: Vec<ImageItem>,
+}
+
+#[derive(Deserialize)]
+struct ImageItem {
+    #[serde(default)]
+    image: String,
+    #[serde(default)]
+    title: String,
+}
+
+pub fn parse_image_response(json: &str) -> Result<Vec<RawImage>, EngineError> {
+    let resp: ImageResponse = serde_json::from_str(json).map_err(|e| {
+        EngineError::ParseError(format!(
+            "DuckDuckGo image response wasn't valid JSON (likely blocked or a stale vqd): {e}"
+        ))
+    })?;
+
+    Ok(resp
+        .results
+        .into_iter()
+        .map(|item| RawImage {
+            url: item.image,
+            title: item.title,
+        })
+        .collect())
+}
+
+async fn fetch_images(
+    query: &str,
+    vqd: &str,
+    start: usize,
+    params: &SearchParams,
+) -> Result<Vec<RawImage>, EngineError> {
+    let json = client_for(DuckDuckGo.name())
+        .get(build_image_search_url(query, vqd, start, params))
+        .header(reqwest::header::REFERER, "https://duckduckgo.com/")
+        .send()
+        .await
+        .map_err(EngineError::ReqwestError)
+        .and_then(check_status)?
+        .text()
+        .await
+        .map_err(EngineError::ReqwestError)?;
+
+    parse_image_response(&json)
+}
+
+#[async_trait]
+impl ImageEngine for DuckDuckGo {
+    /// `i.js` has no strict safe-search level.
+    fn honors_images(&self, params: &SearchParams) -> bool {
+        self.honors(params) && params.safe_search != SafeSearch::Strict
+    }
+
+    /// `count` is unused: `i.js` always serves a fixed-size batch.
+    async fn search_images(
+        &self,
+        query: &str,
+        start: usize,
+        _count: usize,
+        params: &SearchParams,
+    ) -> Result<Vec<RawImage>, EngineError> {
+        let vqd = fetch_vqd(query).await?;
+        match fetch_images(query, &vqd, start, params).await {
+            Ok(images) => Ok(images),
+            // A cached token can expire server-side; drop it and retry once
+            // with a fresh one before giving up.
+            Err(EngineError::ParseError(_) | EngineError::Blocked { .. }) => {
+                VQD_TOKENS.remove(&query.to_string());
+                let vqd = fetch_vqd(query).await?;
+                fetch_images(query, &vqd, start, params).await
+            }
+            Err(e) => Err(e),
         }
     }
-    Some(ddg_href.to_string()) // fallback to raw href
+}
+
+// Same `l`/`p`/`s` conventions as `i.js`; `df` takes the single-letter
+// ranges the HTML endpoint does.
+fn build_news_search_url(query: &str, vqd: &str, start: usize, params: &SearchParams) -> String {
+    let query = utf8_percent_encode(query, NON_ALPHANUMERIC);
+    let vqd = utf8_percent_encode(vqd, NON_ALPHANUMERIC);
+    let region = region_code(params).unwrap_or_else(|| "us-en".to_string());
+    let safe = match params.safe_search {
+        SafeSearch::Off => "-1",
+        SafeSearch::Moderate | SafeSearch::Strict => "1",
+    };
+    let mut url = format!(
+        "https://duckduckgo.com/news.js?l={region}&o=json&noamp=1&q={query}&vqd={vqd}&p={safe}&s={start}"
+    );
+    if let Some(range) = params.time_range {
+        let df = match range {
+            TimeRange::Day => "d",
+            TimeRange::Week => "w",
+            TimeRange::Month => "m",
+            TimeRange::Year => "y",
+        };
+        url.push_str(&format!("&df={df}"));
+    }
+    url
+}
+
+#[derive(Deserialize)]
+struct NewsResponse {
+    #[serde(default)]
+    results: Vec<NewsItem>,
+}
+
+#[derive(Deserialize)]
+struct NewsItem {
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    excerpt: String,
+    #[serde(default)]
+    source: String,
+    /// Unix seconds.
+    date: Option<i64>,
+    image: Option<String>,
+}
+
+pub fn parse_news_response(json: &str) -> Result<Vec<RawNews>, EngineError> {
+    let resp: NewsResponse = serde_json::from_str(json).map_err(|e| {
+        EngineError::ParseError(format!(
+            "DuckDuckGo news response wasn't valid JSON (likely blocked or a stale vqd): {e}"
+        ))
+    })?;
+
+    Ok(resp
+        .results
+        .into_iter()
+        .map(|item| RawNews {
+            url: item.url,
+            // Titles and excerpts come back with HTML entities and `<b>`
+            // highlighting baked in.
+            title: strip_markup(&item.title),
+            snippet: strip_markup(&item.excerpt),
+            source: item.source,
+            published: item
+                .date
+                .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0)),
+            thumbnail: item.image.filter(|i| !i.is_empty()),
+        })
+        .collect())
+}
+
+fn strip_markup(fragment: &str) -> String {
+    scraper::Html::parse_fragment(fragment)
+        .root_element()
+        .text()
+        .collect()
+}
+
+async fn fetch_news(
+    query: &str,
+    vqd: &str,
+    start: usize,
+    params: &SearchParams,
+) -> Result<Vec<RawNews>, EngineError> {
+    let json = client_for(DuckDuckGo.name())
+        .get(build_news_search_url(query, vqd, start, params))
+        .header(reqwest::header::REFERER, "https://duckduckgo.com/")
+        .send()
+        .await
+        .map_err(EngineError::ReqwestError)
+        .and_then(check_status)?
+        .text()
+        .await
+        .map_err(EngineError::ReqwestError)?;
+
+    parse_news_response(&json)
+}
+
+#[async_trait]
+impl NewsEngine for DuckDuckGo {
+    /// Nor has `news.js`.
+    fn honors_news(&self, params: &SearchParams) -> bool {
+        self.honors_images(params)
+    }
+
+    /// `count` is unused: `news.js` always serves a fixed-size batch.
+    async fn search_news(
+        &self,
+        query: &str,
+        start: usize,
+        _count: usize,
+        params: &SearchParams,
+    ) -> Result<Vec<RawNews>, EngineError> {
+        let vqd = fetch_vqd(query).await?;
+        match fetch_news(query, &vqd, start, params).await {
+            Ok(news) => Ok(news),
+            // See `search_images`: the token may have expired.
+            Err(EngineError::ParseError(_) | EngineError::Blocked { .. }) => {
+                VQD_TOKENS.remove(&query.to_string());
+                let vqd = fetch_vqd(query).await?;
+                fetch_news(query, &vqd, start, params).await
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+// `type=list` asks for the OpenSearch array instead of DDG's own
+// `[{"phrase": ...}]` shape.
+fn build_suggest_url(query: &str) -> String {
+    let query = utf8_percent_encode(query, NON_ALPHANUMERIC);
+    format!("https://duckduckgo.com/ac/?q={query}&type=list")
+}
+
+#[async_trait]
+impl SuggestEngine for DuckDuckGo {
+    async fn suggestions(&self, query: &str) -> Result<Vec<String>, EngineError> {
+        let json = client_for(self.name())
+            .get(build_suggest_url(query))
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)
+            .and_then(check_status)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_opensearch_suggestions(&json)
+    }
+}
+
+fn extract_ddg_url(ddg_href: &str) -> Option<String> {
+    // Decode the DDG redirect link, falling back to the raw href
+    Some(unwrap_redirect(ddg_href, "https://duckduckgo.com", "uddg"))
 }
 
 #[cfg(test)]
@@ -101,7 +429,7 @@ mod test {
     #[test]
     fn build_search_url_omits_pagination_params_on_first_page() {
         assert_eq!(
-            build_search_url("rust async", 0),
+            build_search_url("rust async", 0, &SearchParams::default()),
             "https://html.duckduckgo.com/html?q=rust%20async"
         );
     }
@@ -109,7 +437,7 @@ mod test {
     #[test]
     fn build_search_url_adds_start_and_dc_for_later_pages() {
         assert_eq!(
-            build_search_url("rust async", 20),
+            build_search_url("rust async", 20, &SearchParams::default()),
             "https://html.duckduckgo.com/html?q=rust%20async&s=20&dc=21"
         );
     }
@@ -117,18 +445,64 @@ mod test {
     #[test]
     fn build_search_url_encodes_reserved_characters() {
         assert_eq!(
-            build_search_url("AT&T c++ #tag", 0),
+            build_search_url("AT&T c++ #tag", 0, &SearchParams::default()),
             "https://html.duckduckgo.com/html?q=AT%26T%20c%2B%2B%20%23tag"
         );
     }
 
+    #[test]
+    fn build_search_url_maps_params_to_kl_kp_and_df() {
+        let params = SearchParams {
+            locale: Some("en-GB".into()),
+            safe_search: SafeSearch::Off,
+            time_range: Some(TimeRange::Month),
+        };
+        assert_eq!(
+            build_search_url("rust", 0, &params),
+            "https://html.duckduckgo.com/html?q=rust&kl=gb-en&kp=-2&df=m"
+        );
+    }
+
+    #[test]
+    fn build_search_url_skips_kl_without_a_region() {
+        let params = SearchParams {
+            locale: Some("en".into()),
+            safe_search: SafeSearch::Strict,
+            time_range: None,
+        };
+        assert_eq!(
+            build_search_url("rust", 0, &params),
+            "https://html.duckduckgo.com/html?q=rust&kp=1"
+        );
+    }
+
+    #[test]
+    fn a_locale_without_a_region_or_strict_images_and_news_are_not_honored() {
+        let params = |locale: &str| SearchParams {
+            locale: Some(locale.into()),
+            ..Default::default()
+        };
+        assert!(DuckDuckGo.honors(&params("en-GB")));
+        assert!(!DuckDuckGo.honors(&params("en")));
+        assert!(DuckDuckGo.honors(&SearchParams::default()));
+
+        let strict = SearchParams {
+            safe_search: SafeSearch::Strict,
+            ..Default::default()
+        };
+        assert!(DuckDuckGo.honors(&strict));
+        assert!(!DuckDuckGo.honors_images(&strict));
+        assert!(!DuckDuckGo.honors_news(&strict));
+        assert!(DuckDuckGo.honors_news(&params("en-GB")));
+    }
+
     /// Non-ASCII queries must survive a full percent-encode round trip —
     /// regression guard for "encoding support" (unicode search terms are a
     /// normal, not edge-case, input for a search engine).
     #[test]
     fn build_search_url_encodes_non_ascii_query() {
         assert_eq!(
-            build_search_url("café 日本語", 0),
+            build_search_url("café 日本語", 0, &SearchParams::default()),
             "https://html.duckduckgo.com/html?q=caf%C3%A9%20%E6%97%A5%E6%9C%AC%E8%AA%9E"
         );
     }
@@ -183,6 +557,134 @@ mod test {
         assert_eq!(results[0].description, "A systems programming language.");
     }
 
+    #[test]
+    fn build_image_search_url_maps_params_to_l_p_and_time_filter() {
+        let params = SearchParams {
+            locale: Some("fr-FR".into()),
+            safe_search: SafeSearch::Off,
+            time_range: Some(TimeRange::Day),
+        };
+        assert_eq!(
+            build_image_search_url("rust", "4-1", 0, &params),
+            "https://duckduckgo.com/i.js?l=fr-fr&o=json&q=rust&vqd=4%2D1&f=time:Day,,,,,&p=-1&s=0"
+        );
+    }
+
+    #[test]
+    fn build_image_search_url_passes_vqd_and_offset() {
+        assert_eq!(
+            build_image_search_url("rust async", "4-1234_abc", 100, &SearchParams::default()),
+            "https://duckduckgo.com/i.js?l=us-en&o=json&q=rust%20async&vqd=4%2D1234%5Fabc&f=,,,,,&p=1&s=100"
+        );
+    }
+
+    #[test]
+    fn extract_vqd_reads_a_quoted_token() {
+        let html =
+            r#"<script>DDG.deep.initialize('/d.js?q=rust', {vqd="4-1234567890_abcdef"});</script>"#;
+        assert_eq!(extract_vqd(html), Some("4-1234567890_abcdef".to_string()));
+    }
+
+    #[test]
+    fn extract_vqd_reads_an_unquoted_token_from_a_link() {
+        let html = r#"<a href="/i.js?q=rust&vqd=4-98765&p=1">images</a>"#;
+        assert_eq!(extract_vqd(html), Some("4-98765".to_string()));
+    }
+
+    #[test]
+    fn extract_vqd_returns_none_on_a_block_page() {
+        assert_eq!(
+            extract_vqd("<html><body>unusual traffic</body></html>"),
+            None
+        );
+    }
+
+    const IMAGE_FIXTURE: &str = r#"{
+        "query": "rust",
+        "results": [
+            { "image": "https://imgs.example.com/rust-logo.png", "title": "Rust Logo", "thumbnail": "https://tse.mm.bing.net/th?id=1", "url": "https://example.com/logo" },
+            { "image": "https://imgs.example.com/ferris.png", "title": "Ferris the Crab", "thumbnail": "https://tse.mm.bing.net/th?id=2", "url": "https://example.com/ferris" }
+        ],
+        "next": "i.js?q=rust&s=100"
+    }"#;
+
+    #[test]
+    fn parse_image_response_extracts_full_size_images() {
+        let images = parse_image_response(IMAGE_FIXTURE).unwrap();
+
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[0].url, "https://imgs.example.com/rust-logo.png");
+        assert_eq!(images[0].title, "Rust Logo");
+        assert_eq!(images[1].url, "https://imgs.example.com/ferris.png");
+    }
+
+    #[test]
+    fn parse_image_response_returns_empty_vec_once_exhausted() {
+        let images = parse_image_response(r#"{"query": "rust", "results": []}"#).unwrap();
+        assert!(images.is_empty());
+    }
+
+    #[test]
+    fn parse_image_response_rejects_a_non_json_block_page() {
+        assert!(parse_image_response("<html>If this error persists</html>").is_err());
+    }
+
+    #[test]
+    fn build_suggest_url_requests_the_opensearch_list_format() {
+        assert_eq!(
+            build_suggest_url("rust as"),
+            "https://duckduckgo.com/ac/?q=rust%20as&type=list"
+        );
+    }
+
+    #[test]
+    fn build_news_search_url_maps_params_to_l_p_and_df() {
+        let params = SearchParams {
+            locale: Some("de-DE".into()),
+            safe_search: SafeSearch::Off,
+            time_range: Some(TimeRange::Week),
+        };
+        assert_eq!(
+            build_news_search_url("rust", "4-1", 30, &params),
+            "https://duckduckgo.com/news.js?l=de-de&o=json&noamp=1&q=rust&vqd=4%2D1&p=-1&s=30&df=w"
+        );
+    }
+
+    const NEWS_FIXTURE: &str = r#"{
+        "query": "rust",
+        "results": [
+            { "date": 1735732800, "excerpt": "The <b>Rust</b> 2024 edition &amp; more.", "image": "https://imgs.example.com/rust.jpg", "relative_time": "1 hour ago", "source": "Example News", "title": "<b>Rust</b> 2024 ships", "url": "https://news.example.com/rust-2024" },
+            { "excerpt": "No date or image.", "image": "", "source": "Example Blog", "title": "Undated", "url": "https://blog.example.com/undated" }
+        ]
+    }"#;
+
+    #[test]
+    fn parse_news_response_strips_markup_and_reads_unix_dates() {
+        let news = parse_news_response(NEWS_FIXTURE).unwrap();
+
+        assert_eq!(news.len(), 2);
+        assert_eq!(news[0].url, "https://news.example.com/rust-2024");
+        assert_eq!(news[0].title, "Rust 2024 ships");
+        assert_eq!(news[0].snippet, "The Rust 2024 edition & more.");
+        assert_eq!(news[0].source, "Example News");
+        assert_eq!(
+            news[0].published.map(|p| p.to_rfc3339()),
+            Some("2025-01-01T12:00:00+00:00".to_string())
+        );
+        assert_eq!(
+            news[0].thumbnail.as_deref(),
+            Some("https://imgs.example.com/rust.jpg")
+        );
+
+        assert_eq!(news[1].published, None);
+        assert_eq!(news[1].thumbnail, None);
+    }
+
+    #[test]
+    fn parse_news_response_rejects_a_non_json_block_page() {
+        assert!(parse_news_response("<html>If this error persists</html>").is_err());
+    }
+
     use crate::fixtures::cached_html;
 
     // DDG bot-walls datacenter IPs with an "anomaly" page; retry from a
@@ -192,7 +694,7 @@ mod test {
     async fn test_duckduckgo_live() {
         let html = cached_html(
             "duckduckgo/search_p0.html",
-            &build_search_url("rust async", 0),
+            &build_search_url("rust async", 0, &SearchParams::default()),
             looks_like_search_results,
         )
         .await;
@@ -205,13 +707,13 @@ mod test {
     async fn test_duckduckgo_pagination_live() {
         let page1_html = cached_html(
             "duckduckgo/search_p0.html",
-            &build_search_url("rust async", 0),
+            &build_search_url("rust async", 0, &SearchParams::default()),
             looks_like_search_results,
         )
         .await;
         let page2_html = cached_html(
             "duckduckgo/search_p1.html",
-            &build_search_url("rust async", 20),
+            &build_search_url("rust async", 20, &SearchParams::default()),
             looks_like_search_results,
         )
         .await;
@@ -229,4 +731,22 @@ mod test {
              `s`/`dc` pagination params may need revisiting"
         );
     }
+
+    #[ignore]
+    #[tokio::test]
+    async fn test_duckduckgo_images_live() {
+        let html = cached_html(
+            "duckduckgo/images_vqd.html",
+            &build_vqd_url("rust async"),
+            |h| extract_vqd(h).is_some(),
+        )
+        .await;
+        let vqd = extract_vqd(&html).unwrap();
+        // The vqd is session-bound, so only the token page is fixture-cached;
+        // the JSON itself is always fetched live.
+        let images = fetch_images("rust async", &vqd, 0, &SearchParams::default())
+            .await
+            .unwrap();
+        assert!(!images.is_empty());
+    }
 }
@@ -6,16 +6,35 @@
 //! deduplication, and persistence.
 
 use async_trait::async_trait;
-use rand::seq::IndexedRandom;
-use reqwest::Client;
+use chrono::{DateTime, Utc};
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 mod brave;
+mod client;
+mod declarative;
 mod duckduckgo;
+mod marginalia;
+mod mediawiki;
+mod mojeek;
+mod params;
+mod qwant;
+mod startpage;
 
 pub use brave::Brave;
+pub use client::{ClientConfig, ProxyError, configure_clients};
+pub use declarative::{
+    DeclarativeEngine, DefinitionError, EngineDefinition, ImagesDefinition, ParamsDefinition,
+    SafeSearchDefinition, SearchDefinition, TimeRangeDefinition,
+};
 pub use duckduckgo::DuckDuckGo;
+pub use marginalia::Marginalia;
+pub use mediawiki::MediaWiki;
+pub use mojeek::Mojeek;
+pub use params::{SafeSearch, SearchParams, TimeRange, UnknownParam};
+pub use qwant::Qwant;
+pub use startpage::Startpage;
 
 /// One raw text-search hit, straight off an engine's results page — no
 /// ranking, dedup, or engine attribution applied yet.
@@ -33,11 +52,48 @@ pub struct RawImage {
     pub title: String,
 }
 
+/// One raw news hit. `published` is `None` when the engine didn't say or
+/// said it in a form we couldn't parse.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawNews {
+    pub url: String,
+    pub title: String,
+    pub snippet: String,
+    /// The outlet that ran the story, e.g. `"BBC News"`.
+    pub source: String,
+    pub published: Option<DateTime<Utc>>,
+    pub thumbnail: Option<String>,
+}
+
+/// One raw video hit. Every field but `url`/`title` is best-effort: `None`
+/// when the engine didn't show it or showed it in a form we couldn't parse.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawVideo {
+    pub url: String,
+    pub title: String,
+    pub duration: Option<Duration>,
+    /// Channel or account that posted the video.
+    pub uploader: Option<String>,
+    pub published: Option<DateTime<Utc>>,
+    pub thumbnail: Option<String>,
+}
+
 #[derive(Debug)]
 pub enum EngineError {
     ReqwestError(reqwest::Error),
     ParseError(String),
     Timeout, // engine timeout
+    /// The engine answered, but with a captcha/bot wall instead of results.
+    Blocked {
+        reason: String,
+    },
+    /// HTTP 429 (or a 503 carrying `Retry-After`), with how long the engine
+    /// asked us to back off for, if it said.
+    RateLimited {
+        retry_after: Option<Duration>,
+    },
+    /// Any other non-success status.
+    HttpStatus(u16),
 }
 
 impl std::fmt::Display for EngineError {
@@ -46,6 +102,12 @@ impl std::fmt::Display for EngineError {
             EngineError::ReqwestError(e) => write!(f, "request failed: {e}"),
             EngineError::ParseError(e) => write!(f, "parse error: {e}"),
             EngineError::Timeout => write!(f, "engine timed out"),
+            EngineError::Blocked { reason } => write!(f, "blocked: {reason}"),
+            EngineError::RateLimited {
+                retry_after: Some(after),
+            } => write!(f, "rate limited (retry after {}s)", after.as_secs()),
+            EngineError::RateLimited { retry_after: None } => write!(f, "rate limited"),
+            EngineError::HttpStatus(code) => write!(f, "HTTP status {code}"),
         }
     }
 }
@@ -54,64 +116,197 @@ impl std::error::Error for EngineError {}
 
 #[async_trait]
 pub trait EngineInfo: Clone + Send {
-    fn name(&self) -> &'static str;
+    fn name(&self) -> &str;
+
+    /// Whether every field of `params` that isn't at its default can be
+    /// applied. An engine that can't is left out of the search rather than
+    /// returning results that ignore the filter. [`SafeSearch::Off`] counts
+    /// as honored by an engine that never filters. This is for web
+    /// searches; the other verticals default to it (see e.g.
+    /// [`ImageEngine::honors_images`]).
+    fn honors(&self, _params: &SearchParams) -> bool {
+        true
+    }
 }
 
+// `async_trait` marks each method `#[must_use]` on top of the boxed
+// future's own, which clippy flags as doubled.
+#[allow(clippy::double_must_use)]
 #[async_trait]
 pub trait SearchEngine: EngineInfo + Clone + Send {
     /// Fetches one page of results. `start` is how many the caller already
     /// has; `count` is a hint some engines can't honor exactly. An empty
-    /// `Vec` signals no more results.
+    /// `Vec` signals no more results. `params` are applied as far as the
+    /// engine supports them; see [`EngineInfo::honors`].
     async fn search_results(
         &self,
         query: &str,
         start: usize,
         count: usize,
+        params: &SearchParams,
     ) -> Result<Vec<RawResult>, EngineError>;
 }
 
+#[allow(clippy::double_must_use)]
 #[async_trait]
 pub trait ImageEngine: EngineInfo + Clone + Send {
+    /// [`EngineInfo::honors`] for image searches, for an engine whose image
+    /// endpoint takes fewer params than its web one.
+    fn honors_images(&self, params: &SearchParams) -> bool {
+        self.honors(params)
+    }
+
     /// See [`SearchEngine::search_results`] — same paging contract.
     async fn search_images(
         &self,
         query: &str,
         start: usize,
         count: usize,
+        params: &SearchParams,
     ) -> Result<Vec<RawImage>, EngineError>;
 }
 
-static USER_AGENTS: &[&str] = &[
-    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
-    "Mozilla/5.0 (X11; Linux x86_64; rv:118.0) Gecko/20100101 Firefox/118.0",
-    "Mozilla/5.0 (Macintosh; Intel Mac OS X 13_4) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/118.0.5993.72 Safari/537.36",
-];
-
-/// One [`Client`] per user agent, built once and reused so requests actually
-/// benefit from connection pooling/keep-alive instead of paying a fresh
-/// TCP+TLS handshake on every single search. Cloning a `Client` is cheap —
-/// it's just an `Arc` around the shared connection pool.
-static CLIENTS: std::sync::OnceLock<Vec<Client>> = std::sync::OnceLock::new();
-
-/// Picks a random pre-built client (rotating user agent across requests to
-/// avoid looking like a single scripted client to the upstream engine).
-fn new_rand_client() -> Client {
-    let clients = CLIENTS.get_or_init(|| {
-        USER_AGENTS
-            .iter()
-            .map(|ua| {
-                Client::builder()
-                    .user_agent(*ua)
-                    .build()
-                    .expect("failed to build reqwest client")
-            })
-            .collect()
-    });
-
-    clients
-        .choose(&mut rand::rng())
-        .expect("USER_AGENTS is non-empty")
-        .clone()
+#[allow(clippy::double_must_use)]
+#[async_trait]
+pub trait NewsEngine: EngineInfo + Clone + Send {
+    /// See [`ImageEngine::honors_images`].
+    fn honors_news(&self, params: &SearchParams) -> bool {
+        self.honors(params)
+    }
+
+    /// See [`SearchEngine::search_results`] — same paging contract.
+    async fn search_news(
+        &self,
+        query: &str,
+        start: usize,
+        count: usize,
+        params: &SearchParams,
+    ) -> Result<Vec<RawNews>, EngineError>;
+}
+
+#[allow(clippy::double_must_use)]
+#[async_trait]
+pub trait VideoEngine: EngineInfo + Clone + Send {
+    /// See [`ImageEngine::honors_images`].
+    fn honors_videos(&self, params: &SearchParams) -> bool {
+        self.honors(params)
+    }
+
+    /// See [`SearchEngine::search_results`] — same paging contract.
+    async fn search_videos(
+        &self,
+        query: &str,
+        start: usize,
+        count: usize,
+        params: &SearchParams,
+    ) -> Result<Vec<RawVideo>, EngineError>;
+}
+
+#[allow(clippy::double_must_use)]
+#[async_trait]
+pub trait SuggestEngine: EngineInfo + Clone + Send {
+    /// Query completions for a partially-typed `query`, best first.
+    async fn suggestions(&self, query: &str) -> Result<Vec<String>, EngineError>;
+}
+
+/// Turns a non-success response into the matching [`EngineError`] before
+/// its body is read, so a 429/503 page isn't scraped (and, having none of
+/// the expected markup, mistaken for a block page or for exhaustion).
+pub(crate) fn check_status(resp: reqwest::Response) -> Result<reqwest::Response, EngineError> {
+    let retry_after = resp
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok());
+    match status_error(resp.status().as_u16(), retry_after) {
+        Some(e) => Err(e),
+        None => Ok(resp),
+    }
+}
+
+/// `retry_after` is the raw `Retry-After` header. Only the delay-seconds
+/// form is understood; an HTTP-date is treated as absent.
+fn status_error(status: u16, retry_after: Option<&str>) -> Option<EngineError> {
+    let retry_after = retry_after
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(Duration::from_secs);
+    match status {
+        200..=299 => None,
+        429 => Some(EngineError::RateLimited { retry_after }),
+        503 if retry_after.is_some() => Some(EngineError::RateLimited { retry_after }),
+        // Every engine here is fetched anonymously, so a 403 means the
+        // request itself was refused — a bot wall, not a broken adapter.
+        403 => Some(EngineError::Blocked {
+            reason: "HTTP 403 Forbidden".into(),
+        }),
+        code => Some(EngineError::HttpStatus(code)),
+    }
+}
+
+/// Parses the OpenSearch suggestions format (`["query", ["completion",
+/// ...], ...]`) that both DDG's and Brave's suggest endpoints speak; any
+/// trailing description/URL arrays are ignored.
+pub(crate) fn parse_opensearch_suggestions(json: &str) -> Result<Vec<String>, EngineError> {
+    let invalid = |reason: String| {
+        EngineError::ParseError(format!("not an OpenSearch suggestions response: {reason}"))
+    };
+    let parts: Vec<serde_json::Value> =
+        serde_json::from_str(json).map_err(|e| invalid(e.to_string()))?;
+    let suggestions = parts
+        .into_iter()
+        .nth(1)
+        .ok_or_else(|| invalid("no completion list".into()))?;
+    serde_json::from_value(suggestions).map_err(|e| invalid(e.to_string()))
+}
+
+/// A small, bounded, process-wide map for per-query state an engine has to
+/// carry between otherwise-stateless [`SearchEngine`] calls (e.g. a
+/// pagination token scraped off the previous page). Oldest entries are
+/// evicted first once `capacity` is reached, so a stream of one-off queries
+/// can't grow it forever; losing an entry just means the engine has to
+/// re-derive it.
+pub(crate) struct TokenStore<K, V> {
+    inner: std::sync::Mutex<(
+        std::collections::HashMap<K, V>,
+        std::collections::VecDeque<K>,
+    )>,
+    capacity: usize,
+}
+
+impl<K: std::hash::Hash + Eq + Clone, V: Clone> TokenStore<K, V> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            inner: std::sync::Mutex::new(Default::default()),
+            capacity,
+        }
+    }
+
+    pub(crate) fn get(&self, key: &K) -> Option<V> {
+        self.inner.lock().unwrap().0.get(key).cloned()
+    }
+
+    pub(crate) fn remove(&self, key: &K) {
+        let mut guard = self.inner.lock().unwrap();
+        let (map, order) = &mut *guard;
+        if map.remove(key).is_some() {
+            order.retain(|k| k != key);
+        }
+    }
+
+    pub(crate) fn insert(&self, key: K, value: V) {
+        let mut guard = self.inner.lock().unwrap();
+        let (map, order) = &mut *guard;
+        if map.insert(key.clone(), value).is_none() {
+            order.push_back(key);
+        }
+        while map.len() > self.capacity {
+            match order.pop_front() {
+                Some(oldest) => {
+                    map.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+    }
 }
 
 /// Record-once, replay-forever HTML fixtures for the "live" engine tests: the
@@ -149,7 +344,7 @@ pub(crate) mod fixtures {
             return html;
         }
 
-        let html = super::new_rand_client()
+        let html = crate::client::client_for("live fixtures")
             .get(url)
             .send()
             .await
@@ -172,14 +367,30 @@ pub(crate) mod fixtures {
     }
 }
 
+/// Unwraps a click-tracking redirect like DDG's `/l/?uddg=<target>`:
+/// resolves `href` against `base` (hrefs are often protocol- or
+/// path-relative) and returns the decoded value of its `param` query param,
+/// or `href` unchanged if there isn't one.
+pub(crate) fn unwrap_redirect(href: &str, base: &str, param: &str) -> String {
+    reqwest::Url::parse(base)
+        .and_then(|base| base.join(href))
+        .ok()
+        .and_then(|url| {
+            url.query_pairs()
+                .find(|(k, _)| k == param)
+                .map(|(_, v)| v.into_owned())
+        })
+        .unwrap_or_else(|| href.to_string())
+}
+
 const PARSE_ERROR: &str = "Couldnt parse selector string";
 
 pub fn parse_search(
     html: &str,
-    results_selector: &'static str,
-    title_selector: &'static str,
-    href_selector: &'static str,
-    description_selector: &'static str,
+    results_selector: &str,
+    title_selector: &str,
+    href_selector: &str,
+    description_selector: &str,
 ) -> Vec<RawResult> {
     let html = Html::parse_document(html);
 
@@ -218,9 +429,9 @@ pub fn parse_search(
 
 pub fn parse_images(
     html: &str,
-    images_selector: &'static str,
-    title_selector: &'static str,
-    img_selector: &'static str,
+    images_selector: &str,
+    title_selector: &str,
+    img_selector: &str,
 ) -> Vec<RawImage> {
     let html = Html::parse_document(html);
 
@@ -308,6 +519,80 @@ mod test {
         assert_eq!(images[0].title, "A picture");
     }
 
+    #[test]
+    fn status_error_passes_success_through() {
+        assert!(status_error(200, None).is_none());
+        assert!(status_error(202, Some("10")).is_none());
+    }
+
+    #[test]
+    fn status_error_maps_429_and_503_with_retry_after_to_rate_limited() {
+        assert!(matches!(
+            status_error(429, Some("30")),
+            Some(EngineError::RateLimited { retry_after: Some(d) }) if d == Duration::from_secs(30)
+        ));
+        assert!(matches!(
+            status_error(429, Some("Wed, 21 Oct 2015 07:28:00 GMT")),
+            Some(EngineError::RateLimited { retry_after: None })
+        ));
+        assert!(matches!(
+            status_error(503, Some("5")),
+            Some(EngineError::RateLimited { .. })
+        ));
+    }
+
+    #[test]
+    fn status_error_tells_blocks_apart_from_other_failures() {
+        assert!(matches!(
+            status_error(403, None),
+            Some(EngineError::Blocked { .. })
+        ));
+        assert!(matches!(
+            status_error(503, None),
+            Some(EngineError::HttpStatus(503))
+        ));
+        assert!(matches!(
+            status_error(500, None),
+            Some(EngineError::HttpStatus(500))
+        ));
+    }
+
+    #[test]
+    fn parse_opensearch_suggestions_reads_the_completion_list() {
+        assert_eq!(
+            parse_opensearch_suggestions(r#"["rus", ["rust", "rust lang"]]"#).unwrap(),
+            ["rust", "rust lang"]
+        );
+        assert_eq!(
+            parse_opensearch_suggestions(r#"["rus", ["rust"], ["desc"], ["https://x"]]"#).unwrap(),
+            ["rust"]
+        );
+        assert!(parse_opensearch_suggestions("<html>blocked</html>").is_err());
+    }
+
+    #[test]
+    fn token_store_evicts_oldest_entry_past_capacity() {
+        let store = TokenStore::new(2);
+        store.insert("a", 1);
+        store.insert("b", 2);
+        store.insert("c", 3);
+
+        assert_eq!(store.get(&"a"), None);
+        assert_eq!(store.get(&"b"), Some(2));
+        assert_eq!(store.get(&"c"), Some(3));
+    }
+
+    #[test]
+    fn token_store_overwrite_keeps_a_single_entry() {
+        let store = TokenStore::new(2);
+        store.insert("a", 1);
+        store.insert("a", 2);
+        store.insert("b", 3);
+
+        assert_eq!(store.get(&"a"), Some(2));
+        assert_eq!(store.get(&"b"), Some(3));
+    }
+
     #[test]
     fn parse_images_defaults_missing_fields_to_empty_string() {
         let html = r#"<div class="image"></div>"#;
@@ -0,0 +1,207 @@
+use crate::{
+    EngineError, EngineInfo, RawResult, SafeSearch, SearchEngine, SearchParams, check_status,
+    client::client_for, parse_search,
+};
+use async_trait::async_trait;
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+
+/// Marginalia Search — an independent index of the non-commercial "small
+/// web". Its results rarely overlap with the big engines', which is the
+/// point, but also why it's better as an opt-in source than a default one.
+#[derive(Clone)]
+pub struct Marginalia;
+
+impl EngineInfo for Marginalia {
+    fn name(&self) -> &'static str {
+        "Marginalia"
+    }
+
+    /// Marginalia takes no language, filter or date params at all. It never
+    /// filters, so only [`SafeSearch::Strict`] is out of reach.
+    fn honors(&self, params: &SearchParams) -> bool {
+        params.language().is_none()
+            && params.safe_search != SafeSearch::Strict
+            && params.time_range.is_none()
+    }
+}
+
+/// Results per `page` on Marginalia's results page.
+const MARGINALIA_RESULTS_PER_PAGE: usize = 10;
+
+fn build_search_url(query: &str, start: usize) -> String {
+    let page = start / MARGINALIA_RESULTS_PER_PAGE;
+    let query = utf8_percent_encode(query, NON_ALPHANUMERIC);
+    let mut url = format!("https://search.marginalia.nu/search?query={query}");
+    if page > 0 {
+        // `page` is 1-based; page 1 is the bare URL above.
+        url.push_str(&format!("&page={}", page + 1));
+    }
+    url
+}
+
+// A real results page always has either result sections or the explicit
+// "no results" notice; the rate-limit/bot-check interstitial has neither.
+// Without this check a block silently parses to an empty Vec,
+// indistinguishable from genuine exhaustion.
+fn looks_like_search_results(html: &str) -> bool {
+    html.contains("search-result") || html.contains("No search results found")
+}
+
+#[async_trait]
+impl SearchEngine for Marginalia {
+    async fn search_results(
+        &self,
+        query: &str,
+        start: usize,
+        _count: usize,
+        _params: &SearchParams,
+    ) -> Result<Vec<RawResult>, EngineError> {
+        let resp = client_for(self.name())
+            .get(build_search_url(query, start))
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)
+            .and_then(check_status)?;
+
+        let html = resp.text().await.map_err(EngineError::ReqwestError)?;
+        if !looks_like_search_results(&html) {
+            return Err(EngineError::Blocked {
+                reason: "Marginalia response didn't look like real results".into(),
+            });
+        }
+
+        parse_search_response(&html)
+    }
+}
+
+pub fn parse_search_response(html: &str) -> Result<Vec<RawResult>, EngineError> {
+    Ok(parse_search(
+        html,
+        "section.search-result",
+        "h2 a",
+        "h2 a",
+        "p.description",
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn build_search_url_omits_page_on_first_page() {
+        assert_eq!(
+            build_search_url("rust async", 0),
+            "https://search.marginalia.nu/search?query=rust%20async"
+        );
+        assert_eq!(
+            build_search_url("rust async", MARGINALIA_RESULTS_PER_PAGE - 1),
+            "https://search.marginalia.nu/search?query=rust%20async"
+        );
+    }
+
+    #[test]
+    fn build_search_url_adds_one_based_page_for_later_pages() {
+        assert_eq!(
+            build_search_url("rust async", MARGINALIA_RESULTS_PER_PAGE),
+            "https://search.marginalia.nu/search?query=rust%20async&page=2"
+        );
+        assert_eq!(
+            build_search_url("rust async", MARGINALIA_RESULTS_PER_PAGE * 2 + 3),
+            "https://search.marginalia.nu/search?query=rust%20async&page=3"
+        );
+    }
+
+    #[test]
+    fn build_search_url_encodes_non_ascii_query() {
+        assert_eq!(
+            build_search_url("café 日本語", 0),
+            "https://search.marginalia.nu/search?query=caf%C3%A9%20%E6%97%A5%E6%9C%AC%E8%AA%9E"
+        );
+    }
+
+    #[test]
+    fn any_param_besides_safe_search_off_is_not_honored() {
+        let off = SearchParams {
+            safe_search: SafeSearch::Off,
+            ..Default::default()
+        };
+        assert!(Marginalia.honors(&SearchParams::default()));
+        assert!(Marginalia.honors(&off));
+        for params in [
+            SearchParams {
+                locale: Some("en".into()),
+                ..Default::default()
+            },
+            SearchParams {
+                safe_search: SafeSearch::Strict,
+                ..Default::default()
+            },
+            SearchParams {
+                time_range: Some(crate::TimeRange::Day),
+                ..Default::default()
+            },
+        ] {
+            assert!(!Marginalia.honors(&params), "{params:?}");
+        }
+    }
+
+    #[test]
+    fn looks_like_search_results_rejects_a_block_page() {
+        assert!(looks_like_search_results(
+            r#"<section class="search-result">...</section>"#
+        ));
+        assert!(looks_like_search_results("<p>No search results found.</p>"));
+        assert!(!looks_like_search_results(
+            "<html><body>Too many requests, slow down</body></html>"
+        ));
+    }
+
+    const SEARCH_FIXTURE: &str = r#"
+        <section class="search-result">
+            <div class="url"><a href="https://example.com/rust">example.com</a></div>
+            <h2><a class="title" href="https://example.com/rust">A Small Rust Blog</a></h2>
+            <p class="description">Hand-written notes on learning Rust.</p>
+        </section>
+        <section class="search-result">
+            <div class="url"><a href="https://example.org/ferris">example.org</a></div>
+            <h2><a class="title" href="https://example.org/ferris">Ferris Fan Page</a></h2>
+            <p class="description">All about the crab.</p>
+        </section>
+    "#;
+
+    #[test]
+    fn parse_search_response_extracts_results() {
+        let results = parse_search_response(SEARCH_FIXTURE).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].url, "https://example.com/rust");
+        assert_eq!(results[0].title, "A Small Rust Blog");
+        assert_eq!(
+            results[0].description,
+            "Hand-written notes on learning Rust."
+        );
+        assert_eq!(results[1].url, "https://example.org/ferris");
+    }
+
+    #[test]
+    fn parse_search_response_returns_empty_vec_once_exhausted() {
+        let results = parse_search_response("<p>No search results found.</p>").unwrap();
+        assert!(results.is_empty());
+    }
+
+    use crate::fixtures::cached_html;
+
+    #[ignore]
+    #[tokio::test]
+    async fn test_marginalia_search_live() {
+        let html = cached_html(
+            "marginalia/search_p0.html",
+            &build_search_url("rust async", 0),
+            looks_like_search_results,
+        )
+        .await;
+        let results = parse_search_response(&html).unwrap();
+        assert!(!results.is_empty());
+    }
+}
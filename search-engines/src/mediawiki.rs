@@ -0,0 +1,421 @@
+use crate::{
+    EngineError, EngineInfo, RawResult, SafeSearch, SearchEngine, SearchParams, check_status,
+    client::client_for,
+};
+use async_trait::async_trait;
+use percent_encoding::{AsciiSet, CONTROLS, NON_ALPHANUMERIC, utf8_percent_encode};
+use scraper::Html;
+use serde::Deserialize;
+use std::borrow::Cow;
+
+/// Any MediaWiki site's `action=query&list=search` API — Wikipedia in any
+/// language, or a self-hosted wiki. Unlike the scraped engines this talks to
+/// a real, documented JSON API, so there's no block page to detect; errors
+/// come back as a proper `error` object instead.
+#[derive(Clone)]
+pub struct MediaWiki {
+    name: Cow<'static, str>,
+    api_url: String,
+    article_url: String,
+    /// The language the wiki is written in, when known; for a family of
+    /// wikis (see [`MediaWiki::new`]), the edition searched by default.
+    language: Option<String>,
+}
+
+impl MediaWiki {
+    /// The name [`MediaWiki::wikipedia`] reports.
+    pub const WIKIPEDIA: &'static str = "Wikipedia";
+
+    /// `api_url` is the site's `api.php` (e.g.
+    /// `https://wiki.example.com/w/api.php`); `article_url` is the prefix a
+    /// page title is appended to to link to it (e.g.
+    /// `https://wiki.example.com/wiki/`). A `{lang}` in both makes it a
+    /// family of per-language wikis, searched in the search's language or
+    /// else in [`language`](Self::language).
+    pub fn new(
+        name: impl Into<Cow<'static, str>>,
+        api_url: impl Into<String>,
+        article_url: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            api_url: api_url.into(),
+            article_url: article_url.into(),
+            language: None,
+        }
+    }
+
+    /// The wiki's language, e.g. `"de"`.
+    pub fn language(mut self, lang: impl Into<String>) -> Self {
+        self.language = Some(lang.into());
+        self
+    }
+
+    /// Wikipedia, searched in the search's language or else in `lang`
+    /// (e.g. `"en"` or `"de"`).
+    pub fn wikipedia(lang: &str) -> Self {
+        Self::wikipedia_at("https://{lang}.wikipedia.org", lang)
+    }
+
+    /// Like [`wikipedia`](Self::wikipedia), for a mirror or another wiki
+    /// family laid out the same way: `base_url` is the site root, with
+    /// `{lang}` where the language edition goes.
+    pub fn wikipedia_at(base_url: &str, lang: &str) -> Self {
+        let base_url = base_url.trim_end_matches('/');
+        Self::new(
+            Self::WIKIPEDIA,
+            format!("{base_url}/w/api.php"),
+            format!("{base_url}/wiki/"),
+        )
+        .language(lang)
+    }
+
+    fn is_family(&self) -> bool {
+        self.api_url.contains("{lang}")
+    }
+
+    /// The edition of a wiki family matching `params`' language, or just
+    /// this wiki for anything else.
+    fn edition(&self, params: &SearchParams) -> Cow<'_, Self> {
+        match params.language() {
+            Some(lang) if self.is_family() => Cow::Owned(self.clone().language(lang)),
+            _ => Cow::Borrowed(self),
+        }
+    }
+
+    /// `url` with `{lang}` filled in; the language is plain letters (see
+    /// [`SearchParams::language`]), so it's safe in a host name.
+    fn in_language(&self, url: &str) -> String {
+        url.replace("{lang}", self.language.as_deref().unwrap_or_default())
+    }
+
+    fn build_search_url(&self, query: &str, start: usize, count: usize) -> String {
+        let query = utf8_percent_encode(query, NON_ALPHANUMERIC);
+        format!(
+            "{}?action=query&list=search&srsearch={query}&sroffset={start}&srlimit={}\
+             &srprop=snippet&format=json&formatversion=2",
+            self.in_language(&self.api_url),
+            count.clamp(1, MAX_SRLIMIT)
+        )
+    }
+
+    fn article_url(&self, title: &str) -> String {
+        format!(
+            "{}{}",
+            self.in_language(&self.article_url),
+            utf8_percent_encode(&title.replace(' ', "_"), TITLE_ENCODE_SET)
+        )
+    }
+}
+
+impl EngineInfo for MediaWiki {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The search API has no filters to map: a wiki never filters, has no
+    /// date range, and only honors a locale in its own language — any
+    /// language, for a family with an edition per language.
+    fn honors(&self, params: &SearchParams) -> bool {
+        params
+            .language()
+            .is_none_or(|lang| self.is_family() || self.language.as_deref() == Some(&lang))
+            && params.safe_search != SafeSearch::Strict
+            && params.time_range.is_none()
+    }
+}
+
+/// MediaWiki's own cap on `srlimit` for anonymous clients.
+const MAX_SRLIMIT: usize = 50;
+
+/// Characters that have to be escaped in a page title's path segment.
+/// Deliberately leaves `(`, `)`, `,`, `:` etc. alone so links match the
+/// canonical `/wiki/Rust_(programming_language)` form other engines return,
+/// letting the merge cache dedupe them against each other.
+const TITLE_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'&')
+    .add(b'+')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+#[derive(Deserialize)]
+struct ApiResponse {
+    query: Option<ApiQuery>,
+    error: Option<ApiError>,
+}
+
+#[derive(Deserialize)]
+struct ApiQuery {
+    #[serde(default)]
+    search: Vec<ApiHit>,
+}
+
+#[derive(Deserialize)]
+struct ApiHit {
+    title: String,
+    #[serde(default)]
+    snippet: String,
+}
+
+#[derive(Deserialize)]
+struct ApiError {
+    code: String,
+    #[serde(default)]
+    info: String,
+}
+
+/// Snippets come back as HTML (`<span class="searchmatch">` highlights and
+/// entity-escaped text); flattens that to plain text.
+fn strip_html(snippet: &str) -> String {
+    Html::parse_fragment(snippet)
+        .root_element()
+        .text()
+        .collect::<String>()
+}
+
+impl MediaWiki {
+    pub fn parse_search_response(&self, json: &str) -> Result<Vec<RawResult>, EngineError> {
+        let resp: ApiResponse = serde_json::from_str(json).map_err(|e| {
+            EngineError::ParseError(format!("{} response wasn't valid JSON: {e}", self.name))
+        })?;
+
+        if let Some(err) = resp.error {
+            if err.code == "ratelimited" {
+                return Err(EngineError::RateLimited { retry_after: None });
+            }
+            return Err(EngineError::ParseError(format!(
+                "{} API error {}: {}",
+                self.name, err.code, err.info
+            )));
+        }
+
+        Ok(resp
+            .query
+            .map(|q| q.search)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|hit| RawResult {
+                url: self.article_url(&hit.title),
+                description: strip_html(&hit.snippet),
+                title: hit.title,
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl SearchEngine for MediaWiki {
+    async fn search_results(
+        &self,
+        query: &str,
+        start: usize,
+        count: usize,
+        params: &SearchParams,
+    ) -> Result<Vec<RawResult>, EngineError> {
+        let wiki = self.edition(params);
+        let resp = client_for(&self.name)
+            .get(wiki.build_search_url(query, start, count))
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)
+            .and_then(check_status)?;
+
+        let json = resp.text().await.map_err(EngineError::ReqwestError)?;
+        wiki.parse_search_response(&json)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn wikipedia_targets_the_requested_language_edition() {
+        let wiki = MediaWiki::wikipedia("de");
+        assert_eq!(wiki.name(), "Wikipedia");
+        assert_eq!(
+            wiki.build_search_url("rust", 0, 20),
+            "https://de.wikipedia.org/w/api.php?action=query&list=search&srsearch=rust\
+             &sroffset=0&srlimit=20&srprop=snippet&format=json&formatversion=2"
+        );
+    }
+
+    #[test]
+    fn build_search_url_uses_a_custom_base_and_clamps_the_limit() {
+        let wiki = MediaWiki::new(
+            "Team Wiki",
+            "https://wiki.example.com/w/api.php",
+            "https://wiki.example.com/wiki/",
+        );
+        assert_eq!(
+            wiki.build_search_url("café 日本語", 40, 500),
+            "https://wiki.example.com/w/api.php?action=query&list=search\
+             &srsearch=caf%C3%A9%20%E6%97%A5%E6%9C%AC%E8%AA%9E&sroffset=40&srlimit=50\
+             &srprop=snippet&format=json&formatversion=2"
+        );
+    }
+
+    #[test]
+    fn only_a_locale_in_the_wikis_own_language_is_honored() {
+        let locale = |locale: &str| SearchParams {
+            locale: Some(locale.into()),
+            ..Default::default()
+        };
+        let wiki = MediaWiki::new(
+            "German Wiki".to_string(),
+            "https://de.w.example/api.php",
+            "https://de.w.example/",
+        )
+        .language("de");
+        assert!(wiki.honors(&SearchParams::default()));
+        assert!(wiki.honors(&locale("de-AT")));
+        assert!(!wiki.honors(&locale("fr")));
+        let strict = SearchParams {
+            safe_search: SafeSearch::Strict,
+            ..Default::default()
+        };
+        assert!(!wiki.honors(&strict));
+
+        let team = MediaWiki::new(
+            "Team Wiki",
+            "https://w.example/api.php",
+            "https://w.example/",
+        );
+        assert!(!team.honors(&locale("en")));
+        assert!(MediaWiki::wikipedia("de").honors(&locale("fr")));
+    }
+
+    #[test]
+    fn wikipedia_searches_the_edition_in_the_searchs_language() {
+        let wiki = MediaWiki::wikipedia_at("https://{lang}.wiki.example/", "en");
+        let fr = SearchParams {
+            locale: Some("fr-CA".into()),
+            ..Default::default()
+        };
+        let edition = wiki.edition(&fr);
+        assert_eq!(edition.name(), "Wikipedia");
+        assert!(
+            edition
+                .build_search_url("rust", 0, 10)
+                .starts_with("https://fr.wiki.example/w/api.php?")
+        );
+        assert_eq!(
+            edition.article_url("Rust"),
+            "https://fr.wiki.example/wiki/Rust"
+        );
+        let default = wiki.edition(&SearchParams::default());
+        assert_eq!(
+            default.article_url("Rust"),
+            "https://en.wiki.example/wiki/Rust"
+        );
+    }
+
+    #[test]
+    fn article_url_matches_canonical_wikipedia_links() {
+        let wiki = MediaWiki::wikipedia("en");
+        assert_eq!(
+            wiki.article_url("Rust (programming language)"),
+            "https://en.wikipedia.org/wiki/Rust_(programming_language)"
+        );
+        assert_eq!(
+            wiki.article_url("C++"),
+            "https://en.wikipedia.org/wiki/C%2B%2B"
+        );
+        assert_eq!(
+            wiki.article_url("Zürich"),
+            "https://en.wikipedia.org/wiki/Z%C3%BCrich"
+        );
+    }
+
+    const SEARCH_FIXTURE: &str = r#"{
+        "batchcomplete": true,
+        "continue": { "sroffset": 2, "continue": "-||" },
+        "query": {
+            "search": [
+                {
+                    "ns": 0,
+                    "title": "Rust (programming language)",
+                    "pageid": 29414838,
+                    "snippet": "<span class=\"searchmatch\">Rust</span> is a general-purpose programming language &amp; more"
+                },
+                {
+                    "ns": 0,
+                    "title": "Rust",
+                    "pageid": 26477,
+                    "snippet": "<span class=\"searchmatch\">Rust</span> is an iron oxide"
+                }
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn parse_search_response_maps_hits_and_strips_snippet_html() {
+        let results = MediaWiki::wikipedia("en")
+            .parse_search_response(SEARCH_FIXTURE)
+            .unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[0].url,
+            "https://en.wikipedia.org/wiki/Rust_(programming_language)"
+        );
+        assert_eq!(results[0].title, "Rust (programming language)");
+        assert_eq!(
+            results[0].description,
+            "Rust is a general-purpose programming language & more"
+        );
+        assert_eq!(results[1].url, "https://en.wikipedia.org/wiki/Rust");
+    }
+
+    #[test]
+    fn parse_search_response_returns_empty_vec_once_exhausted() {
+        let results = MediaWiki::wikipedia("en")
+            .parse_search_response(r#"{"batchcomplete": true, "query": {"search": []}}"#)
+            .unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn parse_search_response_surfaces_api_errors() {
+        let err = MediaWiki::wikipedia("en")
+            .parse_search_response(
+                r#"{"error": {"code": "badvalue", "info": "Unrecognized value for parameter \"list\"."}}"#,
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("badvalue"));
+    }
+
+    #[test]
+    fn parse_search_response_reports_rate_limiting_as_such() {
+        let err = MediaWiki::wikipedia("en")
+            .parse_search_response(
+                r#"{"error": {"code": "ratelimited", "info": "You've exceeded your rate limit."}}"#,
+            )
+            .unwrap_err();
+        assert!(matches!(err, EngineError::RateLimited { .. }));
+    }
+
+    use crate::fixtures::cached_html;
+
+    #[ignore]
+    #[tokio::test]
+    async fn test_wikipedia_search_live() {
+        let wiki = MediaWiki::wikipedia("en");
+        let json = cached_html(
+            "mediawiki/wikipedia_en_p0.json",
+            &wiki.build_search_url("rust async", 0, 20),
+            |j| j.contains(r#""search""#),
+        )
+        .await;
+        let results = wiki.parse_search_response(&json).unwrap();
+        assert!(!results.is_empty());
+    }
+}
@@ -0,0 +1,289 @@
+use crate::{
+    EngineError, EngineInfo, RawResult, SafeSearch, SearchEngine, SearchParams, TimeRange,
+    check_status, client::client_for, parse_search,
+};
+use async_trait::async_trait;
+use chrono::{Days, Months, NaiveDate, Utc};
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+
+#[derive(Clone)]
+pub struct Mojeek;
+
+impl EngineInfo for Mojeek {
+    fn name(&self) -> &'static str {
+        "Mojeek"
+    }
+}
+
+/// Results per page on Mojeek's web results page (its default; the `s`
+/// param below is a 1-based index of the first result, not a page number).
+const MOJEEK_RESULTS_PER_PAGE: usize = 10;
+
+fn build_search_url(query: &str, start: usize, params: &SearchParams) -> String {
+    let page = start / MOJEEK_RESULTS_PER_PAGE;
+    let query = utf8_percent_encode(query, NON_ALPHANUMERIC);
+    let mut url = format!("https://www.mojeek.com/search?q={query}");
+    if page > 0 {
+        url.push_str(&format!("&s={}", page * MOJEEK_RESULTS_PER_PAGE + 1));
+    }
+    // `lb` only biases towards the language; `arc` does the same by region.
+    if let Some(lang) = params.language() {
+        url.push_str(&format!("&lb={lang}"));
+    }
+    if let Some(region) = params.region() {
+        url.push_str(&format!("&arc={region}"));
+    }
+    match params.safe_search {
+        SafeSearch::Off => url.push_str("&safe=0"),
+        SafeSearch::Moderate => {}
+        SafeSearch::Strict => url.push_str("&safe=1"),
+    }
+    if let Some(range) = params.time_range {
+        url.push_str(&format!("&since={}", since(range, Utc::now().date_naive())));
+    }
+    url
+}
+
+/// Mojeek has no relative time filter, only `since`: the first day, as
+/// `YYYYMMDD`, a result may be from.
+fn since(range: TimeRange, today: NaiveDate) -> String {
+    let first = match range {
+        TimeRange::Day => today.checked_sub_days(Days::new(1)),
+        TimeRange::Week => today.checked_sub_days(Days::new(7)),
+        TimeRange::Month => today.checked_sub_months(Months::new(1)),
+        TimeRange::Year => today.checked_sub_months(Months::new(12)),
+    };
+    first.unwrap_or(today).format("%Y%m%d").to_string()
+}
+
+// A real Mojeek results page either has the results list or, for a query
+// with no hits at all, its "no pages found" notice; the automated-traffic
+// block page has neither. Without this check a block silently parses to an
+// empty Vec, indistinguishable from genuine exhaustion.
+fn looks_like_search_results(html: &str) -> bool {
+    html.contains("results-standard") || html.contains("No pages found matching")
+}
+
+#[async_trait]
+impl SearchEngine for Mojeek {
+    async fn search_results(
+        &self,
+        query: &str,
+        start: usize,
+        _count: usize,
+        params: &SearchParams,
+    ) -> Result<Vec<RawResult>, EngineError> {
+        let resp = client_for(self.name())
+            .get(build_search_url(query, start, params))
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)
+            .and_then(check_status)?;
+
+        let html = resp.text().await.map_err(EngineError::ReqwestError)?;
+        if !looks_like_search_results(&html) {
+            return Err(EngineError::Blocked {
+                reason: "Mojeek response didn't look like real results".into(),
+            });
+        }
+
+        parse_search_response(&html)
+    }
+}
+
+pub fn parse_search_response(html: &str) -> Result<Vec<RawResult>, EngineError> {
+    Ok(parse_search(
+        html,
+        "ul.results-standard > li",
+        "h2 > a.title",
+        "h2 > a.title",
+        "p.s",
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn build_search_url_omits_offset_on_first_page() {
+        assert_eq!(
+            build_search_url("rust async", 0, &SearchParams::default()),
+            "https://www.mojeek.com/search?q=rust%20async"
+        );
+        assert_eq!(
+            build_search_url(
+                "rust async",
+                MOJEEK_RESULTS_PER_PAGE - 1,
+                &SearchParams::default()
+            ),
+            "https://www.mojeek.com/search?q=rust%20async"
+        );
+    }
+
+    #[test]
+    fn build_search_url_adds_one_based_offset_for_later_pages() {
+        assert_eq!(
+            build_search_url(
+                "rust async",
+                MOJEEK_RESULTS_PER_PAGE,
+                &SearchParams::default()
+            ),
+            "https://www.mojeek.com/search?q=rust%20async&s=11"
+        );
+        assert_eq!(
+            build_search_url(
+                "rust async",
+                MOJEEK_RESULTS_PER_PAGE * 2 + 5,
+                &SearchParams::default()
+            ),
+            "https://www.mojeek.com/search?q=rust%20async&s=21"
+        );
+    }
+
+    #[test]
+    fn build_search_url_encodes_reserved_characters() {
+        assert_eq!(
+            build_search_url("AT&T c++ #tag", 0, &SearchParams::default()),
+            "https://www.mojeek.com/search?q=AT%26T%20c%2B%2B%20%23tag"
+        );
+    }
+
+    #[test]
+    fn build_search_url_encodes_non_ascii_query() {
+        assert_eq!(
+            build_search_url("café 日本語", 0, &SearchParams::default()),
+            "https://www.mojeek.com/search?q=caf%C3%A9%20%E6%97%A5%E6%9C%AC%E8%AA%9E"
+        );
+    }
+
+    #[test]
+    fn build_search_url_maps_every_param() {
+        let params = SearchParams {
+            locale: Some("en-GB".into()),
+            safe_search: SafeSearch::Strict,
+            time_range: None,
+        };
+        assert_eq!(
+            build_search_url("rust", 0, &params),
+            "https://www.mojeek.com/search?q=rust&lb=en&arc=gb&safe=1"
+        );
+        let params = SearchParams {
+            safe_search: SafeSearch::Off,
+            time_range: Some(TimeRange::Week),
+            ..Default::default()
+        };
+        let url = build_search_url("rust", 0, &params);
+        assert!(url.starts_with("https://www.mojeek.com/search?q=rust&safe=0&since="));
+    }
+
+    #[test]
+    fn since_counts_back_from_today() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 31).unwrap();
+        assert_eq!(since(TimeRange::Day, today), "20240330");
+        assert_eq!(since(TimeRange::Week, today), "20240324");
+        assert_eq!(since(TimeRange::Month, today), "20240229");
+        assert_eq!(since(TimeRange::Year, today), "20230331");
+    }
+
+    #[test]
+    fn looks_like_search_results_rejects_a_block_page() {
+        assert!(looks_like_search_results(
+            r#"<ul class="results-standard">...</ul>"#
+        ));
+        assert!(looks_like_search_results(
+            "<p>No pages found matching: <b>zzqxj</b></p>"
+        ));
+        assert!(!looks_like_search_results(
+            "<html><body>Sorry, your network appears to be sending automated queries</body></html>"
+        ));
+    }
+
+    const SEARCH_FIXTURE: &str = r#"
+        <ul class="results-standard">
+            <li class="r1">
+                <a class="ob" href="https://example.com/rust">example.com/rust</a>
+                <h2><a class="title" href="https://example.com/rust">Rust Programming Language</a></h2>
+                <p class="s">A systems language for reliable software.</p>
+            </li>
+            <li class="r2">
+                <a class="ob" href="https://example.com/book">example.com/book</a>
+                <h2><a class="title" href="https://example.com/book">The Rust Book</a></h2>
+                <p class="s">Learn Rust from the ground up.</p>
+            </li>
+        </ul>
+    "#;
+
+    #[test]
+    fn parse_search_response_extracts_results() {
+        let results = parse_search_response(SEARCH_FIXTURE).unwrap();
+
+        assert_eq!(results.len(), 2);
+
+        assert_eq!(results[0].url, "https://example.com/rust");
+        assert_eq!(results[0].title, "Rust Programming Language");
+        assert_eq!(
+            results[0].description,
+            "A systems language for reliable software."
+        );
+
+        assert_eq!(results[1].url, "https://example.com/book");
+        assert_eq!(results[1].title, "The Rust Book");
+        assert_eq!(results[1].description, "Learn Rust from the ground up.");
+    }
+
+    #[test]
+    fn parse_search_response_returns_empty_vec_for_a_no_hits_page() {
+        let results =
+            parse_search_response("<p>No pages found matching: <b>zzqxj</b></p>").unwrap();
+        assert!(results.is_empty());
+    }
+
+    use crate::fixtures::cached_html;
+
+    #[ignore]
+    #[tokio::test]
+    async fn test_mojeek_search_live() {
+        let html = cached_html(
+            "mojeek/search_p0.html",
+            &build_search_url("rust async", 0, &SearchParams::default()),
+            looks_like_search_results,
+        )
+        .await;
+        let results = parse_search_response(&html).unwrap();
+        assert!(!results.is_empty());
+    }
+
+    #[ignore]
+    #[tokio::test]
+    async fn test_mojeek_search_pagination_live() {
+        let page1_html = cached_html(
+            "mojeek/search_p0.html",
+            &build_search_url("rust async", 0, &SearchParams::default()),
+            looks_like_search_results,
+        )
+        .await;
+        let page2_html = cached_html(
+            "mojeek/search_p1.html",
+            &build_search_url(
+                "rust async",
+                MOJEEK_RESULTS_PER_PAGE,
+                &SearchParams::default(),
+            ),
+            looks_like_search_results,
+        )
+        .await;
+
+        let page1 = parse_search_response(&page1_html).unwrap();
+        let page2 = parse_search_response(&page2_html).unwrap();
+
+        assert!(!page1.is_empty());
+        assert!(!page2.is_empty());
+        assert!(
+            page1
+                .iter()
+                .all(|r| !page2.iter().any(|r2| r2.url == r.url)),
+            "page 2 should not repeat page 1's results"
+        );
+    }
+}
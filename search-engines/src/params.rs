@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// Per-search knobs every engine is handed alongside the query. Each adapter
+/// maps what it supports onto its own URL parameters and ignores the rest;
+/// only values that differ from an engine's own default are sent, so
+/// `SearchParams::default()` requests exactly what a bare query would.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SearchParams {
+    /// Language with an optional region, e.g. `"en-US"`, `"de_AT"` or just
+    /// `"fr"`. `None` leaves both to the engine.
+    pub locale: Option<String>,
+    pub safe_search: SafeSearch,
+    pub time_range: Option<TimeRange>,
+}
+
+impl SearchParams {
+    /// Lowercase language subtag of [`locale`](Self::locale), e.g. `"en"`.
+    pub fn language(&self) -> Option<String> {
+        self.locale_parts().map(|(lang, _)| lang)
+    }
+
+    /// Lowercase region subtag of [`locale`](Self::locale), e.g. `"us"`.
+    pub fn region(&self) -> Option<String> {
+        self.locale_parts().and_then(|(_, region)| region)
+    }
+
+    /// Splits the locale, rejecting anything that isn't plain letters so a
+    /// caller-supplied value can't smuggle extra URL params into a request.
+    fn locale_parts(&self) -> Option<(String, Option<String>)> {
+        let locale = self.locale.as_deref()?.trim();
+        let mut parts = locale.split(['-', '_']);
+        let lang = parts.next().filter(|l| is_subtag(l, 2..=3))?;
+        let region = match parts.next() {
+            Some(r) if is_subtag(r, 2..=2) => Some(r.to_ascii_lowercase()),
+            Some(_) => return None,
+            None => None,
+        };
+        if parts.next().is_some() {
+            return None;
+        }
+        Some((lang.to_ascii_lowercase(), region))
+    }
+}
+
+fn is_subtag(s: &str, len: std::ops::RangeInclusive<usize>) -> bool {
+    len.contains(&s.len()) && s.bytes().all(|b| b.is_ascii_alphabetic())
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SafeSearch {
+    Off,
+    /// What every supported engine does when not told otherwise.
+    #[default]
+    Moderate,
+    Strict,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeRange {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+/// Returned when parsing a [`SafeSearch`] or [`TimeRange`] from a string
+/// (e.g. a URL query param) that isn't one of its lowercase names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownParam(pub String);
+
+impl fmt::Display for UnknownParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown search parameter value {:?}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownParam {}
+
+impl FromStr for SafeSearch {
+    type Err = UnknownParam;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(SafeSearch::Off),
+            "moderate" => Ok(SafeSearch::Moderate),
+            "strict" => Ok(SafeSearch::Strict),
+            _ => Err(UnknownParam(s.to_string())),
+        }
+    }
+}
+
+impl FromStr for TimeRange {
+    type Err = UnknownParam;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "day" => Ok(TimeRange::Day),
+            "week" => Ok(TimeRange::Week),
+            "month" => Ok(TimeRange::Month),
+            "year" => Ok(TimeRange::Year),
+            _ => Err(UnknownParam(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn with_locale(locale: &str) -> SearchParams {
+        SearchParams {
+            locale: Some(locale.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn locale_splits_into_lowercase_language_and_region() {
+        let params = with_locale("en-US");
+        assert_eq!(params.language().as_deref(), Some("en"));
+        assert_eq!(params.region().as_deref(), Some("us"));
+
+        let params = with_locale("de_AT");
+        assert_eq!(params.language().as_deref(), Some("de"));
+        assert_eq!(params.region().as_deref(), Some("at"));
+    }
+
+    #[test]
+    fn locale_without_a_region_only_has_a_language() {
+        let params = with_locale("fr");
+        assert_eq!(params.language().as_deref(), Some("fr"));
+        assert_eq!(params.region(), None);
+    }
+
+    #[test]
+    fn malformed_locales_are_ignored_entirely() {
+        for locale in ["", "e", "en-USA", "en&kp=-2", "en-US-x", "日本"] {
+            let params = with_locale(locale);
+            assert_eq!(params.language(), None, "{locale:?}");
+            assert_eq!(params.region(), None, "{locale:?}");
+        }
+    }
+
+    #[test]
+    fn safe_search_and_time_range_parse_their_lowercase_names() {
+        assert_eq!("strict".parse(), Ok(SafeSearch::Strict));
+        assert_eq!("week".parse(), Ok(TimeRange::Week));
+        assert!("Strict".parse::<SafeSearch>().is_err());
+        assert!("fortnight".parse::<TimeRange>().is_err());
+    }
+}
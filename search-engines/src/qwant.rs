@@ -0,0 +1,431 @@
+use crate::{
+    EngineError, EngineInfo, ImageEngine, RawImage, RawResult, SafeSearch, SearchEngine,
+    SearchParams, TimeRange, check_status, client::client_for,
+};
+use async_trait::async_trait;
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use serde::Deserialize;
+
+#[derive(Clone)]
+pub struct Qwant;
+
+impl EngineInfo for Qwant {
+    fn name(&self) -> &'static str {
+        "Qwant"
+    }
+
+    /// See [`locale`]: a language without a region can't be applied.
+    fn honors(&self, params: &SearchParams) -> bool {
+        params.language().is_none() || params.region().is_some()
+    }
+}
+
+/// Qwant's web endpoint rejects any `count` above 10.
+const QWANT_WEB_PAGE_SIZE: usize = 10;
+/// Qwant's image endpoint rejects any `count` above 50.
+const QWANT_IMAGE_PAGE_SIZE: usize = 50;
+
+fn build_search_url(query: &str, start: usize, params: &SearchParams) -> String {
+    let query = utf8_percent_encode(query, NON_ALPHANUMERIC);
+    let mut url = format!(
+        "https://api.qwant.com/v3/search/web?q={query}&count={QWANT_WEB_PAGE_SIZE}\
+         &offset={start}&locale={}&device=desktop",
+        locale(params)
+    );
+    push_safesearch(&mut url, params);
+    if let Some(range) = params.time_range {
+        let freshness = match range {
+            TimeRange::Day => "day",
+            TimeRange::Week => "week",
+            TimeRange::Month => "month",
+            TimeRange::Year => "year",
+        };
+        url.push_str(&format!("&freshness={freshness}"));
+    }
+    url
+}
+
+fn build_image_search_url(query: &str, start: usize, params: &SearchParams) -> String {
+    let query = utf8_percent_encode(query, NON_ALPHANUMERIC);
+    let mut url = format!(
+        "https://api.qwant.com/v3/search/images?q={query}&count={QWANT_IMAGE_PAGE_SIZE}\
+         &offset={start}&locale={}&device=desktop",
+        locale(params)
+    );
+    push_safesearch(&mut url, params);
+    url
+}
+
+/// Qwant only accepts full `lang_REGION` locales, so a locale without a
+/// region keeps the `en_US` default (and Qwant sits such a search out).
+fn locale(params: &SearchParams) -> String {
+    match (params.language(), params.region()) {
+        (Some(lang), Some(region)) => format!("{lang}_{}", region.to_ascii_uppercase()),
+        _ => "en_US".to_string(),
+    }
+}
+
+/// `safesearch` is 0 (off), 1 (moderate, the default) or 2 (strict).
+fn push_safesearch(url: &mut String, params: &SearchParams) {
+    match params.safe_search {
+        SafeSearch::Off => url.push_str("&safesearch=0"),
+        SafeSearch::Moderate => {}
+        SafeSearch::Strict => url.push_str("&safesearch=2"),
+    }
+}
+
+/// Qwant's JSON envelope. Both verticals share it; only the shape of
+/// `data.result.items` differs, so it's left generic.
+#[derive(Deserialize)]
+struct Envelope<T> {
+    status: String,
+    data: Option<EnvelopeData<T>>,
+}
+
+#[derive(Deserialize)]
+struct EnvelopeData<T> {
+    result: Option<EnvelopeResult<T>>,
+    /// Set instead of `result` on failures — e.g. 27 for the captcha wall.
+    error_code: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct EnvelopeResult<T> {
+    items: T,
+}
+
+#[derive(Deserialize)]
+struct WebItems {
+    #[serde(default)]
+    mainline: Vec<MainlineBlock>,
+}
+
+/// The web mainline interleaves organic blocks (`"web"`) with ads, videos,
+/// news carousels etc.; only `"web"` blocks are real organic results.
+#[derive(Deserialize)]
+struct MainlineBlock {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    items: Vec<WebItem>,
+}
+
+#[derive(Deserialize)]
+struct WebItem {
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    desc: String,
+}
+
+#[derive(Deserialize)]
+struct ImageItem {
+    #[serde(default)]
+    media: String,
+    #[serde(default)]
+    title: String,
+}
+
+/// Unwraps the envelope, turning Qwant's in-band error reports into an
+/// `Err` instead of an empty (and therefore "exhausted") page. A missing
+/// `result` on a successful response is genuine exhaustion.
+fn parse_envelope<T: serde::de::DeserializeOwned>(json: &str) -> Result<Option<T>, EngineError> {
+    let envelope: Envelope<T> = serde_json::from_str(json)
+        .map_err(|e| EngineError::ParseError(format!("Qwant response wasn't valid JSON: {e}")))?;
+
+    if envelope.status != "success" {
+        let code = envelope.data.and_then(|d| d.error_code);
+        return Err(EngineError::Blocked {
+            reason: format!(
+                "Qwant returned an error (code {})",
+                code.map_or_else(|| "unknown".to_string(), |c| c.to_string())
+            ),
+        });
+    }
+
+    Ok(envelope
+        .data
+        .and_then(|d| d.result)
+        .map(|result| result.items))
+}
+
+pub fn parse_search_response(json: &str) -> Result<Vec<RawResult>, EngineError> {
+    let Some(items) = parse_envelope::<WebItems>(json)? else {
+        return Ok(Vec::new());
+    };
+
+    Ok(items
+        .mainline
+        .into_iter()
+        .filter(|block| block.kind == "web")
+        .flat_map(|block| block.items)
+        .map(|item| RawResult {
+            url: item.url,
+            title: item.title,
+            description: item.desc,
+        })
+        .collect())
+}
+
+pub fn parse_image_response(json: &str) -> Result<Vec<RawImage>, EngineError> {
+    let Some(items) = parse_envelope::<Vec<ImageItem>>(json)? else {
+        return Ok(Vec::new());
+    };
+
+    Ok(items
+        .into_iter()
+        .map(|item| RawImage {
+            url: item.media,
+            title: item.title,
+        })
+        .collect())
+}
+
+#[async_trait]
+impl SearchEngine for Qwant {
+    async fn search_results(
+        &self,
+        query: &str,
+        start: usize,
+        _count: usize,
+        params: &SearchParams,
+    ) -> Result<Vec<RawResult>, EngineError> {
+        let resp = client_for(self.name())
+            .get(build_search_url(query, start, params))
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)
+            .and_then(check_status)?;
+
+        let json = resp.text().await.map_err(EngineError::ReqwestError)?;
+        parse_search_response(&json)
+    }
+}
+
+#[async_trait]
+impl ImageEngine for Qwant {
+    /// The image endpoint takes no `freshness`.
+    fn honors_images(&self, params: &SearchParams) -> bool {
+        self.honors(params) && params.time_range.is_none()
+    }
+
+    async fn search_images(
+        &self,
+        query: &str,
+        start: usize,
+        _count: usize,
+        params: &SearchParams,
+    ) -> Result<Vec<RawImage>, EngineError> {
+        let resp = client_for(self.name())
+            .get(build_image_search_url(query, start, params))
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)
+            .and_then(check_status)?;
+
+        let json = resp.text().await.map_err(EngineError::ReqwestError)?;
+        parse_image_response(&json)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn build_search_url_passes_start_as_offset() {
+        assert_eq!(
+            build_search_url("rust async", 0, &SearchParams::default()),
+            "https://api.qwant.com/v3/search/web?q=rust%20async&count=10&offset=0&locale=en_US&device=desktop"
+        );
+        assert_eq!(
+            build_search_url("rust async", 20, &SearchParams::default()),
+            "https://api.qwant.com/v3/search/web?q=rust%20async&count=10&offset=20&locale=en_US&device=desktop"
+        );
+    }
+
+    #[test]
+    fn build_image_search_url_passes_start_as_offset() {
+        assert_eq!(
+            build_image_search_url("rust", 50, &SearchParams::default()),
+            "https://api.qwant.com/v3/search/images?q=rust&count=50&offset=50&locale=en_US&device=desktop"
+        );
+    }
+
+    #[test]
+    fn build_search_url_maps_params_to_locale_safesearch_and_freshness() {
+        let params = SearchParams {
+            locale: Some("de-at".into()),
+            safe_search: SafeSearch::Strict,
+            time_range: Some(TimeRange::Year),
+        };
+        assert_eq!(
+            build_search_url("rust", 0, &params),
+            "https://api.qwant.com/v3/search/web?q=rust&count=10&offset=0&locale=de_AT&device=desktop&safesearch=2&freshness=year"
+        );
+        assert_eq!(
+            build_image_search_url("rust", 0, &params),
+            "https://api.qwant.com/v3/search/images?q=rust&count=50&offset=0&locale=de_AT&device=desktop&safesearch=2"
+        );
+    }
+
+    #[test]
+    fn a_locale_without_a_region_or_dated_images_are_not_honored() {
+        let params = |locale: &str| SearchParams {
+            locale: Some(locale.into()),
+            ..Default::default()
+        };
+        assert!(Qwant.honors(&params("fr-FR")));
+        assert!(!Qwant.honors(&params("fr")));
+        assert!(Qwant.honors(&SearchParams::default()));
+
+        let dated = SearchParams {
+            time_range: Some(TimeRange::Day),
+            ..Default::default()
+        };
+        assert!(Qwant.honors(&dated));
+        assert!(!Qwant.honors_images(&dated));
+        assert!(Qwant.honors_images(&params("fr-FR")));
+    }
+
+    #[test]
+    fn build_search_url_encodes_non_ascii_query() {
+        assert!(
+            build_search_url("café 日本語", 0, &SearchParams::default())
+                .contains("q=caf%C3%A9%20%E6%97%A5%E6%9C%AC%E8%AA%9E&")
+        );
+    }
+
+    const SEARCH_FIXTURE: &str = r#"{
+        "status": "success",
+        "data": {
+            "result": {
+                "items": {
+                    "mainline": [
+                        {
+                            "type": "ads",
+                            "items": [
+                                { "url": "https://example.com/ad", "title": "Sponsored", "desc": "Buy now." }
+                            ]
+                        },
+                        {
+                            "type": "web",
+                            "items": [
+                                { "url": "https://example.com/rust", "title": "Rust Programming Language", "desc": "A systems language." },
+                                { "url": "https://example.com/book", "title": "The Rust Book", "desc": "Learn Rust." }
+                            ]
+                        },
+                        { "type": "videos", "items": [] }
+                    ]
+                }
+            }
+        }
+    }"#;
+
+    #[test]
+    fn parse_search_response_keeps_only_organic_web_blocks() {
+        let results = parse_search_response(SEARCH_FIXTURE).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].url, "https://example.com/rust");
+        assert_eq!(results[0].title, "Rust Programming Language");
+        assert_eq!(results[0].description, "A systems language.");
+        assert_eq!(results[1].url, "https://example.com/book");
+        assert!(results.iter().all(|r| r.url != "https://example.com/ad"));
+    }
+
+    #[test]
+    fn parse_search_response_returns_empty_vec_once_exhausted() {
+        let results = parse_search_response(r#"{"status": "success", "data": {}}"#).unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn parse_search_response_rejects_an_error_envelope() {
+        let err = parse_search_response(r#"{"status": "error", "data": {"error_code": 27}}"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("27"));
+    }
+
+    #[test]
+    fn parse_search_response_rejects_non_json_block_page() {
+        assert!(parse_search_response("<html>captcha</html>").is_err());
+    }
+
+    const IMAGE_FIXTURE: &str = r#"{
+        "status": "success",
+        "data": {
+            "result": {
+                "items": [
+                    { "media": "https://imgs.example.com/rust-logo.png", "title": "Rust Logo", "thumbnail": "https://s.qwant.com/t/1" },
+                    { "media": "https://imgs.example.com/ferris.png", "title": "Ferris the Crab" }
+                ]
+            }
+        }
+    }"#;
+
+    #[test]
+    fn parse_image_response_extracts_images() {
+        let images = parse_image_response(IMAGE_FIXTURE).unwrap();
+
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[0].url, "https://imgs.example.com/rust-logo.png");
+        assert_eq!(images[0].title, "Rust Logo");
+        assert_eq!(images[1].url, "https://imgs.example.com/ferris.png");
+        assert_eq!(images[1].title, "Ferris the Crab");
+    }
+
+    use crate::fixtures::cached_html;
+
+    fn looks_like_success(json: &str) -> bool {
+        json.contains(r#""status":"success""#)
+    }
+
+    #[ignore]
+    #[tokio::test]
+    async fn test_qwant_search_live() {
+        let json = cached_html(
+            "qwant/search_p0.json",
+            &build_search_url("rust async", 0, &SearchParams::default()),
+            looks_like_success,
+        )
+        .await;
+        let results = parse_search_response(&json).unwrap();
+        assert!(!results.is_empty());
+    }
+
+    #[ignore]
+    #[tokio::test]
+    async fn test_qwant_images_pagination_live() {
+        let page1_json = cached_html(
+            "qwant/images_p0.json",
+            &build_image_search_url("rust async", 0, &SearchParams::default()),
+            looks_like_success,
+        )
+        .await;
+        let page2_json = cached_html(
+            "qwant/images_p1.json",
+            &build_image_search_url(
+                "rust async",
+                QWANT_IMAGE_PAGE_SIZE,
+                &SearchParams::default(),
+            ),
+            looks_like_success,
+        )
+        .await;
+
+        let page1 = parse_image_response(&page1_json).unwrap();
+        let page2 = parse_image_response(&page2_json).unwrap();
+
+        assert!(!page1.is_empty());
+        assert!(!page2.is_empty());
+        assert!(
+            page1
+                .iter()
+                .all(|r| !page2.iter().any(|r2| r2.url == r.url)),
+            "image page 2 should not repeat page 1's results"
+        );
+    }
+}
@@ -0,0 +1,410 @@
+use crate::{
+    EngineError, EngineInfo, RawResult, SafeSearch, SearchEngine, SearchParams, TimeRange,
+    TokenStore, check_status, client::client_for, parse_search,
+};
+use async_trait::async_trait;
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use scraper::{Html, Selector};
+use std::sync::LazyLock;
+
+#[derive(Clone)]
+pub struct Startpage;
+
+impl EngineInfo for Startpage {
+    fn name(&self) -> &'static str {
+        "Startpage"
+    }
+
+    /// Startpage picks its index by language alone; a region narrows
+    /// nothing, but a language it has no name for can't be asked for.
+    fn honors(&self, params: &SearchParams) -> bool {
+        params
+            .language()
+            .is_none_or(|lang| language_name(&lang).is_some())
+    }
+}
+
+const SEARCH_ENDPOINT: &str = "https://www.startpage.com/sp/search";
+
+/// How many pages [`Startpage`] will walk forward from page 1 to recover a
+/// lost pagination token (see [`fetch_page_at`]) before giving up.
+const MAX_TOKEN_WALK: usize = 10;
+
+/// Hidden-form fields (`query`, `page`, `sc`, ...) that request one
+/// specific Startpage results page.
+type PageForm = Vec<(String, String)>;
+
+/// Next-page forms scraped off previously fetched pages, keyed by the
+/// search's first-page URL (query and params) and the `start` they lead to.
+/// Startpage's pagination is driven by a session-bound `sc` token in each
+/// page's hidden form rather than a plain offset param, so the only way to
+/// request page N+1 is to replay the form found on page N. Keying by
+/// `start` lines up with the cache layer, which always asks for the next
+/// page at exactly `previous start + rows returned`.
+static NEXT_PAGE_FORMS: LazyLock<TokenStore<(String, usize), PageForm>> =
+    LazyLock::new(|| TokenStore::new(1024));
+
+/// Later pages replay the form found on this one, which carries the same
+/// filters, so only the first request needs them.
+fn build_first_page_url(query: &str, params: &SearchParams) -> String {
+    let query = utf8_percent_encode(query, NON_ALPHANUMERIC);
+    let mut url = format!("{SEARCH_ENDPOINT}?query={query}");
+    if let Some(language) = params.language().and_then(|lang| language_name(&lang)) {
+        url.push_str(&format!("&language={language}"));
+    }
+    match params.safe_search {
+        SafeSearch::Off => url.push_str("&qadf=none"),
+        SafeSearch::Moderate => {}
+        SafeSearch::Strict => url.push_str("&qadf=heavy"),
+    }
+    if let Some(range) = params.time_range {
+        let with_date = match range {
+            TimeRange::Day => "d",
+            TimeRange::Week => "w",
+            TimeRange::Month => "m",
+            TimeRange::Year => "y",
+        };
+        url.push_str(&format!("&with_date={with_date}"));
+    }
+    url
+}
+
+/// Startpage's `language` values are the languages' own names, not codes.
+fn language_name(lang: &str) -> Option<&'static str> {
+    Some(match lang {
+        "da" => "dansk",
+        "de" => "deutsch",
+        "en" => "english",
+        "es" => "espanol",
+        "fi" => "suomi",
+        "fr" => "francais",
+        "it" => "italiano",
+        "nl" => "nederlands",
+        "no" | "nb" => "norsk",
+        "pl" => "polski",
+        "pt" => "portugues",
+        "sv" => "svenska",
+        _ => return None,
+    })
+}
+
+// A real results page always has the `w-gl` results wrapper, or, for a query
+// with no hits at all, Startpage's "did not match" notice; the captcha
+// interstitial has neither. Without this check a block silently parses to
+// an empty Vec, indistinguishable from genuine exhaustion.
+fn looks_like_search_results(html: &str) -> bool {
+    html.contains(r#"class="w-gl"#) || html.contains("did not match any")
+}
+
+/// Extracts the hidden form that requests page `page` (1-based) from a
+/// results page's pagination controls. Only forms carrying an `sc` token
+/// count — without it Startpage treats the request as a brand new search.
+fn extract_page_form(html: &str, page: usize) -> Option<PageForm> {
+    let html = Html::parse_document(html);
+    let form_selector = Selector::parse(r#"form[action*="/sp/search"]"#).ok()?;
+    let input_selector = Selector::parse("input[type=hidden][name]").ok()?;
+    let page = page.to_string();
+
+    html.select(&form_selector)
+        .map(|form| {
+            form.select(&input_selector)
+                .filter_map(|input| {
+                    let name = input.value().attr("name")?;
+                    let value = input.value().attr("value").unwrap_or_default();
+                    Some((name.to_string(), value.to_string()))
+                })
+                .collect::<PageForm>()
+        })
+        .find(|fields| {
+            fields.iter().any(|(k, v)| k == "page" && *v == page)
+                && fields.iter().any(|(k, v)| k == "sc" && !v.is_empty())
+        })
+}
+
+async fn fetch_first_page(url: &str) -> Result<String, EngineError> {
+    let resp = client_for(Startpage.name())
+        .get(url)
+        .send()
+        .await
+        .map_err(EngineError::ReqwestError)
+        .and_then(check_status)?;
+    checked_html(resp).await
+}
+
+async fn fetch_form(form: &PageForm) -> Result<String, EngineError> {
+    let resp = client_for(Startpage.name())
+        .post(SEARCH_ENDPOINT)
+        .form(form)
+        .send()
+        .await
+        .map_err(EngineError::ReqwestError)
+        .and_then(check_status)?;
+    checked_html(resp).await
+}
+
+async fn checked_html(resp: reqwest::Response) -> Result<String, EngineError> {
+    let html = resp.text().await.map_err(EngineError::ReqwestError)?;
+    if !looks_like_search_results(&html) {
+        return Err(EngineError::Blocked {
+            reason: "Startpage response didn't look like real results".into(),
+        });
+    }
+    Ok(html)
+}
+
+/// Which 1-based Startpage page a form requests, so the form found on it
+/// for the *following* page can be picked out.
+fn form_page(form: &PageForm) -> usize {
+    form.iter()
+        .find(|(k, _)| k == "page")
+        .and_then(|(_, v)| v.parse().ok())
+        .unwrap_or(1)
+}
+
+/// Parses one fetched page and remembers its next-page form under the
+/// `start` it leads to. `search` is the search's first-page URL.
+fn record_page(
+    search: &str,
+    start: usize,
+    page: usize,
+    html: &str,
+) -> Result<Vec<RawResult>, EngineError> {
+    let results = parse_search_response(html)?;
+    if !results.is_empty()
+        && let Some(next) = extract_page_form(html, page + 1)
+    {
+        NEXT_PAGE_FORMS.insert((search.to_string(), start + results.len()), next);
+    }
+    Ok(results)
+}
+
+/// Fetches the page beginning at `start`. Normally that's one request,
+/// replaying the form stored by the previous page; if the token was lost
+/// (evicted, or the process restarted while the cache layer kept its
+/// progress), walks forward from page 1 re-collecting tokens instead.
+async fn fetch_page_at(
+    query: &str,
+    start: usize,
+    params: &SearchParams,
+) -> Result<Vec<RawResult>, EngineError> {
+    let search = build_first_page_url(query, params);
+    if start == 0 {
+        let html = fetch_first_page(&search).await?;
+        return record_page(&search, 0, 1, &html);
+    }
+
+    if let Some(form) = NEXT_PAGE_FORMS.get(&(search.clone(), start)) {
+        let html = fetch_form(&form).await?;
+        return record_page(&search, start, form_page(&form), &html);
+    }
+
+    let mut offset = 0;
+    let mut html = fetch_first_page(&search).await?;
+    let mut results = record_page(&search, offset, 1, &html)?;
+    for page in 2..=MAX_TOKEN_WALK {
+        if results.is_empty() {
+            return Ok(Vec::new());
+        }
+        offset += results.len();
+        if offset > start {
+            break;
+        }
+        let Some(form) = NEXT_PAGE_FORMS.get(&(search.clone(), offset)) else {
+            // Page `page - 1` had no next-page form — the real end of results.
+            return Ok(Vec::new());
+        };
+        html = fetch_form(&form).await?;
+        results = record_page(&search, offset, page, &html)?;
+        if offset == start {
+            return Ok(results);
+        }
+    }
+
+    Err(EngineError::ParseError(format!(
+        "Startpage pagination token for offset {start} couldn't be recovered"
+    )))
+}
+
+#[async_trait]
+impl SearchEngine for Startpage {
+    /// `count` is unused: Startpage's page size is fixed, and pages can only
+    /// be reached by following each page's hidden next-page form in order.
+    async fn search_results(
+        &self,
+        query: &str,
+        start: usize,
+        _count: usize,
+        params: &SearchParams,
+    ) -> Result<Vec<RawResult>, EngineError> {
+        fetch_page_at(query, start, params).await
+    }
+}
+
+pub fn parse_search_response(html: &str) -> Result<Vec<RawResult>, EngineError> {
+    Ok(parse_search(
+        html,
+        ".w-gl .result",
+        ".wgl-title",
+        "a.result-link",
+        "p.description",
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn build_first_page_url_encodes_reserved_characters() {
+        assert_eq!(
+            build_first_page_url("AT&T c++ #tag", &SearchParams::default()),
+            "https://www.startpage.com/sp/search?query=AT%26T%20c%2B%2B%20%23tag"
+        );
+    }
+
+    #[test]
+    fn build_first_page_url_encodes_non_ascii_query() {
+        assert_eq!(
+            build_first_page_url("café 日本語", &SearchParams::default()),
+            "https://www.startpage.com/sp/search?query=caf%C3%A9%20%E6%97%A5%E6%9C%AC%E8%AA%9E"
+        );
+    }
+
+    #[test]
+    fn build_first_page_url_maps_every_param() {
+        let params = SearchParams {
+            locale: Some("de-AT".into()),
+            safe_search: SafeSearch::Strict,
+            time_range: Some(TimeRange::Month),
+        };
+        assert_eq!(
+            build_first_page_url("rust", &params),
+            "https://www.startpage.com/sp/search?query=rust&language=deutsch&qadf=heavy&with_date=m"
+        );
+    }
+
+    #[test]
+    fn a_language_without_a_startpage_name_is_not_honored() {
+        let params = |locale: &str| SearchParams {
+            locale: Some(locale.into()),
+            ..Default::default()
+        };
+        assert!(Startpage.honors(&params("en-US")));
+        assert!(!Startpage.honors(&params("ja")));
+        assert!(Startpage.honors(&SearchParams::default()));
+    }
+
+    #[test]
+    fn looks_like_search_results_rejects_a_block_page() {
+        assert!(looks_like_search_results(
+            r#"<section class="w-gl">...</section>"#
+        ));
+        assert!(looks_like_search_results(
+            "<p>Your search did not match any documents.</p>"
+        ));
+        assert!(!looks_like_search_results(
+            "<html><body>Please complete the captcha</body></html>"
+        ));
+    }
+
+    const SEARCH_FIXTURE: &str = r#"
+        <section class="w-gl">
+            <div class="result">
+                <a class="result-link" href="https://example.com/rust">
+                    <h2 class="wgl-title">Rust Programming Language</h2>
+                </a>
+                <p class="description">A systems language for reliable software.</p>
+            </div>
+            <div class="result">
+                <a class="result-link" href="https://example.com/book">
+                    <h2 class="wgl-title">The Rust Book</h2>
+                </a>
+                <p class="description">Learn Rust from the ground up.</p>
+            </div>
+        </section>
+        <div class="pagination">
+            <form action="/sp/search" method="post">
+                <input type="hidden" name="query" value="rust">
+                <input type="hidden" name="page" value="1">
+                <input type="hidden" name="sc" value="prevtoken">
+            </form>
+            <form action="/sp/search" method="post">
+                <input type="hidden" name="query" value="rust">
+                <input type="hidden" name="page" value="3">
+                <input type="hidden" name="sc" value="nexttoken">
+                <input type="hidden" name="cat" value="web">
+                <button class="next">Next</button>
+            </form>
+        </div>
+    "#;
+
+    #[test]
+    fn parse_search_response_extracts_results() {
+        let results = parse_search_response(SEARCH_FIXTURE).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].url, "https://example.com/rust");
+        assert_eq!(results[0].title, "Rust Programming Language");
+        assert_eq!(
+            results[0].description,
+            "A systems language for reliable software."
+        );
+        assert_eq!(results[1].url, "https://example.com/book");
+        assert_eq!(results[1].title, "The Rust Book");
+    }
+
+    #[test]
+    fn extract_page_form_picks_the_form_for_the_requested_page() {
+        let form = extract_page_form(SEARCH_FIXTURE, 3).unwrap();
+
+        assert!(form.contains(&("sc".to_string(), "nexttoken".to_string())));
+        assert!(form.contains(&("page".to_string(), "3".to_string())));
+        assert!(form.contains(&("cat".to_string(), "web".to_string())));
+        assert_eq!(form_page(&form), 3);
+    }
+
+    #[test]
+    fn extract_page_form_returns_none_without_a_matching_page() {
+        assert!(extract_page_form(SEARCH_FIXTURE, 7).is_none());
+    }
+
+    #[test]
+    fn extract_page_form_ignores_forms_without_an_sc_token() {
+        let html = r#"
+            <form action="/sp/search" method="post">
+                <input type="hidden" name="query" value="rust">
+                <input type="hidden" name="page" value="2">
+            </form>
+        "#;
+        assert!(extract_page_form(html, 2).is_none());
+    }
+
+    #[test]
+    fn record_page_stores_the_next_form_under_the_offset_it_leads_to() {
+        let query = "record_page unit test query";
+        let results = record_page(query, 20, 2, SEARCH_FIXTURE).unwrap();
+
+        assert_eq!(results.len(), 2);
+        let stored = NEXT_PAGE_FORMS.get(&(query.to_string(), 22)).unwrap();
+        assert_eq!(form_page(&stored), 3);
+    }
+
+    use crate::fixtures::cached_html;
+
+    #[ignore]
+    #[tokio::test]
+    async fn test_startpage_search_live() {
+        let html = cached_html(
+            "startpage/search_p0.html",
+            &build_first_page_url("rust async", &SearchParams::default()),
+            looks_like_search_results,
+        )
+        .await;
+        let results = parse_search_response(&html).unwrap();
+        assert!(!results.is_empty());
+        assert!(
+            extract_page_form(&html, 2).is_some(),
+            "first page should carry a next-page form with an `sc` token"
+        );
+    }
+}